@@ -0,0 +1,508 @@
+use crate::commands::imaging_sessions::{
+    ImagingSessionBase, ImagingSessionCalibration, ImagingSessionDetails, ImagingSessionEdit,
+    ImagingSessionEquipment, ImagingSessionGeneral, ImagingSessionWeather,
+};
+use crate::duplicates::DuplicateFrame;
+use crate::image::{read_metadata, ImageMetadata};
+use crate::models::equipment::{EquipmentItem, EquipmentList};
+use crate::models::imaging_frames::calibration_type::CalibrationType;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileError {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+// a file whose header value differs from the value most of the selected files share
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Disagreement {
+    pub path: PathBuf,
+    pub field: String,
+    pub value: Option<String>,
+    pub majority: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyzedLightFrames {
+    pub session: ImagingSessionEdit,
+    pub total_subs: usize,
+    pub missing: Vec<String>,
+    pub disagreements: Vec<Disagreement>,
+    pub errors: Vec<FileError>,
+    pub duplicates: Vec<DuplicateFrame>,
+}
+
+pub fn read_frames(frames: &Vec<PathBuf>) -> (Vec<(PathBuf, ImageMetadata)>, Vec<FileError>) {
+    let mut metadata = vec![];
+    let mut errors = vec![];
+
+    for path in frames {
+        match read_metadata(path) {
+            Ok(data) => metadata.push((path.clone(), data)),
+            Err(e) => errors.push(FileError {
+                path: path.clone(),
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    (metadata, errors)
+}
+
+pub fn analyze_light_frames(
+    frames: &Vec<PathBuf>,
+    equipment_list: &EquipmentList,
+    duplicates: Vec<DuplicateFrame>,
+    timezone: Option<&Tz>,
+) -> AnalyzedLightFrames {
+    let (metadata, errors) = read_frames(frames);
+    let mut disagreements = vec![];
+
+    let mut field = |name: &str, value: &dyn Fn(&ImageMetadata) -> Option<String>| {
+        majority(&metadata, name, value, &mut disagreements)
+    };
+
+    let target = field("target", &|m| m.target.clone());
+    let gain = field("gain", &|m| m.gain.map(|v| v.to_string()));
+    let offset = field("offset", &|m| m.offset.map(|v| v.to_string()));
+    let sub_length = field("sub_length", &|m| m.exposure_time.map(|v| v.to_string()));
+    // sensor temperatures jitter around the set point, so they are compared in whole degrees
+    let camera_temp = field("camera_temp", &|m| {
+        m.camera_temp.map(|v| format!("{:.0}", v))
+    });
+    let filter = field("filter", &|m| m.filter.clone());
+    let camera = field("camera", &|m| m.camera.clone());
+    let telescope = field("telescope", &|m| m.telescope.clone());
+
+    // the session starts with its first sub
+    let date = metadata.iter().filter_map(|(_, m)| m.date_utc(timezone)).min();
+
+    let gain = gain.and_then(|v| v.parse::<u32>().ok());
+    let sub_length = sub_length.and_then(|v| v.parse::<f64>().ok());
+
+    let mut missing = vec![];
+    for (name, found) in [
+        ("date", date.is_some()),
+        ("target", target.is_some()),
+        ("gain", gain.is_some()),
+        ("sub_length", sub_length.is_some()),
+    ] {
+        if !found {
+            missing.push(name.to_string());
+        }
+    }
+
+    let session = ImagingSessionEdit {
+        base: ImagingSessionBase {
+            id: Uuid::new_v4(),
+            frames: frames.clone(),
+        },
+        general: ImagingSessionGeneral {
+            date: date.unwrap_or_else(Utc::now),
+            target: target.unwrap_or_default(),
+            location_id: Uuid::nil(),
+        },
+        details: ImagingSessionDetails {
+            gain: gain.unwrap_or_default(),
+            sub_length: sub_length.unwrap_or_default(),
+            offset: offset.and_then(|v| v.parse().ok()),
+            camera_temp: camera_temp.and_then(|v| v.parse().ok()),
+            notes: None,
+        },
+        equipment: ImagingSessionEquipment {
+            camera_id: camera
+                .and_then(|name| match_equipment(&equipment_list.cameras, &name))
+                .unwrap_or_default(),
+            telescope_id: telescope
+                .and_then(|name| match_equipment(&equipment_list.telescopes, &name))
+                .unwrap_or_default(),
+            mount_id: Uuid::nil(),
+            filter_id: filter.and_then(|name| match_equipment(&equipment_list.filters, &name)),
+            flattener_id: None,
+        },
+        weather: ImagingSessionWeather {
+            outside_temp: None,
+            average_seeing: None,
+            average_cloud_cover: None,
+        },
+        calibration: ImagingSessionCalibration {
+            dark_frame_list_id: None,
+            bias_frame_list_id: None,
+            flat_frame_list_id: None,
+            flat_frames_to_classify: vec![],
+            dark_frames_to_classify: vec![],
+            dark_flat_frame_list_id: None,
+            dark_flat_frames_to_classify: vec![],
+        },
+    };
+
+    AnalyzedLightFrames {
+        // files that couldn't be read aren't subs of the session
+        total_subs: frames.len() - errors.len(),
+        session,
+        missing,
+        disagreements,
+        errors,
+        duplicates,
+    }
+}
+
+// files sharing type, gain, exposure, temperature and binning, each group is one calibration set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationFrameGroup {
+    pub calibration_type: CalibrationType,
+    pub gain: Option<i32>,
+    pub sub_length: Option<f64>,
+    pub camera_temp: Option<f64>,
+    pub binning: Option<u32>,
+    pub frames: Vec<PathBuf>,
+}
+
+// the values of the largest group are returned at the top level to fill in the editor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyzedCalibrationFrames {
+    pub calibration_type: CalibrationType,
+    pub gain: Option<i32>,
+    pub sub_length: Option<f64>,
+    pub camera_temp: Option<f64>,
+    pub total_subs: usize,
+    pub groups: Vec<CalibrationFrameGroup>,
+    pub split: bool,
+    // files whose header couldn't be read, they aren't counted in total_subs
+    pub unreadable: Vec<FileError>,
+    // files that were read but aren't dark, dark flat or bias frames
+    pub errors: Vec<FileError>,
+    pub duplicates: Vec<DuplicateFrame>,
+    pub message: Option<String>,
+}
+
+pub fn analyze_calibration_frames(
+    frames: &Vec<PathBuf>,
+    duplicates: Vec<DuplicateFrame>,
+) -> AnalyzedCalibrationFrames {
+    let (metadata, unreadable) = read_frames(frames);
+    let mut errors = vec![];
+    let mut groups: Vec<CalibrationFrameGroup> = vec![];
+
+    for (path, m) in metadata {
+        let calibration_type = match calibration_type(&m) {
+            Ok(calibration_type) => calibration_type,
+            Err(reason) => {
+                errors.push(FileError { path, reason });
+                continue;
+            }
+        };
+
+        // the set point is stable, the measured sensor temperature is compared in whole degrees
+        let camera_temp = m.set_temp.or(m.camera_temp).map(|t| t.round());
+        let sub_length = match calibration_type {
+            CalibrationType::BIAS => None,
+            _ => m.exposure_time,
+        };
+
+        let group = groups.iter_mut().find(|g| {
+            g.calibration_type == calibration_type
+                && g.gain == m.gain
+                && g.sub_length == sub_length
+                && g.camera_temp == camera_temp
+                && g.binning == m.binning
+        });
+
+        match group {
+            Some(group) => group.frames.push(path),
+            None => groups.push(CalibrationFrameGroup {
+                calibration_type,
+                gain: m.gain,
+                sub_length,
+                camera_temp,
+                binning: m.binning,
+                frames: vec![path],
+            }),
+        }
+    }
+
+    // stable sort, groups of the same size stay in the order of the selection
+    groups.sort_by(|a, b| b.frames.len().cmp(&a.frames.len()));
+
+    let mut messages = vec![];
+    if groups.len() > 1 {
+        messages.push(format!(
+            "The selection contains {} different calibration sets and should be split.",
+            groups.len()
+        ));
+    }
+    if !unreadable.is_empty() {
+        let names: Vec<String> = unreadable
+            .iter()
+            .map(|error| {
+                error
+                    .path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        messages.push(format!(
+            "{} file(s) couldn't be read and are left out: {}.",
+            unreadable.len(),
+            names.join(", ")
+        ));
+    }
+    if !errors.is_empty() {
+        messages.push(format!("{} file(s) couldn't be analyzed.", errors.len()));
+    }
+    if !duplicates.is_empty() {
+        messages.push(format!(
+            "{} file(s) are already in the library.",
+            duplicates.len()
+        ));
+    }
+
+    let main = groups.first();
+    if let Some(group) = main {
+        let mut missing = vec![];
+        if group.gain.is_none() {
+            missing.push("gain");
+        }
+        if group.sub_length.is_none() && group.calibration_type != CalibrationType::BIAS {
+            missing.push("exposure time");
+        }
+        if group.camera_temp.is_none()
+            && matches!(
+                group.calibration_type,
+                CalibrationType::DARK | CalibrationType::DARKFLAT
+            )
+        {
+            missing.push("camera temperature");
+        }
+        if !missing.is_empty() {
+            messages.push(format!(
+                "The headers don't record the {}, it is left empty.",
+                missing.join(" and ")
+            ));
+        }
+    }
+
+    AnalyzedCalibrationFrames {
+        calibration_type: main.map_or(CalibrationType::DARK, |g| g.calibration_type.clone()),
+        gain: main.and_then(|g| g.gain),
+        sub_length: main.and_then(|g| g.sub_length),
+        camera_temp: main.and_then(|g| g.camera_temp),
+        total_subs: groups.iter().map(|g| g.frames.len()).sum(),
+        split: groups.len() > 1,
+        groups,
+        unreadable,
+        errors,
+        duplicates,
+        message: if messages.is_empty() {
+            None
+        } else {
+            Some(messages.join(" "))
+        },
+    }
+}
+
+// the gain and temperature most of the dark frames share, None if no header contains them
+pub fn dark_frame_values(frames: &Vec<PathBuf>) -> (Option<u32>, Option<f64>) {
+    let (metadata, _) = read_frames(frames);
+    let mut disagreements = vec![];
+
+    let mut field = |name: &str, value: &dyn Fn(&ImageMetadata) -> Option<String>| {
+        majority(&metadata, name, value, &mut disagreements)
+    };
+
+    let gain = field("gain", &|m| m.gain.map(|v| v.to_string()));
+    // the set point is stable, the measured sensor temperature is compared in whole degrees
+    let camera_temp = field("camera_temp", &|m| {
+        m.set_temp.or(m.camera_temp).map(|v| format!("{:.0}", v))
+    });
+    // the first value of the majority keeps its precision
+    let camera_temp = camera_temp.and_then(|majority| {
+        metadata
+            .iter()
+            .filter_map(|(_, m)| m.set_temp.or(m.camera_temp))
+            .find(|v| format!("{:.0}", v) == majority)
+    });
+
+    (gain.and_then(|v| v.parse::<u32>().ok()), camera_temp)
+}
+
+// a set is dated by its earliest file
+pub fn capture_date(frames: &[PathBuf], timezone: Option<&Tz>) -> Option<DateTime<Utc>> {
+    frames
+        .iter()
+        .filter_map(|path| read_metadata(path).ok()?.date_utc(timezone))
+        .min()
+}
+
+// the frame type keyword is preferred, files without one are told apart by their exposure
+fn calibration_type(metadata: &ImageMetadata) -> Result<CalibrationType, String> {
+    if let Some(frame_type) = &metadata.frame_type {
+        let lowercase = frame_type.to_lowercase();
+        if lowercase.contains("bias") || lowercase.contains("offset") {
+            return Ok(CalibrationType::BIAS);
+        }
+        if lowercase.contains("dark") && lowercase.contains("flat") {
+            return Ok(CalibrationType::DARKFLAT);
+        }
+        if lowercase.contains("dark") {
+            return Ok(CalibrationType::DARK);
+        }
+        if lowercase.contains("light") || lowercase.contains("flat") {
+            return Err(format!(
+                "File is marked as '{}', not as a dark, dark flat or bias frame.",
+                frame_type
+            ));
+        }
+    }
+
+    match metadata.exposure_time {
+        Some(exposure_time) if exposure_time < 0.001 => Ok(CalibrationType::BIAS),
+        Some(_) => Ok(CalibrationType::DARK),
+        None => Err("Couldn't get sub length.".to_string()),
+    }
+}
+
+// returns the most common value, files with another or without a value are added to disagreements
+pub fn majority(
+    metadata: &[(PathBuf, ImageMetadata)],
+    field: &str,
+    value: &dyn Fn(&ImageMetadata) -> Option<String>,
+    disagreements: &mut Vec<Disagreement>,
+) -> Option<String> {
+    let values: Vec<(&PathBuf, Option<String>)> =
+        metadata.iter().map(|(path, m)| (path, value(m))).collect();
+
+    // counted in order of appearance, so ties are resolved by the first file
+    let mut counts: Vec<(&String, usize)> = vec![];
+    for value in values.iter().filter_map(|(_, v)| v.as_ref()) {
+        match counts.iter_mut().find(|(v, _)| *v == value) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((value, 1)),
+        }
+    }
+
+    let mut result: Option<(&String, usize)> = None;
+    for (value, count) in counts {
+        if result.map_or(true, |(_, max)| count > max) {
+            result = Some((value, count));
+        }
+    }
+    let result = result.map(|(value, _)| value.clone())?;
+
+    for (path, value) in values {
+        if value.as_ref() != Some(&result) {
+            disagreements.push(Disagreement {
+                path: path.clone(),
+                field: field.to_string(),
+                value,
+                majority: result.clone(),
+            });
+        }
+    }
+
+    Some(result)
+}
+
+// header names rarely match exactly, so an item whose name is part of the header value is accepted too
+pub fn match_equipment<T: EquipmentItem>(items: &HashMap<Uuid, T>, name: &str) -> Option<Uuid> {
+    let name = name.trim().to_lowercase();
+
+    let exact = items.iter().find(|(_, item)| {
+        item.view_name().trim().to_lowercase() == name || item.name().trim().to_lowercase() == name
+    });
+    if let Some((id, _)) = exact {
+        return Some(*id);
+    }
+
+    items
+        .iter()
+        .map(|(id, item)| (id, item.name().trim().to_lowercase()))
+        .filter(|(_, item_name)| !item_name.is_empty() && name.contains(item_name.as_str()))
+        .max_by_key(|(_, item_name)| item_name.len())
+        .map(|(id, _)| *id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDir;
+
+    // writes the cards as a single FITS header block
+    fn fits_file(dir: &TempDir, name: &str, cards: &[&str]) -> PathBuf {
+        let mut header = String::new();
+        for card in ["SIMPLE  =                    T"]
+            .iter()
+            .chain(cards)
+            .chain(&["END"])
+        {
+            header.push_str(&format!("{:<80}", card));
+        }
+        let mut bytes = header.into_bytes();
+        bytes.resize(2880, b' ');
+
+        dir.write(name, bytes)
+    }
+
+    #[test]
+    fn dark_flats_leave_missing_values_empty() {
+        let dir = TempDir::new();
+        let frames: Vec<PathBuf> = (1..=3)
+            .map(|i| {
+                fits_file(
+                    &dir,
+                    &format!("DarkFlat_00{}.fits", i),
+                    &[
+                        "IMAGETYP= 'Dark Flat'",
+                        "EXPTIME =                  1.5",
+                        "GAIN    =                  120",
+                    ],
+                )
+            })
+            .collect();
+        let analyzed = analyze_calibration_frames(&frames, vec![]);
+
+        assert_eq!(analyzed.calibration_type, CalibrationType::DARKFLAT);
+        assert_eq!(analyzed.gain, Some(120));
+        assert_eq!(analyzed.sub_length, Some(1.5));
+        assert_eq!(analyzed.camera_temp, None);
+        assert!(analyzed.message.unwrap().contains("camera temperature"));
+    }
+
+    #[test]
+    fn unreadable_files_are_not_counted() {
+        let dir = TempDir::new();
+        let frames = vec![
+            fits_file(
+                &dir,
+                "Dark_001.fits",
+                &["IMAGETYP= 'Dark'", "EXPTIME = 300"],
+            ),
+            fits_file(
+                &dir,
+                "Dark_002.fits",
+                &["IMAGETYP= 'Dark'", "EXPTIME = 300"],
+            ),
+            dir.write("Dark_003.fits", "not a FITS file"),
+            fits_file(
+                &dir,
+                "Light_001.fits",
+                &["IMAGETYP= 'Light'", "EXPTIME = 300"],
+            ),
+        ];
+        let analyzed = analyze_calibration_frames(&frames, vec![]);
+
+        assert_eq!(analyzed.total_subs, 2);
+        assert_eq!(analyzed.unreadable.len(), 1);
+        assert_eq!(analyzed.unreadable[0].path, frames[2]);
+        assert_eq!(analyzed.errors.len(), 1);
+        assert!(analyzed.message.unwrap().contains("Dark_003.fits"));
+    }
+}
@@ -0,0 +1,317 @@
+use crate::models::location::Location;
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+// like the moon, the sun follows the low precision formulae of the Astronomical Almanac
+
+// altitudes of the sun's center, the horizon one includes refraction and the solar radius
+const SUNSET_ALTITUDE: f64 = -0.833;
+const CIVIL_ALTITUDE: f64 = -6.0;
+const NAUTICAL_ALTITUDE: f64 = -12.0;
+const ASTRONOMICAL_ALTITUDE: f64 = -18.0;
+
+const NIGHT_SAMPLE_MINUTES: i64 = 1;
+const CURVE_SAMPLE_MINUTES: i64 = 10;
+
+// a dusk without a matching dawn (or the other way round) happens close to the polar circles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Twilight {
+    pub dusk: Option<DateTime<Utc>>,
+    pub dawn: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NightTimes {
+    pub night_of: NaiveDate,
+    pub sun: Twilight,
+    pub civil: Twilight,
+    pub nautical: Twilight,
+    pub astronomical: Twilight,
+    // hours the sun spends below the astronomical twilight altitude
+    pub dark_hours: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AltitudePoint {
+    pub date: DateTime<Utc>,
+    pub altitude: f64,
+    pub azimuth: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetVisibility {
+    // crossings of the location's horizon profile, or of 0° if it has none
+    pub rise: Option<DateTime<Utc>>,
+    pub transit: DateTime<Utc>,
+    pub set: Option<DateTime<Utc>>,
+    pub max_altitude: f64,
+    // hours the target is above the horizon during astronomical darkness
+    pub observable_hours: f64,
+    pub altitude_curve: Vec<AltitudePoint>,
+}
+
+// the night runs from local noon of the given date to local noon of the next day
+pub fn night_times(location: &Location, night_of: NaiveDate) -> NightTimes {
+    let samples = sample_night(location, night_of, NIGHT_SAMPLE_MINUTES, |date| {
+        let (ra, dec) = sun_position(julian_day(date));
+        horizontal(ra, dec, julian_day(date), location).0
+    });
+
+    let dark_samples = samples
+        .iter()
+        .filter(|(_, altitude)| *altitude < ASTRONOMICAL_ALTITUDE)
+        .count();
+
+    NightTimes {
+        night_of,
+        sun: twilight(&samples, SUNSET_ALTITUDE),
+        civil: twilight(&samples, CIVIL_ALTITUDE),
+        nautical: twilight(&samples, NAUTICAL_ALTITUDE),
+        astronomical: twilight(&samples, ASTRONOMICAL_ALTITUDE),
+        dark_hours: (dark_samples as i64 * NIGHT_SAMPLE_MINUTES) as f64 / 60.0,
+    }
+}
+
+pub fn target_visibility(
+    location: &Location,
+    night_of: NaiveDate,
+    ra: f64,
+    dec: f64,
+) -> TargetVisibility {
+    let night = night_times(location, night_of);
+    let curve: Vec<AltitudePoint> =
+        sample_night(location, night_of, CURVE_SAMPLE_MINUTES, |date| {
+            horizontal(ra, dec, julian_day(date), location)
+        })
+        .into_iter()
+        .map(|(date, (altitude, azimuth))| AltitudePoint {
+            date,
+            altitude,
+            azimuth,
+        })
+        .collect();
+
+    // the target is visible where it stands above the obstructions towards its azimuth
+    let clearance: Vec<(DateTime<Utc>, f64)> = curve
+        .iter()
+        .map(|point| {
+            let horizon = horizon_altitude(location, point.azimuth);
+            (point.date, point.altitude - horizon)
+        })
+        .collect();
+
+    let transit = curve
+        .iter()
+        .max_by(|a, b| a.altitude.total_cmp(&b.altitude))
+        .expect("a night always has samples");
+
+    let dark = |date: &DateTime<Utc>| match (night.astronomical.dusk, night.astronomical.dawn) {
+        (Some(dusk), Some(dawn)) => *date >= dusk && *date <= dawn,
+        (Some(dusk), None) => *date >= dusk,
+        (None, Some(dawn)) => *date <= dawn,
+        // either the sun never sets that deep or it never rises above it
+        (None, None) => night.dark_hours > 0.0,
+    };
+    let observable_samples = clearance
+        .iter()
+        .filter(|(date, clearance)| *clearance > 0.0 && dark(date))
+        .count();
+
+    TargetVisibility {
+        rise: crossing(&clearance, 0.0, true),
+        transit: transit.date,
+        set: crossing(&clearance, 0.0, false),
+        max_altitude: transit.altitude,
+        observable_hours: (observable_samples as i64 * CURVE_SAMPLE_MINUTES) as f64 / 60.0,
+        altitude_curve: curve,
+    }
+}
+
+fn sample_night<T>(
+    location: &Location,
+    night_of: NaiveDate,
+    minutes: i64,
+    value: impl Fn(&DateTime<Utc>) -> T,
+) -> Vec<(DateTime<Utc>, T)> {
+    let noon = night_of.and_time(NaiveTime::from_hms_opt(12, 0, 0).unwrap());
+    let start = location
+        .tz()
+        .from_local_datetime(&noon)
+        .earliest()
+        .map_or_else(
+            || Utc.from_utc_datetime(&noon),
+            |date| date.with_timezone(&Utc),
+        );
+    let end = start + Duration::days(1);
+
+    let mut samples = vec![];
+    let mut date = start;
+    while date <= end {
+        samples.push((date, value(&date)));
+        date += Duration::minutes(minutes);
+    }
+    samples
+}
+
+fn twilight(samples: &[(DateTime<Utc>, f64)], altitude: f64) -> Twilight {
+    Twilight {
+        dusk: crossing(samples, altitude, false),
+        dawn: crossing(samples, altitude, true),
+    }
+}
+
+// the first time the value passes the threshold in the given direction, interpolated between samples
+fn crossing(
+    samples: &[(DateTime<Utc>, f64)],
+    threshold: f64,
+    rising: bool,
+) -> Option<DateTime<Utc>> {
+    samples.windows(2).find_map(|pair| {
+        let (before, a) = pair[0];
+        let (after, b) = pair[1];
+        let crosses = if rising {
+            a < threshold && b >= threshold
+        } else {
+            a >= threshold && b < threshold
+        };
+        if !crosses {
+            return None;
+        }
+
+        let fraction = (threshold - a) / (b - a);
+        let step = (after - before).num_milliseconds() as f64;
+        Some(before + Duration::milliseconds((step * fraction) as i64))
+    })
+}
+
+// the horizon profile is interpolated linearly and wraps around north
+fn horizon_altitude(location: &Location, azimuth: f64) -> f64 {
+    let horizon = &location.horizon;
+    if horizon.is_empty() {
+        return 0.0;
+    }
+
+    let after = horizon.iter().position(|point| point.azimuth >= azimuth);
+    let (a, b) = match after {
+        Some(0) | None => (&horizon[horizon.len() - 1], &horizon[0]),
+        Some(index) => (&horizon[index - 1], &horizon[index]),
+    };
+
+    let span = (b.azimuth - a.azimuth).rem_euclid(360.0);
+    if span == 0.0 {
+        return a.altitude;
+    }
+    let offset = (azimuth - a.azimuth).rem_euclid(360.0);
+    a.altitude + (b.altitude - a.altitude) * offset / span
+}
+
+pub(crate) fn julian_day(date: &DateTime<Utc>) -> f64 {
+    date.timestamp_millis() as f64 / 86_400_000.0 + 2_440_587.5
+}
+
+pub(crate) fn sin(degrees: f64) -> f64 {
+    degrees.to_radians().sin()
+}
+
+pub(crate) fn cos(degrees: f64) -> f64 {
+    degrees.to_radians().cos()
+}
+
+pub(crate) fn obliquity(jd: f64) -> f64 {
+    23.439 - 0.000_000_4 * (jd - 2_451_545.0)
+}
+
+pub(crate) fn sun_longitude(jd: f64) -> f64 {
+    let n = jd - 2_451_545.0;
+    let mean_longitude = 280.460 + 0.985_647_4 * n;
+    let mean_anomaly = 357.528 + 0.985_600_3 * n;
+
+    (mean_longitude + 1.915 * sin(mean_anomaly) + 0.020 * sin(2.0 * mean_anomaly)).rem_euclid(360.0)
+}
+
+fn sun_position(jd: f64) -> (f64, f64) {
+    let longitude = sun_longitude(jd);
+    let epsilon = obliquity(jd);
+
+    let ra = (cos(epsilon) * sin(longitude))
+        .atan2(cos(longitude))
+        .to_degrees()
+        .rem_euclid(360.0);
+    let dec = (sin(epsilon) * sin(longitude)).asin().to_degrees();
+
+    (ra, dec)
+}
+
+// altitude and azimuth (from north over east) of equatorial coordinates
+pub(crate) fn horizontal(ra: f64, dec: f64, jd: f64, location: &Location) -> (f64, f64) {
+    horizontal_at(ra, dec, jd, location.latitude, location.longitude)
+}
+
+pub(crate) fn horizontal_at(
+    ra: f64,
+    dec: f64,
+    jd: f64,
+    latitude: f64,
+    longitude: f64,
+) -> (f64, f64) {
+    let sidereal_time = 280.460_618_37 + 360.985_647_366_29 * (jd - 2_451_545.0) + longitude;
+    let hour_angle = sidereal_time - ra;
+
+    let altitude = (sin(latitude) * sin(dec) + cos(latitude) * cos(dec) * cos(hour_angle))
+        .clamp(-1.0, 1.0)
+        .asin()
+        .to_degrees();
+    let azimuth = (-cos(dec) * sin(hour_angle))
+        .atan2(sin(dec) * cos(latitude) - cos(dec) * sin(latitude) * cos(hour_angle))
+        .to_degrees()
+        .rem_euclid(360.0);
+
+    (altitude, azimuth)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::location::HorizonPoint;
+    use uuid::Uuid;
+
+    fn location(horizon: &[(f64, f64)]) -> Location {
+        let mut location = Location::new(Uuid::new_v4(), "Backyard".to_string());
+        location.horizon = horizon
+            .iter()
+            .map(|&(azimuth, altitude)| HorizonPoint { azimuth, altitude })
+            .collect();
+        location
+    }
+
+    #[test]
+    fn without_a_profile_the_horizon_is_flat() {
+        assert_eq!(horizon_altitude(&location(&[]), 123.0), 0.0);
+    }
+
+    #[test]
+    fn interpolates_between_points() {
+        let location = location(&[(90.0, 10.0), (180.0, 30.0), (270.0, 20.0)]);
+
+        assert_eq!(horizon_altitude(&location, 135.0), 20.0);
+        assert_eq!(horizon_altitude(&location, 180.0), 30.0);
+        assert_eq!(horizon_altitude(&location, 225.0), 25.0);
+    }
+
+    #[test]
+    fn wraps_around_north() {
+        let location = location(&[(90.0, 10.0), (180.0, 30.0), (270.0, 20.0)]);
+
+        assert_eq!(horizon_altitude(&location, 0.0), 15.0);
+        assert_eq!(horizon_altitude(&location, 45.0), 12.5);
+        assert_eq!(horizon_altitude(&location, 315.0), 17.5);
+    }
+
+    #[test]
+    fn a_single_point_is_a_flat_horizon() {
+        let location = location(&[(200.0, 15.0)]);
+
+        assert_eq!(horizon_altitude(&location, 10.0), 15.0);
+        assert_eq!(horizon_altitude(&location, 300.0), 15.0);
+    }
+}
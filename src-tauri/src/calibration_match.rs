@@ -0,0 +1,482 @@
+use crate::image::read_metadata;
+use crate::models::database::Database;
+use crate::models::equipment::{Camera, EquipmentItem};
+use crate::models::imaging_frames::bias_frame::BiasFrame;
+use crate::models::imaging_frames::calibration_type::CalibrationType;
+use crate::models::imaging_frames::dark_flat_frame::DarkFlatFrame;
+use crate::models::imaging_frames::dark_frame::DarkFrame;
+use crate::models::imaging_frames::flat_frame::FlatFrame;
+use crate::models::imaging_frames::imaging_frame::ClassifiableFrame;
+use crate::models::imaging_frames::light_frame::LightFrame;
+use crate::models::imaging_session::ImagingSession;
+use crate::models::preferences::{CalibrationTolerances, CalibrationValidity};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchCheck {
+    pub criterion: String,
+    pub passed: bool,
+    pub reason: String,
+}
+
+// a library set ranked against a light frame, a lower score is a closer match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationMatch {
+    pub id: Uuid,
+    pub calibration_type: CalibrationType,
+    pub camera: String,
+    pub gain: u32,
+    pub sub_length: Option<f64>,
+    pub camera_temp: Option<f64>,
+    pub total_subs: u32,
+    pub date: Option<DateTime<Utc>>,
+    pub matches: bool,
+    pub score: f64,
+    pub checks: Vec<MatchCheck>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationMatches {
+    pub dark_frames: Vec<CalibrationMatch>,
+    pub bias_frames: Vec<CalibrationMatch>,
+    pub flat_frames: Vec<CalibrationMatch>,
+    pub dark_flat_frames: Vec<CalibrationMatch>,
+}
+
+// the values of a calibration set that take part in the matching
+pub struct CalibrationCandidate {
+    pub id: Uuid,
+    pub calibration_type: CalibrationType,
+    pub camera_id: Uuid,
+    pub gain: u32,
+    pub offset: Option<i32>,
+    pub sub_length: Option<f64>,
+    pub camera_temp: Option<f64>,
+    pub total_subs: u32,
+    pub capture_date: Option<DateTime<Utc>>,
+    pub telescope_id: Option<Uuid>,
+    pub flattener_id: Option<Uuid>,
+    pub filter_id: Option<Uuid>,
+    pub first_frame: Option<PathBuf>,
+}
+
+impl CalibrationCandidate {
+    // the offset isn't stored with the set, it and a missing capture date are read from its first file
+    pub fn read_header(&mut self, root: &PathBuf) {
+        let metadata = self
+            .first_frame
+            .as_ref()
+            .and_then(|path| read_metadata(&root.join(path)).ok());
+
+        if let Some(metadata) = metadata {
+            self.offset = self.offset.or(metadata.offset);
+            self.capture_date = self
+                .capture_date
+                .or(metadata.date.map(|date| date.and_utc()));
+        }
+    }
+}
+
+impl From<&DarkFrame> for CalibrationCandidate {
+    fn from(frame: &DarkFrame) -> Self {
+        CalibrationCandidate {
+            id: frame.id,
+            calibration_type: CalibrationType::DARK,
+            camera_id: frame.camera_id,
+            gain: frame.gain,
+            offset: None,
+            sub_length: Some(frame.sub_length),
+            camera_temp: Some(frame.camera_temp),
+            total_subs: frame.total_subs(),
+            capture_date: frame.capture_date,
+            telescope_id: None,
+            flattener_id: None,
+            filter_id: None,
+            first_frame: frame.frames_classified.first().cloned(),
+        }
+    }
+}
+
+impl From<&BiasFrame> for CalibrationCandidate {
+    fn from(frame: &BiasFrame) -> Self {
+        CalibrationCandidate {
+            id: frame.id,
+            calibration_type: CalibrationType::BIAS,
+            camera_id: frame.camera_id,
+            gain: frame.gain,
+            offset: None,
+            sub_length: None,
+            camera_temp: None,
+            total_subs: frame.total_subs(),
+            capture_date: frame.capture_date,
+            telescope_id: None,
+            flattener_id: None,
+            filter_id: None,
+            first_frame: frame.frames_classified.first().cloned(),
+        }
+    }
+}
+
+impl From<&FlatFrame> for CalibrationCandidate {
+    fn from(frame: &FlatFrame) -> Self {
+        CalibrationCandidate {
+            id: frame.id,
+            calibration_type: CalibrationType::FLAT,
+            camera_id: frame.camera_id,
+            gain: frame.gain,
+            offset: None,
+            sub_length: None,
+            camera_temp: None,
+            total_subs: frame.total_subs(),
+            capture_date: frame.capture_date,
+            telescope_id: frame.telescope_id,
+            flattener_id: frame.flattener_id,
+            filter_id: frame.filter_id,
+            first_frame: frame.frames_classified.first().cloned(),
+        }
+    }
+}
+
+// the exposure of dark flats follows the flats, so it isn't compared with the light frames
+impl From<&DarkFlatFrame> for CalibrationCandidate {
+    fn from(frame: &DarkFlatFrame) -> Self {
+        CalibrationCandidate {
+            id: frame.id,
+            calibration_type: CalibrationType::DARKFLAT,
+            camera_id: frame.camera_id,
+            gain: frame.gain,
+            offset: None,
+            sub_length: None,
+            camera_temp: None,
+            total_subs: frame.total_subs(),
+            capture_date: frame.capture_date,
+            telescope_id: None,
+            flattener_id: None,
+            filter_id: None,
+            first_frame: frame.frames_classified.first().cloned(),
+        }
+    }
+}
+
+// matching sets come first, then the closest ones and for equal scores the larger ones
+pub fn rank_candidates(
+    candidates: &mut Vec<CalibrationCandidate>,
+    light_frame: &LightFrame,
+    root: &PathBuf,
+    cameras: &HashMap<Uuid, Camera>,
+    tolerances: &CalibrationTolerances,
+    validity: &CalibrationValidity,
+) -> Vec<CalibrationMatch> {
+    let mut matches: Vec<CalibrationMatch> = candidates
+        .iter_mut()
+        .map(|candidate| {
+            candidate.read_header(root);
+            match_candidate(candidate, light_frame, cameras, tolerances, validity)
+        })
+        .collect();
+
+    matches.sort_by(|a, b| {
+        b.matches
+            .cmp(&a.matches)
+            .then(a.score.total_cmp(&b.score))
+            .then(b.total_subs.cmp(&a.total_subs))
+    });
+
+    matches
+}
+
+// checks the set against the light frame, values the candidate doesn't know aren't compared
+pub fn match_candidate(
+    candidate: &CalibrationCandidate,
+    light_frame: &LightFrame,
+    cameras: &HashMap<Uuid, Camera>,
+    tolerances: &CalibrationTolerances,
+    validity: &CalibrationValidity,
+) -> CalibrationMatch {
+    let camera_name = |id: &Uuid| {
+        cameras
+            .get(id)
+            .map_or("N/A".to_string(), |camera| camera.view_name())
+    };
+
+    let mut checks = vec![];
+    let mut score = 0.0;
+    let mut check = |criterion: &str, passed: bool, reason: String| {
+        checks.push(MatchCheck {
+            criterion: criterion.to_string(),
+            passed,
+            reason,
+        });
+    };
+
+    let camera = camera_name(&candidate.camera_id);
+    if candidate.camera_id == light_frame.camera_id {
+        check(
+            "camera",
+            true,
+            format!("Taken with the same camera ({}).", camera),
+        );
+    } else {
+        check(
+            "camera",
+            false,
+            format!(
+                "Taken with {}, the session uses {}.",
+                camera,
+                camera_name(&light_frame.camera_id)
+            ),
+        );
+    }
+
+    let gain_difference = candidate.gain.abs_diff(light_frame.gain);
+    score += gain_difference as f64;
+    check(
+        "gain",
+        gain_difference <= tolerances.gain,
+        if gain_difference == 0 {
+            format!("Gain {} is the same.", candidate.gain)
+        } else {
+            format!(
+                "Gain {} differs by {} from {} (tolerance {}).",
+                candidate.gain, gain_difference, light_frame.gain, tolerances.gain
+            )
+        },
+    );
+
+    match (candidate.offset, light_frame.offset) {
+        (Some(offset), Some(light_offset)) => {
+            let offset_difference = (offset as i64 - light_offset as i64).unsigned_abs();
+            score += offset_difference as f64;
+            check(
+                "offset",
+                offset_difference <= tolerances.offset as u64,
+                if offset_difference == 0 {
+                    format!("Offset {} is the same.", offset)
+                } else {
+                    format!(
+                        "Offset {} differs by {} from {} (tolerance {}).",
+                        offset, offset_difference, light_offset, tolerances.offset
+                    )
+                },
+            );
+        }
+        _ => check(
+            "offset",
+            true,
+            "Offset isn't known for both, it wasn't compared.".to_string(),
+        ),
+    }
+
+    if let Some(sub_length) = candidate.sub_length {
+        let percent = if light_frame.sub_length > 0.0 {
+            (sub_length - light_frame.sub_length).abs() / light_frame.sub_length * 100.0
+        } else {
+            0.0
+        };
+        score += percent;
+        check(
+            "sub_length",
+            percent <= tolerances.sub_length_percent,
+            if percent == 0.0 {
+                format!("Sub length {}s is the same.", sub_length)
+            } else {
+                format!(
+                    "Sub length {}s differs by {:.1}% from {}s (tolerance {}%).",
+                    sub_length, percent, light_frame.sub_length, tolerances.sub_length_percent
+                )
+            },
+        );
+    }
+
+    if let Some(camera_temp) = candidate.camera_temp {
+        match light_frame.camera_temp {
+            Some(light_temp) => {
+                let difference = (camera_temp - light_temp).abs();
+                score += difference;
+                check(
+                    "camera_temp",
+                    difference <= tolerances.camera_temp,
+                    format!(
+                        "Camera temperature {}°C differs by {:.1}°C from {}°C (tolerance {}°C).",
+                        camera_temp, difference, light_temp, tolerances.camera_temp
+                    ),
+                );
+            }
+            None => check(
+                "camera_temp",
+                true,
+                "The session has no camera temperature, it wasn't compared.".to_string(),
+            ),
+        }
+    }
+
+    // flats have to be taken with the same telescope and filter, the flattener only counts if both know it
+    let mut same_train = false;
+    if candidate.calibration_type == CalibrationType::FLAT {
+        let train = [
+            (
+                "telescope",
+                candidate.telescope_id,
+                Some(light_frame.telescope_id),
+            ),
+            (
+                "flattener",
+                candidate.flattener_id,
+                light_frame.flattener_id,
+            ),
+            ("filter", candidate.filter_id, light_frame.filter_id),
+        ];
+        let differing: Vec<&str> = train
+            .iter()
+            .filter(|(_, flat, light)| flat.is_some() && light.is_some() && flat != light)
+            .map(|(name, _, _)| *name)
+            .collect();
+
+        if !differing.is_empty() {
+            check(
+                "train",
+                false,
+                format!(
+                    "Taken with another {} than the session.",
+                    differing.join(" and ")
+                ),
+            );
+        } else if candidate.telescope_id.is_some() && candidate.filter_id == light_frame.filter_id {
+            same_train = true;
+            check(
+                "train",
+                true,
+                "Taken with the same telescope and filter.".to_string(),
+            );
+        } else {
+            check(
+                "train",
+                true,
+                "The train of the set isn't fully known, it wasn't compared.".to_string(),
+            );
+        }
+    }
+
+    let max_days = validity.max_days(&candidate.calibration_type);
+    match candidate.capture_date {
+        Some(capture_date) => {
+            let days = (light_frame.date - capture_date).num_days().abs();
+            // a month of age weighs as much as a single step of gain
+            score += days as f64 / 30.0;
+            match max_days {
+                _ if same_train && validity.flat_same_train => check(
+                    "age",
+                    true,
+                    format!(
+                        "Taken {} day(s) apart from the session, flats of the same train don't expire.",
+                        days
+                    ),
+                ),
+                Some(0) => check(
+                    "age",
+                    days == 0,
+                    format!(
+                        "Taken {} day(s) apart from the session (the same night is required).",
+                        days
+                    ),
+                ),
+                Some(max_days) => check(
+                    "age",
+                    days <= max_days as i64,
+                    format!(
+                        "Taken {} day(s) apart from the session (valid for {}).",
+                        days, max_days
+                    ),
+                ),
+                None => check(
+                    "age",
+                    true,
+                    format!("Taken {} day(s) apart from the session.", days),
+                ),
+            }
+        }
+        None => check(
+            "age",
+            true,
+            "The capture date of the set is unknown, its age wasn't checked.".to_string(),
+        ),
+    }
+
+    CalibrationMatch {
+        id: candidate.id,
+        calibration_type: candidate.calibration_type.clone(),
+        camera,
+        gain: candidate.gain,
+        sub_length: candidate.sub_length,
+        camera_temp: candidate.camera_temp,
+        total_subs: candidate.total_subs,
+        date: candidate.capture_date,
+        matches: checks.iter().all(|check| check.passed),
+        score,
+        checks,
+    }
+}
+
+// failed checks of the calibration sets linked to a session
+pub fn session_warnings(
+    db: &Database,
+    imaging_session: &ImagingSession,
+    light_frame: &LightFrame,
+    tolerances: &CalibrationTolerances,
+    validity: &CalibrationValidity,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut candidates: Vec<CalibrationCandidate> = vec![];
+
+    if let Some(id) = &imaging_session.dark_frame_id {
+        if let Some(frame) = db.get_dark_frame_by_id(id)? {
+            candidates.push(CalibrationCandidate::from(&frame));
+        }
+    }
+    if let Some(id) = &imaging_session.bias_frame_id {
+        if let Some(frame) = db.get_bias_frame_by_id(id)? {
+            candidates.push(CalibrationCandidate::from(&frame));
+        }
+    }
+    if let Some(id) = &imaging_session.flat_frame_id {
+        if let Some(frame) = db.get_flat_frame_by_id(id)? {
+            candidates.push(CalibrationCandidate::from(&frame));
+        }
+    }
+    if let Some(id) = &imaging_session.dark_flat_frame_id {
+        if let Some(frame) = db.get_dark_flat_frame_by_id(id)? {
+            candidates.push(CalibrationCandidate::from(&frame));
+        }
+    }
+
+    if candidates.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let cameras = db.get_cameras()?;
+    let warnings = candidates
+        .iter()
+        .flat_map(|candidate| {
+            let calibration_match =
+                match_candidate(candidate, light_frame, &cameras, tolerances, validity);
+            let label = match candidate.calibration_type {
+                CalibrationType::DARK => "Dark frames",
+                CalibrationType::BIAS => "Bias frames",
+                CalibrationType::FLAT => "Flat frames",
+                _ => "Dark flat frames",
+            };
+
+            calibration_match
+                .checks
+                .into_iter()
+                .filter(|check| !check.passed)
+                .map(move |check| format!("{}: {}", label, check.reason))
+        })
+        .collect();
+
+    Ok(warnings)
+}
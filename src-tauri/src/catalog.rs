@@ -0,0 +1,422 @@
+use crate::image::fits::parse_sexagesimal;
+use crate::models::database::Database;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+const BUNDLED_CATALOG: &str = include_str!("../resources/catalog.csv");
+
+// catalog prefixes as they are written in target names, longest first so "MESSIER" wins over "M"
+const PREFIXES: [(&str, &str); 9] = [
+    ("SHARPLESS2", "SH2"),
+    ("SHARPLESS", "SH2"),
+    ("CALDWELL", "C"),
+    ("MESSIER", "M"),
+    ("SH2", "SH2"),
+    ("NGC", "NGC"),
+    ("IC", "IC"),
+    ("M", "M"),
+    ("C", "C"),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    // designation the object is shown with, e.g. "M 31"
+    pub id: String,
+    pub names: Vec<String>,
+    pub object_type: String,
+    // degrees
+    pub ra: f64,
+    pub dec: f64,
+    // major and minor axis in arcminutes
+    pub size: Option<(f64, f64)>,
+    pub magnitude: Option<f64>,
+}
+
+pub struct Catalog {
+    entries: Vec<CatalogEntry>,
+    // normalized names to the index of their entry
+    index: HashMap<String, usize>,
+}
+
+impl Catalog {
+    // the user catalogs in .astrolog extend the bundled one, broken lines of them are skipped,
+    // NGC.csv and addendum.csv of the OpenNGC database add the full NGC and IC catalogs
+    pub fn load(root: &PathBuf) -> Catalog {
+        let mut catalog = Catalog {
+            entries: vec![],
+            index: HashMap::new(),
+        };
+        catalog.extend(BUNDLED_CATALOG);
+
+        for file_name in ["catalog.csv", "NGC.csv", "addendum.csv"] {
+            let user_catalog = root.join(".astrolog").join(file_name);
+            if let Ok(content) = fs::read_to_string(&user_catalog) {
+                catalog.extend(&content);
+            }
+        }
+
+        catalog
+    }
+
+    fn extend(&mut self, content: &str) {
+        let mut lines = content.lines().skip_while(|line| line.starts_with('#'));
+        let header = lines.next().unwrap_or_default();
+        let open_ngc = OpenNgcColumns::from_header(header);
+
+        for line in lines {
+            let entry = match &open_ngc {
+                Some(columns) => columns.parse_entry(line),
+                None => parse_entry(line),
+            };
+            if let Ok(entry) = entry {
+                self.add(entry);
+            }
+        }
+    }
+
+    // names already known keep pointing to the earlier entry, an entry without a new name is dropped
+    fn add(&mut self, entry: CatalogEntry) {
+        let keys: Vec<String> = entry.names.iter().map(|name| normalize(name)).collect();
+        if keys.iter().all(|key| self.index.contains_key(key)) {
+            return;
+        }
+
+        let index = self.entries.len();
+        for key in keys {
+            self.index.entry(key).or_insert(index);
+        }
+        self.entries.push(entry);
+    }
+
+    pub fn resolve(&self, name: &str) -> Option<&CatalogEntry> {
+        self.index
+            .get(&normalize(name))
+            .map(|index| &self.entries[*index])
+    }
+
+    pub fn get(&self, id: &str) -> Option<&CatalogEntry> {
+        self.resolve(id).filter(|entry| entry.id == id)
+    }
+
+    // entries with a name starting with the query come first
+    pub fn search(&self, query: &str, limit: usize) -> Vec<&CatalogEntry> {
+        let query = normalize(query);
+        if query.is_empty() {
+            return vec![];
+        }
+
+        let mut matches: Vec<(bool, &CatalogEntry)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let keys: Vec<String> = entry.names.iter().map(|name| normalize(name)).collect();
+                if keys.iter().any(|key| key.starts_with(&query)) {
+                    Some((true, entry))
+                } else if keys.iter().any(|key| key.contains(&query)) {
+                    Some((false, entry))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        matches.sort_by_key(|(prefix, _)| !*prefix);
+
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(_, entry)| entry)
+            .collect()
+    }
+}
+
+// "M 31", "m31" and "Messier 031" all become "M31", common names lose case and punctuation
+pub fn normalize(name: &str) -> String {
+    let key: String = name
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_uppercase())
+        .collect();
+
+    for (prefix, canonical) in PREFIXES {
+        if let Some(number) = key.strip_prefix(prefix) {
+            // an optional letter suffix, as in "NGC 5195A"
+            let digits = number.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+            if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+                let suffix = &number[digits.len()..];
+                let digits = digits.trim_start_matches('0');
+                return format!("{}{}{}", canonical, digits, suffix);
+            }
+        }
+    }
+
+    key
+}
+
+fn parse_entry(line: &str) -> Result<CatalogEntry, Box<dyn Error>> {
+    let fields: Vec<&str> = line.split(';').map(str::trim).collect();
+    if fields.len() < 6 {
+        return Err(format!("Expected 6 fields: {}", line).into());
+    }
+
+    let names: Vec<String> = fields[0]
+        .split('|')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect();
+    let id = names.first().ok_or("An entry needs a name.")?.clone();
+
+    let ra = parse_sexagesimal(fields[2]).ok_or(format!("Invalid RA: {}", fields[2]))? * 15.0;
+    let dec = parse_sexagesimal(fields[3]).ok_or(format!("Invalid Dec: {}", fields[3]))?;
+
+    let size = match fields[4].split_once('x') {
+        Some((major, minor)) => Some((major.parse()?, minor.parse()?)),
+        None if fields[4].is_empty() => None,
+        None => {
+            let diameter: f64 = fields[4].parse()?;
+            Some((diameter, diameter))
+        }
+    };
+    let magnitude = if fields[5].is_empty() {
+        None
+    } else {
+        Some(fields[5].parse()?)
+    };
+
+    Ok(CatalogEntry {
+        id,
+        names,
+        object_type: fields[1].to_string(),
+        ra,
+        dec,
+        size,
+        magnitude,
+    })
+}
+
+// the columns of the OpenNGC database, its files are found by their header
+struct OpenNgcColumns {
+    name: usize,
+    object_type: usize,
+    ra: usize,
+    dec: usize,
+    major_axis: Option<usize>,
+    minor_axis: Option<usize>,
+    v_mag: Option<usize>,
+    b_mag: Option<usize>,
+    messier: Option<usize>,
+    common_names: Option<usize>,
+}
+
+impl OpenNgcColumns {
+    fn from_header(header: &str) -> Option<OpenNgcColumns> {
+        let columns: Vec<&str> = header.split(';').map(str::trim).collect();
+        let position = |name: &str| columns.iter().position(|column| *column == name);
+
+        Some(OpenNgcColumns {
+            name: position("Name")?,
+            object_type: position("Type")?,
+            ra: position("RA")?,
+            dec: position("Dec")?,
+            major_axis: position("MajAx"),
+            minor_axis: position("MinAx"),
+            v_mag: position("V-Mag"),
+            b_mag: position("B-Mag"),
+            messier: position("M"),
+            common_names: position("Common names"),
+        })
+    }
+
+    // "NGC0224" is shown as "NGC 224", duplicates and objects that don't exist are skipped
+    fn parse_entry(&self, line: &str) -> Result<CatalogEntry, Box<dyn Error>> {
+        let fields: Vec<&str> = line.split(';').map(str::trim).collect();
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|index| fields.get(index).copied())
+                .filter(|value| !value.is_empty())
+        };
+        let number =
+            |index: Option<usize>| field(index).and_then(|value| value.parse::<f64>().ok());
+
+        let object_type = match field(Some(self.object_type)).unwrap_or_default() {
+            "Dup" | "NonEx" => return Err(format!("Not a separate object: {}", line).into()),
+            code => open_ngc_type(code),
+        };
+
+        let key = normalize(field(Some(self.name)).ok_or("An entry needs a name.")?);
+        let split = key.find(|c: char| c.is_ascii_digit()).unwrap_or(key.len());
+        let id = format!("{} {}", &key[..split], &key[split..])
+            .trim()
+            .to_string();
+
+        let mut names = vec![id.clone()];
+        if let Some(messier) = field(self.messier) {
+            names.push(format!("M {}", messier.trim_start_matches('0')));
+        }
+        if let Some(common_names) = field(self.common_names) {
+            names.extend(
+                common_names
+                    .split(',')
+                    .map(|name| name.trim().to_string())
+                    .filter(|name| !name.is_empty()),
+            );
+        }
+
+        let ra = field(Some(self.ra))
+            .and_then(parse_sexagesimal)
+            .ok_or(format!("Invalid RA: {}", line))?
+            * 15.0;
+        let dec = field(Some(self.dec))
+            .and_then(parse_sexagesimal)
+            .ok_or(format!("Invalid Dec: {}", line))?;
+
+        let size =
+            number(self.major_axis).map(|major| (major, number(self.minor_axis).unwrap_or(major)));
+
+        Ok(CatalogEntry {
+            id,
+            names,
+            object_type: object_type.to_string(),
+            ra,
+            dec,
+            size,
+            magnitude: number(self.v_mag).or(number(self.b_mag)),
+        })
+    }
+}
+
+// the object types of OpenNGC as they are written in the bundled catalog
+fn open_ngc_type(code: &str) -> &str {
+    match code {
+        "G" => "Galaxy",
+        "GPair" | "GTrpl" | "GGroup" => "Galaxy Group",
+        "OCl" => "Open Cluster",
+        "GCl" => "Globular Cluster",
+        "Cl+N" => "Cluster with Nebula",
+        "PN" => "Planetary Nebula",
+        "HII" | "EmN" => "Emission Nebula",
+        "RfN" => "Reflection Nebula",
+        "DrkN" => "Dark Nebula",
+        "SNR" => "Supernova Remnant",
+        "Neb" => "Nebula",
+        "*Ass" => "Stellar Association",
+        "*" => "Star",
+        "**" => "Double Star",
+        "Nova" => "Nova",
+        _ => "Other",
+    }
+}
+
+// sessions added before the catalog existed
+pub fn link_catalog_targets(db: &mut Database, catalog: &Catalog) -> Result<(), Box<dyn Error>> {
+    let locations = db.get_locations()?;
+
+    for mut light_frame in db.get_light_frames()?.into_values() {
+        if light_frame.catalog_id.is_some() || catalog.resolve(&light_frame.target).is_none() {
+            continue;
+        }
+
+        let coordinates = (light_frame.ra, light_frame.dec);
+        light_frame.link_target(catalog);
+        if (light_frame.ra, light_frame.dec) != coordinates {
+            light_frame.update_moon(locations.get(&light_frame.location_id));
+        }
+        db.insert_light_frame(&light_frame)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDir;
+
+    #[test]
+    fn normalizes_designations() {
+        assert_eq!(normalize("M 31"), "M31");
+        assert_eq!(normalize("m31"), "M31");
+        assert_eq!(normalize("Messier 031"), "M31");
+        assert_eq!(normalize("Caldwell 9"), "C9");
+        assert_eq!(normalize("Sharpless 2-155"), "SH2155");
+        assert_eq!(normalize("Sh2-155"), "SH2155");
+        assert_eq!(normalize("NGC 5195A"), "NGC5195A");
+        assert_eq!(normalize("Cave Nebula"), "CAVENEBULA");
+        assert_eq!(normalize("M-Galaxy"), "MGALAXY");
+    }
+
+    #[test]
+    fn resolves_every_name_of_an_entry() {
+        let catalog = Catalog::load(TempDir::new().path());
+
+        for name in ["Messier 031", "NGC 224", "andromeda galaxy"] {
+            assert_eq!(catalog.resolve(name).map(|e| e.id.as_str()), Some("M 31"));
+        }
+        assert!(catalog.get("M 31").is_some());
+        assert!(catalog.get("NGC 224").is_none());
+        assert!(catalog.resolve("Not a target").is_none());
+
+        let m31 = catalog.resolve("M31").unwrap();
+        assert!((m31.ra - 10.675).abs() < 1e-9);
+        assert_eq!(m31.size, Some((190.0, 60.0)));
+    }
+
+    #[test]
+    fn the_user_catalog_extends_the_bundled_one() {
+        let root = TempDir::new();
+        root.write(
+            ".astrolog/catalog.csv",
+            "# my targets\nnames;type;ra;dec;size;magnitude\n\
+             Abell 39;Planetary Nebula;16:27:33.7;+27:54:33;2.8;13.7\n\
+             broken line\n\
+             M 31|My Galaxy;Galaxy;00:00;+00:00;;\n",
+        );
+
+        let catalog = Catalog::load(root.path());
+
+        assert_eq!(
+            catalog.resolve("abell39").map(|e| e.size),
+            Some(Some((2.8, 2.8)))
+        );
+        // names already in the bundled catalog keep their entry
+        assert_eq!(catalog.resolve("M 31").map(|e| e.names.len()), Some(4));
+        assert_eq!(catalog.resolve("My Galaxy").map(|e| e.ra), Some(0.0));
+    }
+
+    #[test]
+    fn reads_the_open_ngc_database() {
+        let root = TempDir::new();
+        root.write(
+            ".astrolog/NGC.csv",
+            "Name;Type;RA;Dec;Const;MajAx;MinAx;PosAng;B-Mag;V-Mag;M;NGC;IC;Common names\n\
+             NGC0224;G;00:42:44.35;+41:16:08.6;And;177.83;69.66;35;4.29;3.44;031;;;Andromeda Galaxy\n\
+             NGC7331;G;22:37:04.09;+34:24:56.5;Peg;9.33;3.66;171;10.20;9.48;;;;\n\
+             IC0434;DrkN;05:40:59.0;-02:27:30.0;Ori;;;;;;;;;Horsehead Nebula\n\
+             NGC0225;Dup;00:43:39.0;+61:46:30.0;Cas;;;;;;;;;\n",
+        );
+
+        let catalog = Catalog::load(root.path());
+
+        let ngc7331 = catalog.resolve("NGC7331").unwrap();
+        assert_eq!(ngc7331.id, "NGC 7331");
+        assert_eq!(ngc7331.object_type, "Galaxy");
+        assert_eq!(ngc7331.size, Some((9.33, 3.66)));
+        assert_eq!(ngc7331.magnitude, Some(9.48));
+        assert!((ngc7331.dec - 34.4157).abs() < 1e-3);
+        // objects of the bundled catalog aren't added twice
+        assert_eq!(catalog.search("Andromeda Galaxy", 5).len(), 1);
+        assert!(catalog.resolve("NGC 225").is_none());
+    }
+
+    #[test]
+    fn search_prefers_prefix_matches() {
+        let catalog = Catalog::load(TempDir::new().path());
+
+        let results = catalog.search("andromeda", 5);
+
+        assert_eq!(results.first().map(|e| e.id.as_str()), Some("M 31"));
+        assert!(catalog.search("", 5).is_empty());
+    }
+}
@@ -1,16 +1,24 @@
+use crate::duplicates::HashCache;
+use crate::file_system::hash_file;
+use crate::image::{read_metadata, ImageMetadata};
 use crate::models::frontend::process::Process;
+use crate::models::preferences::{CollisionHandling, CollisionStrategy, ImportMode};
 use crate::models::state::AppState;
+use chrono::{DateTime, Local};
 use regex::Regex;
+use std::collections::HashSet;
 use std::error::Error;
+use std::ffi::OsString;
 use std::fs;
 use std::path::{Component, PathBuf};
 use std::sync::Mutex;
 use tauri::{State, Window};
 
-// TODO: two images with same name?
 pub fn classify<F>(
     base: &PathBuf,
     frames_to_classify: &Vec<PathBuf>,
+    file_pattern: &str,
+    field_value: &dyn Fn(&str) -> String,
     state: &State<AppState>,
     mut save: F,
     window: &Window,
@@ -26,16 +34,36 @@ where
     ) -> Result<(), Box<dyn Error>>,
 {
     let mut errors = Vec::new();
+    let (import_mode, collision_handling) = {
+        let config = state.config.lock().map_err(|e| e.to_string())?;
+        (config.import_mode, config.collision_handling.clone())
+    };
 
-    for frame in frames_to_classify {
+    // the sequence follows the order of the selection
+    let file_names: Vec<Option<OsString>> = frames_to_classify
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| classified_name(frame, i + 1, file_pattern, field_value))
+        .collect();
+
+    if collision_handling.strategy == CollisionStrategy::ABORT {
+        if let Some(file_name) = first_collision(&state.root_directory.join(base), &file_names) {
+            return Err(format!(
+                "{:?} already exists in {:?}, the classification was aborted.",
+                file_name, base
+            )
+            .into());
+        }
+    }
+
+    for (i, (frame, file_name)) in frames_to_classify.iter().zip(&file_names).enumerate() {
         let mut destination = state.root_directory.clone();
         destination.push(&base);
         if !destination.exists() {
             fs::create_dir_all(&destination)?;
         }
 
-        // extract file_name out of frame
-        let file_name = match frame.file_name() {
+        let file_name = match file_name {
             Some(name) => name,
             None => {
                 errors.push(format!(
@@ -49,20 +77,65 @@ where
         };
 
         destination.push(file_name);
-        // try to copy frame
-        if let Err(e) = fs::copy(frame, &destination) {
-            errors.push(format!(
-                "Failed to copy {:?} -> {:?}: {}",
-                frame, destination, e
-            ));
-            process.update(&window);
-
-            continue;
+        // the capture software reuses file names, every night starts with Light_001.fits again
+        if destination.exists() {
+            let free =
+                resolve_collision(frame, i + 1, &destination, &collision_handling, field_value);
+            process.collision(frame, free.as_ref(), collision_handling.strategy);
+            match free {
+                Some(free) => destination = free,
+                None => {
+                    process.update(&window);
+
+                    continue;
+                }
+            }
         }
+        let file_name = destination.file_name().unwrap_or(file_name).to_owned();
+
+        // try to transfer frame
+        let mode = match transfer(frame, &destination, import_mode) {
+            Ok(mode) => mode,
+            Err(e) => {
+                errors.push(format!(
+                    "Failed to transfer {:?} -> {:?}: {}",
+                    frame, destination, e
+                ));
+                process.update(&window);
+
+                continue;
+            }
+        };
 
-        save(base, &file_name.into(), frame, &destination, state)?;
+        // a broken copy never replaces the source
+        let (hash, size) = match verify(frame, &destination, mode, &state.hashes) {
+            Ok(checksum) => checksum,
+            Err(e) => {
+                errors.push(format!("Failed to verify {:?}: {}", destination, e));
+                fs::remove_file(&destination).ok();
+                process.update(&window);
 
-        process.update(&window);
+                continue;
+            }
+        };
+
+        let captured_at = read_metadata(&destination).ok().and_then(|m| m.date);
+
+        save(base, &PathBuf::from(&file_name), frame, &destination, state)?;
+        state
+            .db
+            .lock()
+            .map_err(|e| e.to_string())?
+            .identify_frame_file(&base.join(file_name), &hash, size, captured_at)?;
+
+        // the source is only deleted once the frame is stored in the library
+        if mode == ImportMode::MOVE {
+            if let Err(e) = fs::remove_file(frame) {
+                errors.push(format!("Failed to delete the source {:?}: {}", frame, e));
+            }
+        }
+
+        process.transferred(&window, frame, mode);
     }
 
     // return an error if any failures occurred
@@ -76,6 +149,202 @@ where
     Ok(())
 }
 
+// the name of the frame in the library, None if the frame has no file name
+fn classified_name(
+    frame: &PathBuf,
+    sequence: usize,
+    file_pattern: &str,
+    field_value: &dyn Fn(&str) -> String,
+) -> Option<OsString> {
+    let original = frame.file_name()?;
+    if file_pattern.is_empty() {
+        return Some(original.to_owned());
+    }
+
+    let metadata = read_metadata(frame).ok();
+    let mut file_name = replace_tokens(file_pattern, |field| {
+        file_field_value(field, frame, sequence, metadata.as_ref(), field_value)
+    });
+    // a pattern of tokens the frame has no values for
+    if file_name.is_empty() {
+        return Some(original.to_owned());
+    }
+    if let Some(extension) = frame.extension() {
+        file_name.push('.');
+        file_name.push_str(&extension.to_string_lossy());
+    }
+
+    Some(file_name.into())
+}
+
+// file names that are taken in the destination or appear twice in the selection
+fn first_collision(destination: &PathBuf, file_names: &[Option<OsString>]) -> Option<PathBuf> {
+    let mut seen = HashSet::new();
+
+    file_names
+        .iter()
+        .flatten()
+        .find(|file_name| !seen.insert(*file_name) || destination.join(file_name).exists())
+        .map(PathBuf::from)
+}
+
+// a free destination for the frame, None if it is skipped
+fn resolve_collision(
+    frame: &PathBuf,
+    sequence: usize,
+    destination: &PathBuf,
+    collision_handling: &CollisionHandling,
+    field_value: &dyn Fn(&str) -> String,
+) -> Option<PathBuf> {
+    match collision_handling.strategy {
+        CollisionStrategy::SKIP | CollisionStrategy::ABORT => None,
+        CollisionStrategy::SUFFIX => Some(with_suffix(destination)),
+        CollisionStrategy::PATTERN => {
+            let metadata = read_metadata(frame).ok();
+            let mut file_name = replace_tokens(&collision_handling.pattern, |field| {
+                file_field_value(field, frame, sequence, metadata.as_ref(), field_value)
+            });
+            if let Some(extension) = destination.extension() {
+                file_name.push('.');
+                file_name.push_str(&extension.to_string_lossy());
+            }
+
+            let renamed = destination.with_file_name(file_name);
+            if renamed.exists() {
+                Some(with_suffix(&renamed))
+            } else {
+                Some(renamed)
+            }
+        }
+    }
+}
+
+fn with_suffix(path: &PathBuf) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+
+    (1..)
+        .map(|i| path.with_file_name(format!("{}_{}{}", stem, i, extension)))
+        .find(|candidate| !candidate.exists())
+        .unwrap()
+}
+
+// tokens a file name pattern can use, the values come from the header of the frame,
+// tokens the header has no value for come from the frame the file belongs to
+fn file_field_value(
+    field: &str,
+    frame: &PathBuf,
+    sequence: usize,
+    metadata: Option<&ImageMetadata>,
+    field_value: &dyn Fn(&str) -> String,
+) -> String {
+    let value = match field {
+        "NAME" => frame
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string(),
+        "SEQUENCE" => format!("{:04}", sequence),
+        // the capture time from the header, the modification time of files without one
+        "TIMESTAMP" => metadata
+            .and_then(|metadata| metadata.date)
+            .map(|date| date.format("%Y%m%d-%H%M%S").to_string())
+            .or_else(|| {
+                let modified = fs::metadata(frame).ok()?.modified().ok()?;
+                Some(
+                    DateTime::<Local>::from(modified)
+                        .format("%Y%m%d-%H%M%S")
+                        .to_string(),
+                )
+            })
+            .unwrap_or_default(),
+        "EXPOSURE" => metadata
+            .and_then(|metadata| metadata.exposure_time)
+            .map(|exposure| format!("{}s", exposure))
+            .unwrap_or_default(),
+        "FILTER" => metadata
+            .and_then(|metadata| metadata.filter.clone())
+            .unwrap_or_default(),
+        "TEMP" => metadata
+            .and_then(|metadata| metadata.camera_temp)
+            .map(|temp| format!("{:.0}C", temp))
+            .unwrap_or_default(),
+        "GAIN" => metadata
+            .and_then(|metadata| metadata.gain)
+            .map(|gain| gain.to_string())
+            .unwrap_or_default(),
+        "TARGET" => metadata
+            .and_then(|metadata| metadata.target.clone())
+            .unwrap_or_default(),
+        _ => String::new(),
+    };
+    let value = if value.is_empty() {
+        // frames return the token itself for fields they don't know
+        Some(field_value(field))
+            .filter(|value| value != field)
+            .unwrap_or_default()
+    } else {
+        value
+    };
+
+    // header values like "Ha/OIII" must not turn into folders
+    value
+        .trim()
+        .replace(['/', '\\', '<', '>', ':', '"', '|', '?', '*'], "_")
+}
+
+// returns the mode that was used, a move still has to delete its source
+fn transfer(
+    source: &PathBuf,
+    destination: &PathBuf,
+    mode: ImportMode,
+) -> Result<ImportMode, Box<dyn Error>> {
+    match mode {
+        ImportMode::COPY | ImportMode::MOVE => {
+            fs::copy(source, destination)?;
+        }
+        // fails across filesystems
+        ImportMode::HARDLINK => {
+            if destination.exists() {
+                fs::remove_file(destination)?;
+            }
+            if fs::hard_link(source, destination).is_err() {
+                return transfer(source, destination, ImportMode::COPY);
+            }
+        }
+        ImportMode::REFLINK => {
+            if destination.exists() {
+                fs::remove_file(destination)?;
+            }
+            if reflink_copy::reflink(source, destination).is_err() {
+                return transfer(source, destination, ImportMode::COPY);
+            }
+        }
+    }
+
+    Ok(mode)
+}
+
+// returns the checksum of the destination
+fn verify(
+    source: &PathBuf,
+    destination: &PathBuf,
+    mode: ImportMode,
+    hashes: &HashCache,
+) -> Result<(String, u64), Box<dyn Error>> {
+    let checksum = hash_file(destination)?;
+
+    // links share their data with the source, there is nothing to compare
+    if matches!(mode, ImportMode::COPY | ImportMode::MOVE) && hashes.hash(source)? != checksum {
+        return Err("The copy differs from the source.".into());
+    }
+
+    Ok(checksum)
+}
+
 pub fn build_path<F>(
     base_folder: &PathBuf,
     pattern: &PathBuf,
@@ -84,17 +353,12 @@ pub fn build_path<F>(
 where
     F: Fn(&str) -> String,
 {
-    let re = Regex::new(r"\$\$(\w+)\$\$")?;
     let mut path = PathBuf::from(base_folder);
 
     for component in pattern.components() {
         if let Component::Normal(segment_osstr) = component {
             let segment = segment_osstr.to_string_lossy();
-            let replaced = re.replace_all(&segment, |caps: &regex::Captures| {
-                let field_name = &caps[1];
-                get_field_value(field_name)
-            });
-            let replaced_str = replaced.to_string();
+            let replaced_str = replace_tokens(&segment, &get_field_value);
             if !replaced_str.is_empty() {
                 path.push(replaced_str);
             }
@@ -103,3 +367,64 @@ where
 
     Ok(path)
 }
+
+// replaces every $$TOKEN$$ with the value of its field
+pub fn replace_tokens<F>(segment: &str, get_field_value: F) -> String
+where
+    F: Fn(&str) -> String,
+{
+    let re = Regex::new(r"\$\$(\w+)\$\$").unwrap();
+
+    re.replace_all(segment, |caps: &regex::Captures| {
+        let field_name = &caps[1];
+        get_field_value(field_name)
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDir;
+
+    fn directory(files: &[&str]) -> TempDir {
+        let directory = TempDir::new();
+        for file in files {
+            directory.write(file, "");
+        }
+        directory
+    }
+
+    #[test]
+    fn suffixes_the_first_free_name() {
+        let directory = directory(&["Light_001.fits", "Light_001_1.fits", "Light_001_3.fits"]);
+
+        let free = with_suffix(&directory.join("Light_001.fits"));
+        let without_extension = with_suffix(&directory.join("notes"));
+
+        assert_eq!(free, directory.join("Light_001_2.fits"));
+        assert_eq!(without_extension, directory.join("notes_1"));
+    }
+
+    #[test]
+    fn finds_collisions_in_the_destination_and_the_selection() {
+        let directory = directory(&["Light_001.fits"]);
+        let names = |names: &[&str]| -> Vec<Option<OsString>> {
+            names
+                .iter()
+                .map(|name| Some(OsString::from(name)))
+                .collect()
+        };
+
+        let taken = first_collision(
+            directory.path(),
+            &names(&["Light_002.fits", "Light_001.fits"]),
+        );
+        let repeated = first_collision(directory.path(), &names(&["a.fits", "b.fits", "a.fits"]));
+        let free = first_collision(directory.path(), &names(&["a.fits", "b.fits"]));
+
+        assert_eq!(taken, Some(PathBuf::from("Light_001.fits")));
+        assert_eq!(repeated, Some(PathBuf::from("a.fits")));
+        assert_eq!(free, None);
+    }
+}
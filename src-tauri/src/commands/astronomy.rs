@@ -0,0 +1,40 @@
+use crate::astronomy::{night_times, target_visibility, NightTimes, TargetVisibility};
+use crate::models::location::Location;
+use crate::models::state::AppState;
+use chrono::NaiveDate;
+use tauri::State;
+use uuid::Uuid;
+
+fn get_location(state: &State<AppState>, location_id: Uuid) -> Result<Location, String> {
+    state
+        .db
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get_location_by_id(location_id)
+        .map_err(|e| e.to_string())?
+        .ok_or("Location not found.".to_string())
+}
+
+#[tauri::command]
+pub fn get_night_times(
+    state: State<AppState>,
+    location_id: Uuid,
+    night_of: NaiveDate,
+) -> Result<NightTimes, String> {
+    let location = get_location(&state, location_id)?;
+
+    Ok(night_times(&location, night_of))
+}
+
+#[tauri::command]
+pub fn get_target_visibility(
+    state: State<AppState>,
+    location_id: Uuid,
+    night_of: NaiveDate,
+    ra: f64,
+    dec: f64,
+) -> Result<TargetVisibility, String> {
+    let location = get_location(&state, location_id)?;
+
+    Ok(target_visibility(&location, night_of, ra, dec))
+}
@@ -1,78 +1,39 @@
-use crate::image::{get_exposure_time, get_gain};
+use crate::analyze::{capture_date, match_equipment, AnalyzedCalibrationFrames};
+use crate::calibration_match::{rank_candidates, CalibrationCandidate, CalibrationMatches};
+use crate::commands::imaging_sessions::ImagingSessionEdit;
+use crate::dark_coverage::DarkCoverageReport;
+use crate::duplicates::{find_duplicates, reject_duplicates};
+use crate::file_system::{remove_files, FileRemoval};
+use crate::image::read_metadata;
 use crate::models::frontend::process::Process;
 use crate::models::imaging_frames::bias_frame::BiasFrame;
-use crate::models::imaging_frames::calibration_type::CalibrationType;
+use crate::models::imaging_frames::dark_flat_frame::DarkFlatFrame;
 use crate::models::imaging_frames::dark_frame::DarkFrame;
+use crate::models::imaging_frames::flat_frame::FlatFrame;
 use crate::models::imaging_frames::imaging_frame::{
     CalibrationFrame, ClassifiableFrame, ImagingSessionFrame,
 };
+use crate::models::imaging_frames::light_frame::LightFrame;
 use crate::models::state::AppState;
-use serde::{Deserialize, Serialize};
+use std::error::Error;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Mutex;
 use tauri::{State, Window};
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct AnalyzedCalibrationFrames {
-    calibration_type: CalibrationType,
-    gain: Option<i32>,
-    sub_length: Option<f64>,
-    total_subs: usize,
-    message: Option<String>,
-}
+use uuid::Uuid;
 
 #[tauri::command]
-pub fn analyze_calibration_frames(
+pub async fn analyze_calibration_frames(
+    state: State<'_, AppState>,
     frames: Vec<PathBuf>,
 ) -> Result<AnalyzedCalibrationFrames, String> {
-    let mut sub_length: Option<f64> = None;
-    let mut message = None;
-    let mut gain: Option<i32> = None;
-    let mut calibration_type = CalibrationType::DARK;
-    let total_subs = frames.len();
-
-    let path = frames.get(0).ok_or("No frames found")?;
-
-    match get_exposure_time(path) {
-        Ok(result) => {
-            sub_length = Option::from(result);
-
-            if result < 0.001 {
-                calibration_type = CalibrationType::BIAS;
-            }
-        }
-        Err(e) => {
-            let error = e.to_string();
-            message = Some(format!(
-                "{} Couldn't get sub length: {}",
-                message.unwrap_or_default(),
-                error
-            ));
-        }
+    if frames.is_empty() {
+        return Err("No frames found".to_string());
     }
 
-    match get_gain(path) {
-        Ok(result) => {
-            gain = Option::from(result);
-        }
-        Err(e) => {
-            let error = e.to_string();
-            message = Some(format!(
-                "{} Couldn't get gain: {}",
-                message.unwrap_or_default(),
-                error
-            ));
-        }
-    }
+    let duplicates = find_duplicates(&frames, &state).map_err(|e| e.to_string())?;
 
-    Ok(AnalyzedCalibrationFrames {
-        calibration_type,
-        gain,
-        sub_length,
-        total_subs,
-        message,
-    })
+    Ok(crate::analyze::analyze_calibration_frames(&frames, duplicates))
 }
 
 #[tauri::command]
@@ -81,7 +42,10 @@ pub async fn classify_dark_frame(
     state: State<'_, AppState>,
     mut dark_frame: DarkFrame,
 ) -> Result<(), String> {
-    // check for duplicates
+    // frames that are already in the library
+    reject_duplicates(&dark_frame.frames_to_classify, &state).map_err(|e| e.to_string())?;
+
+    // check for existing calibration sets
     let mut path = state.root_directory.clone();
     path.push(
         <DarkFrame as CalibrationFrame>::build_path(&dark_frame, &state)
@@ -94,6 +58,7 @@ pub async fn classify_dark_frame(
         }
     }
 
+    dark_frame.capture_date = capture_date(&dark_frame.frames_to_classify, None);
     dark_frame.add(&state).map_err(|e| e.to_string())?;
 
     let mut process = Process::spawn(
@@ -104,15 +69,10 @@ pub async fn classify_dark_frame(
         Some(dark_frame.frames_to_classify.len() as u32),
     );
 
-    if let Err(e) =
-        <DarkFrame as CalibrationFrame>::classify(&mut dark_frame, &state, &window, &mut process)
-    {
-        dark_frame.remove(&state).ok();
-    }
+    let result =
+        <DarkFrame as CalibrationFrame>::classify(&mut dark_frame, &state, &window, &mut process);
 
-    process.finish(&window);
-
-    Ok(())
+    finish_classification(&dark_frame, result, &state, &window, process)
 }
 
 #[tauri::command]
@@ -121,7 +81,10 @@ pub async fn classify_bias_frame(
     state: State<'_, AppState>,
     mut bias_frame: BiasFrame,
 ) -> Result<(), String> {
-    // check for duplicates
+    // frames that are already in the library
+    reject_duplicates(&bias_frame.frames_to_classify, &state).map_err(|e| e.to_string())?;
+
+    // check for existing calibration sets
     let mut path = state.root_directory.clone();
     path.push(bias_frame.build_path(&state).map_err(|e| e.to_string())?);
     if path.exists() {
@@ -131,6 +94,7 @@ pub async fn classify_bias_frame(
         }
     }
 
+    bias_frame.capture_date = capture_date(&bias_frame.frames_to_classify, None);
     bias_frame.add(&state).map_err(|e| e.to_string())?;
 
     let mut process = Process::spawn(
@@ -141,11 +105,340 @@ pub async fn classify_bias_frame(
         Some(bias_frame.frames_to_classify.len() as u32),
     );
 
-    if let Err(e) = bias_frame.classify(&state, &window, &mut process) {
-        bias_frame.remove(&state).ok();
+    let result = bias_frame.classify(&state, &window, &mut process);
+
+    finish_classification(&bias_frame, result, &state, &window, process)
+}
+
+#[tauri::command]
+pub async fn classify_flat_frame(
+    window: Window,
+    state: State<'_, AppState>,
+    mut flat_frame: FlatFrame,
+) -> Result<(), String> {
+    // frames that are already in the library
+    reject_duplicates(&flat_frame.frames_to_classify, &state).map_err(|e| e.to_string())?;
+
+    // the date and optical train can be part of the folder path
+    flat_frame.in_imaging_session = false;
+    flat_frame.capture_date = capture_date(&flat_frame.frames_to_classify, None);
+
+    // the headers name the telescope and filter, the flattener isn't recorded there
+    if let Some(metadata) = flat_frame
+        .frames_to_classify
+        .first()
+        .and_then(|path| read_metadata(path).ok())
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let equipment_list = db.get_equipment_list().map_err(|e| e.to_string())?;
+        drop(db);
+
+        flat_frame.telescope_id = flat_frame.telescope_id.or(metadata
+            .telescope
+            .and_then(|name| match_equipment(&equipment_list.telescopes, &name)));
+        flat_frame.filter_id = flat_frame.filter_id.or(metadata
+            .filter
+            .and_then(|name| match_equipment(&equipment_list.filters, &name)));
+    }
+
+    // check for existing calibration sets
+    let mut path = state.root_directory.clone();
+    path.push(
+        <FlatFrame as CalibrationFrame>::build_path(&flat_frame, &state)
+            .map_err(|e| e.to_string())?,
+    );
+    if path.exists() {
+        let entries = fs::read_dir(&path).map_err(|e| e.to_string())?;
+        if entries.count() > 0 {
+            return Err("Such a Flat Frame already exists.".to_string());
+        }
+    }
+
+    flat_frame.add(&state).map_err(|e| e.to_string())?;
+
+    let mut process = Process::spawn(
+        &window,
+        "Classifying Flat Frames",
+        true,
+        Some(0),
+        Some(flat_frame.frames_to_classify.len() as u32),
+    );
+
+    let result =
+        <FlatFrame as CalibrationFrame>::classify(&mut flat_frame, &state, &window, &mut process);
+
+    finish_classification(&flat_frame, result, &state, &window, process)
+}
+
+#[tauri::command]
+pub async fn classify_dark_flat_frame(
+    window: Window,
+    state: State<'_, AppState>,
+    mut dark_flat_frame: DarkFlatFrame,
+) -> Result<(), String> {
+    // frames that are already in the library
+    reject_duplicates(&dark_flat_frame.frames_to_classify, &state).map_err(|e| e.to_string())?;
+
+    // the date can be part of the folder path
+    dark_flat_frame.in_imaging_session = false;
+    dark_flat_frame.capture_date = capture_date(&dark_flat_frame.frames_to_classify, None);
+
+    // check for existing calibration sets
+    let mut path = state.root_directory.clone();
+    path.push(
+        <DarkFlatFrame as CalibrationFrame>::build_path(&dark_flat_frame, &state)
+            .map_err(|e| e.to_string())?,
+    );
+    if path.exists() {
+        let entries = fs::read_dir(&path).map_err(|e| e.to_string())?;
+        if entries.count() > 0 {
+            return Err("Such a Dark Flat Frame already exists.".to_string());
+        }
+    }
+
+    dark_flat_frame.add(&state).map_err(|e| e.to_string())?;
+
+    let mut process = Process::spawn(
+        &window,
+        "Classifying Dark Flat Frames",
+        true,
+        Some(0),
+        Some(dark_flat_frame.frames_to_classify.len() as u32),
+    );
+
+    let result = <DarkFlatFrame as CalibrationFrame>::classify(
+        &mut dark_flat_frame,
+        &state,
+        &window,
+        &mut process,
+    );
+
+    finish_classification(&dark_flat_frame, result, &state, &window, process)
+}
+
+// frames that made it into the library were already moved, so the set keeps them and
+// is only removed if none did, the error is passed on either way
+fn finish_classification<T: CalibrationFrame>(
+    frame: &T,
+    result: Result<(), Box<dyn Error>>,
+    state: &State<AppState>,
+    window: &Window,
+    process: Process,
+) -> Result<(), String> {
+    match result {
+        Ok(()) => {
+            process.finish(window);
+            Ok(())
+        }
+        Err(e) => {
+            if frame.frames_classified().is_empty() {
+                frame.remove(state).ok();
+            }
+            let message = format!("Classification failed:\n{}", e);
+            process.kill(window, message.clone());
+            Err(message)
+        }
     }
+}
 
-    process.finish(&window);
+// returns the library dark flats taken with the camera, gain and exposure of the given flats
+#[tauri::command]
+pub fn find_dark_flat_frames(
+    state: State<AppState>,
+    flat_frame_id: Uuid,
+) -> Result<Vec<DarkFlatFrame>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let flat_frame = db
+        .get_flat_frame_by_id(&flat_frame_id)
+        .map_err(|e| e.to_string())?
+        .ok_or("Flat frames not found.")?;
+
+    // flats don't store their exposure, so it is read from the first file
+    let path = match flat_frame.frames_classified.first() {
+        Some(path) => state.root_directory.join(path),
+        None => flat_frame
+            .frames_to_classify
+            .first()
+            .cloned()
+            .ok_or("The flat frames don't contain any files.")?,
+    };
+    let sub_length = read_metadata(&path)
+        .map_err(|e| e.to_string())?
+        .exposure_time
+        .ok_or("Couldn't get the sub length of the flat frames.")?;
+
+    let mut dark_flat_frames: Vec<DarkFlatFrame> = db
+        .get_dark_flat_frames()
+        .map_err(|e| e.to_string())?
+        .into_values()
+        .filter(|frame| !frame.in_imaging_session && frame.matches(&flat_frame, sub_length))
+        .collect();
+    dark_flat_frames.sort_by(|a, b| b.total_subs().cmp(&a.total_subs()));
 
-    Ok(())
+    Ok(dark_flat_frames)
+}
+
+// ranks the library calibration sets for a session that is about to be classified
+#[tauri::command]
+pub fn match_calibration_frames(
+    state: State<AppState>,
+    session: ImagingSessionEdit,
+) -> Result<CalibrationMatches, String> {
+    match_light_frame(&state, &LightFrame::from(&session))
+}
+
+#[tauri::command]
+pub fn match_imaging_session_calibration_frames(
+    state: State<AppState>,
+    id: Uuid,
+) -> Result<CalibrationMatches, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let imaging_session = db
+        .get_imaging_session_by_id(id)
+        .map_err(|e| e.to_string())?
+        .ok_or("Imaging session not found.")?;
+    let light_frame = db
+        .get_light_frame_by_id(imaging_session.light_frame_id)
+        .map_err(|e| e.to_string())?
+        .ok_or("light_frame_id not found")?;
+    drop(db);
+
+    match_light_frame(&state, &light_frame)
+}
+
+fn match_light_frame(
+    state: &State<AppState>,
+    light_frame: &LightFrame,
+) -> Result<CalibrationMatches, String> {
+    let config = state.config.lock().map_err(|e| e.to_string())?;
+    let tolerances = config.calibration_tolerances.clone();
+    let validity = config.calibration_validity.clone();
+    drop(config);
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let cameras = db.get_cameras().map_err(|e| e.to_string())?;
+
+    // only library sets are suggested, frames taken within a session belong to that session
+    let mut dark_frames: Vec<CalibrationCandidate> = db
+        .get_dark_frames()
+        .map_err(|e| e.to_string())?
+        .values()
+        .filter(|frame| !frame.in_imaging_session)
+        .map(CalibrationCandidate::from)
+        .collect();
+    let mut bias_frames: Vec<CalibrationCandidate> = db
+        .get_bias_frames()
+        .map_err(|e| e.to_string())?
+        .values()
+        .map(CalibrationCandidate::from)
+        .collect();
+    let mut flat_frames: Vec<CalibrationCandidate> = db
+        .get_flat_frames()
+        .map_err(|e| e.to_string())?
+        .values()
+        .filter(|frame| !frame.in_imaging_session)
+        .map(CalibrationCandidate::from)
+        .collect();
+    let mut dark_flat_frames: Vec<CalibrationCandidate> = db
+        .get_dark_flat_frames()
+        .map_err(|e| e.to_string())?
+        .values()
+        .filter(|frame| !frame.in_imaging_session)
+        .map(CalibrationCandidate::from)
+        .collect();
+    drop(db);
+
+    let rank = |candidates: &mut Vec<CalibrationCandidate>| {
+        rank_candidates(
+            candidates,
+            light_frame,
+            &state.root_directory,
+            &cameras,
+            &tolerances,
+            &validity,
+        )
+    };
+
+    Ok(CalibrationMatches {
+        dark_frames: rank(&mut dark_frames),
+        bias_frames: rank(&mut bias_frames),
+        flat_frames: rank(&mut flat_frames),
+        dark_flat_frames: rank(&mut dark_flat_frames),
+    })
+}
+
+#[tauri::command]
+pub fn get_dark_coverage(state: State<AppState>) -> Result<DarkCoverageReport, String> {
+    let tolerances = state
+        .config
+        .lock()
+        .map_err(|e| e.to_string())?
+        .calibration_tolerances
+        .clone();
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    crate::dark_coverage::dark_coverage(&db, &tolerances).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_calibration_frame(
+    state: State<AppState>,
+    id: Uuid,
+    removal: FileRemoval,
+) -> Result<(), String> {
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let dark_frame = db.get_dark_frame_by_id(&id).map_err(|e| e.to_string())?;
+    let bias_frame = db.get_bias_frame_by_id(&id).map_err(|e| e.to_string())?;
+    let flat_frame = db.get_flat_frame_by_id(&id).map_err(|e| e.to_string())?;
+    let dark_flat_frame = db
+        .get_dark_flat_frame_by_id(&id)
+        .map_err(|e| e.to_string())?;
+
+    let files = match (dark_frame, bias_frame, flat_frame, dark_flat_frame) {
+        (Some(dark_frame), _, _, _) => {
+            if dark_frame.in_imaging_session {
+                return Err("Can't delete dark frames: They belong to an imaging session, delete the imaging session instead!".to_string());
+            }
+            dark_frame.frames_classified
+        }
+        (None, Some(bias_frame), _, _) => bias_frame.frames_classified,
+        (None, None, Some(flat_frame), _) => {
+            if flat_frame.in_imaging_session {
+                return Err("Can't delete flat frames: They belong to an imaging session, delete the imaging session instead!".to_string());
+            }
+            flat_frame.frames_classified
+        }
+        (None, None, None, Some(dark_flat_frame)) => {
+            if dark_flat_frame.in_imaging_session {
+                return Err("Can't delete dark flat frames: They belong to an imaging session, delete the imaging session instead!".to_string());
+            }
+            dark_flat_frame.frames_classified
+        }
+        (None, None, None, None) => return Err("Calibration frames not found.".to_string()),
+    };
+
+    let sessions = db
+        .get_imaging_sessions_using(&id)
+        .map_err(|e| e.to_string())?;
+    if !sessions.is_empty() {
+        return Err(format!(
+            "Can't delete calibration frames: They are used in {} imaging session(s)!",
+            sessions.len()
+        ));
+    }
+
+    db.remove_dark_frame(id).map_err(|e| e.to_string())?;
+    db.remove_bias_frame(id).map_err(|e| e.to_string())?;
+    db.remove_flat_frame(id).map_err(|e| e.to_string())?;
+    db.remove_dark_flat_frame(id).map_err(|e| e.to_string())?;
+    drop(db);
+
+    remove_files(&state.root_directory, &files, &removal, &id).map_err(|e| {
+        format!(
+            "Calibration frames deleted, but some files couldn't be removed:\n{}",
+            e
+        )
+    })
 }
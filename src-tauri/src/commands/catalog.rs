@@ -0,0 +1,20 @@
+use crate::catalog::CatalogEntry;
+use crate::models::state::AppState;
+use tauri::State;
+
+const SEARCH_LIMIT: usize = 20;
+
+#[tauri::command]
+pub fn resolve_target(state: State<AppState>, name: String) -> Option<CatalogEntry> {
+    state.catalog.resolve(&name).cloned()
+}
+
+#[tauri::command]
+pub fn search_catalog(state: State<AppState>, query: String) -> Vec<CatalogEntry> {
+    state
+        .catalog
+        .search(&query, SEARCH_LIMIT)
+        .into_iter()
+        .cloned()
+        .collect()
+}
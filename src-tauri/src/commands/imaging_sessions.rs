@@ -1,21 +1,255 @@
+use crate::analyze::AnalyzedLightFrames;
+use crate::csv::{
+    read_log, write_log, CsvImportOptions, CsvOptions, CsvRowError, LogColumn, MissingEntry,
+};
+use crate::duplicates::{find_duplicates, reject_duplicates};
+use crate::file_system::FileRemoval;
 use crate::models::database::Database;
+use crate::models::equipment::{Camera, Filter, Flattener, Mount, Telescope};
 use crate::models::frontend::state::LogTableRow;
 use crate::models::imaging_frames::light_frame::LightFrame;
 use crate::models::imaging_session::ImagingSession;
+use crate::models::location::Location;
 use crate::models::state::AppState;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use std::process::Command;
-use std::sync::Mutex;
 use tauri::{State, Window};
 use uuid::Uuid;
 
 #[tauri::command]
-pub fn export_csv(path: PathBuf) {
-    println!("{}", path.display());
-} // TODO: implement
+pub fn export_csv(
+    state: State<AppState>,
+    path: PathBuf,
+    options: Option<CsvOptions>,
+) -> Result<(), String> {
+    let options = options.unwrap_or_default();
+    let unit = state
+        .local_config
+        .lock()
+        .map_err(|e| e.to_string())?
+        .unit
+        .clone();
+
+    let imaging_sessions = state
+        .db
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get_imaging_sessions()
+        .map_err(|e| e.to_string())?;
+
+    let mut rows: Vec<LogTableRow> = vec![];
+    let mut unresolved: Vec<String> = vec![];
+    for imaging_session in imaging_sessions.values() {
+        match LogTableRow::new(imaging_session, &state) {
+            Some(row) => rows.push(row),
+            None => unresolved.push(format!("{:?}", imaging_session.folder_dir)),
+        }
+    }
+
+    // an incomplete log must not look like a complete export
+    if !unresolved.is_empty() {
+        unresolved.sort();
+        return Err(format!(
+            "The light frames of {} imaging session(s) couldn't be found, nothing was exported: {}",
+            unresolved.len(),
+            unresolved.join(", ")
+        ));
+    }
+    rows.sort_by(|a, b| a.date.cmp(&b.date));
+
+    write_log(&path, &rows, &options, &unit).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CsvImportRow {
+    pub line: usize,
+    pub date: DateTime<Utc>,
+    pub target: String,
+    pub total_subs: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CsvImportPreview {
+    pub total_rows: usize,
+    pub sessions: Vec<CsvImportRow>,
+    pub errors: Vec<CsvRowError>,
+    pub missing: Vec<MissingEntry>,
+    pub imported: usize,
+}
+
+#[tauri::command]
+pub fn import_csv(
+    state: State<AppState>,
+    path: PathBuf,
+    options: Option<CsvImportOptions>,
+    dry_run: bool,
+) -> Result<CsvImportPreview, String> {
+    let options = options.unwrap_or_default();
+    let unit = state
+        .local_config
+        .lock()
+        .map_err(|e| e.to_string())?
+        .unit
+        .clone();
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let equipment_list = db.get_equipment_list().map_err(|e| e.to_string())?;
+    let locations = db.get_locations().map_err(|e| e.to_string())?;
+    let light_frames = db.get_light_frames().map_err(|e| e.to_string())?;
+    drop(db);
+
+    let import = read_log(
+        &path,
+        &options,
+        &unit,
+        &equipment_list,
+        &locations,
+        &light_frames,
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut preview = CsvImportPreview {
+        total_rows: import.total_rows,
+        sessions: import
+            .sessions
+            .iter()
+            .map(|s| CsvImportRow {
+                line: s.line,
+                date: s.session.general.date,
+                target: s.session.general.target.clone(),
+                total_subs: s.total_subs,
+            })
+            .collect(),
+        errors: import.errors,
+        missing: import.missing,
+        imported: 0,
+    };
+
+    if dry_run || import.sessions.is_empty() {
+        return Ok(preview);
+    }
+
+    let folder_paths = state
+        .config
+        .lock()
+        .map_err(|e| e.to_string())?
+        .folder_paths
+        .clone();
+
+    // a failing row rolls back the whole import, including the created equipment and locations
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    preview.imported = db
+        .in_transaction(|db| {
+            if options.create_missing {
+                create_missing_entries(db, &preview.missing)?;
+            }
+
+            let equipment_list = db.get_equipment_list()?;
+            let locations = db.get_locations()?;
+
+            // sessions are imported as metadata only, so there are no frames to classify
+            for imported in &import.sessions {
+                let mut light_frame = LightFrame::from(&imported.session);
+                light_frame.imported_subs = Some(imported.total_subs);
+                light_frame.link_target(&state.catalog);
+                light_frame.update_location(locations.get(&light_frame.location_id));
+
+                let imaging_session = ImagingSession {
+                    id: imported.session.base.id,
+                    folder_dir: ImagingSession::build_path_with(
+                        &light_frame,
+                        &folder_paths,
+                        &equipment_list,
+                    )?,
+                    light_frame_id: light_frame.id,
+                    flat_frame_id: None,
+                    dark_frame_id: None,
+                    bias_frame_id: None,
+                    dark_flat_frame_id: None,
+                };
+
+                db.insert_imaging_session(&imaging_session)
+                    .and_then(|_| db.insert_light_frame(&light_frame))
+                    .map_err(|e| format!("Line {}: {}", imported.line, e))?;
+            }
+
+            Ok(import.sessions.len())
+        })
+        .map_err(|e| e.to_string())?;
+
+    Ok(preview)
+}
+
+fn create_missing_entries(
+    db: &mut Database,
+    missing: &Vec<MissingEntry>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for entry in missing {
+        if entry.column == LogColumn::LOCATION {
+            // the log only names the location, it waits for its coordinates and timezone
+            let mut location = Location::new(entry.id, entry.name.clone());
+            location.needs_review = true;
+            location.validate()?;
+            db.insert_location(&location)?;
+            continue;
+        }
+
+        // equipment names are stored as "<brand> <name>"
+        let (brand, name) = match entry.name.trim().split_once(' ') {
+            Some((brand, name)) => (brand.to_string(), name.trim().to_string()),
+            None => (String::new(), entry.name.trim().to_string()),
+        };
+
+        match entry.column {
+            LogColumn::CAMERA => db.insert_camera(&Camera {
+                id: entry.id,
+                brand,
+                name,
+                notes: HashMap::new(),
+                pixel_size: 0.0,
+                pixel_x: 0,
+                pixel_y: 0,
+                is_monochrome: false,
+                is_dslr: false,
+            })?,
+            LogColumn::TELESCOPE => db.insert_telescope(&Telescope {
+                id: entry.id,
+                brand,
+                name,
+                notes: HashMap::new(),
+                focal_length: 0,
+                aperture: 0,
+            })?,
+            LogColumn::MOUNT => db.insert_mount(&Mount {
+                id: entry.id,
+                brand,
+                name,
+                notes: HashMap::new(),
+            })?,
+            LogColumn::FILTER => db.insert_filter(&Filter {
+                id: entry.id,
+                brand,
+                name,
+                notes: HashMap::new(),
+                filter_type: String::new(),
+            })?,
+            LogColumn::FLATTENER => db.insert_flattener(&Flattener {
+                id: entry.id,
+                brand,
+                name,
+                notes: HashMap::new(),
+                factor: 1.0,
+            })?,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
 
 #[tauri::command]
 pub fn open_imaging_session(state: State<AppState>, id: Uuid) -> Result<(), String> {
@@ -129,8 +363,14 @@ pub struct ImagingSessionWeather {
 pub struct ImagingSessionCalibration {
     pub dark_frame_list_id: Option<Uuid>,
     pub bias_frame_list_id: Option<Uuid>,
+    #[serde(default)]
+    pub flat_frame_list_id: Option<Uuid>,
     pub flat_frames_to_classify: Vec<PathBuf>,
     pub dark_frames_to_classify: Vec<PathBuf>,
+    #[serde(default)]
+    pub dark_flat_frame_list_id: Option<Uuid>,
+    #[serde(default)]
+    pub dark_flat_frames_to_classify: Vec<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -144,15 +384,84 @@ pub struct ImagingSessionEdit {
 }
 
 #[tauri::command]
-pub fn classify_imaging_session(
+pub async fn analyze_light_frames(
+    state: State<'_, AppState>,
+    frames: Vec<PathBuf>,
+    location_id: Option<Uuid>,
+) -> Result<AnalyzedLightFrames, String> {
+    if frames.is_empty() {
+        return Err("No frames found".to_string());
+    }
+
+    let (equipment_list, location) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let location = match location_id {
+            Some(id) => db.get_location_by_id(id).map_err(|e| e.to_string())?,
+            None => None,
+        };
+        (db.get_equipment_list().map_err(|e| e.to_string())?, location)
+    };
+
+    let duplicates = find_duplicates(&frames, &state).map_err(|e| e.to_string())?;
+
+    Ok(crate::analyze::analyze_light_frames(
+        &frames,
+        &equipment_list,
+        duplicates,
+        location.map(|l| l.tz()).as_ref(),
+    ))
+}
+
+#[tauri::command]
+pub async fn classify_imaging_session(
     window: Window,
-    state: State<AppState>,
+    state: State<'_, AppState>,
     session: ImagingSessionEdit,
 ) -> Result<LogTableRow, String> {
     // create light_frame
-    let light_frame = LightFrame::from(&session);
+    let mut light_frame = LightFrame::from(&session);
+    let location = state
+        .db
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get_location_by_id(light_frame.location_id)
+        .map_err(|e| e.to_string())?;
+    light_frame.link_target(&state.catalog);
+    light_frame.update_location(location.as_ref());
+
+    // check for duplicates, a night shot past midnight is still one session
+    let duplicate = state
+        .db
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get_light_frames()
+        .map_err(|e| e.to_string())?
+        .values()
+        .any(|frame| {
+            frame.night() == light_frame.night()
+                && frame.target_key() == light_frame.target_key()
+                && frame.camera_id == light_frame.camera_id
+                && frame.telescope_id == light_frame.telescope_id
+                && frame.filter_id == light_frame.filter_id
+        });
+    if duplicate {
+        return Err(format!(
+            "An imaging session of {} in the night of {} already exists.",
+            light_frame.target,
+            light_frame.night().format("%Y-%m-%d")
+        ));
+    }
+
+    let calibration = &session.calibration;
+    let frames = [
+        light_frame.frames_to_classify.as_slice(),
+        &calibration.flat_frames_to_classify,
+        &calibration.dark_frames_to_classify,
+        &calibration.dark_flat_frames_to_classify,
+    ]
+    .concat();
+    reject_duplicates(&frames, &state).map_err(|e| e.to_string())?;
 
-    // check for duplicates
     let mut path = state.root_directory.clone();
     path.push(ImagingSession::build_path(&light_frame, &state).map_err(|e| e.to_string())?);
     if path.exists() {
@@ -192,9 +501,19 @@ pub fn classify_imaging_session(
 
 #[tauri::command]
 pub fn edit_imaging_session(
-    window: Window,
-    state: State<Mutex<AppState>>,
+    state: State<AppState>,
     session: ImagingSessionEdit,
+) -> Result<LogTableRow, String> {
+    let imaging_session = ImagingSession::edit(&state, &session).map_err(|e| e.to_string())?;
+
+    LogTableRow::new(&imaging_session, &state).ok_or("Failed to create LogTableRow".to_string())
+}
+
+#[tauri::command]
+pub fn delete_imaging_session(
+    state: State<AppState>,
+    id: Uuid,
+    removal: FileRemoval,
 ) -> Result<(), String> {
-    Ok(())
+    ImagingSession::delete(&state, id, &removal).map_err(|e| e.to_string())
 }
@@ -1,4 +1,6 @@
+pub mod astronomy;
 pub mod calibration;
+pub mod catalog;
 pub mod equipment;
 pub mod gallery;
 pub mod image;
@@ -1,5 +1,10 @@
 use crate::file_system::{dir_contains_metadata, is_directory_empty};
-use crate::models::preferences::{LocalConfig, Location, Unit};
+use crate::models::location::Location;
+use crate::models::preferences::{
+    CalibrationTolerances, CalibrationValidity, CollisionHandling, FilePatterns, FolderPaths,
+    ImportMode, LocalConfig, Unit,
+};
+use crate::models::relayout::RelayoutPlan;
 use crate::models::state::AppState;
 use std::path::PathBuf;
 use tauri::{AppHandle, Manager, State};
@@ -47,74 +52,163 @@ pub fn setup_astrolog(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub fn preview_folder_paths(
+    state: State<AppState>,
+    folder_paths: FolderPaths,
+) -> Result<RelayoutPlan, String> {
+    RelayoutPlan::new(&state, &folder_paths).map_err(|e| e.to_string())
+}
+
+// an interrupted re-layout, if there is one
+#[tauri::command]
+pub fn pending_relayout(state: State<AppState>) -> Result<Option<RelayoutPlan>, String> {
+    RelayoutPlan::load(&state.root_directory).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn resume_relayout(state: State<AppState>) -> Result<(), String> {
+    let plan = RelayoutPlan::load(&state.root_directory).map_err(|e| e.to_string())?;
+
+    match plan {
+        Some(mut plan) => plan.execute(&state).map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
 #[tauri::command]
 pub fn change_imaging_session_folder_path(
     state: State<AppState>,
     base_folder: PathBuf,
     pattern: PathBuf,
 ) -> Result<(), String> {
-    let mut config = state.config.lock().map_err(|e| e.to_string())?;
-    let local_config = state.local_config.lock().map_err(|e| e.to_string())?;
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let mut folder_paths = state
+        .config
+        .lock()
+        .map_err(|e| e.to_string())?
+        .folder_paths
+        .clone();
 
-    if !db.get_light_frames().map_err(|e| e.to_string())?.is_empty() {
-        return Err("Feature not implemented: Can't change folder structure of already added imaging sessions in this version.".to_string());
-    }
+    folder_paths.imaging_session_base_folder = base_folder;
+    folder_paths.imaging_session_pattern = pattern;
+
+    change_folder_paths(&state, &folder_paths)
+}
 
-    let old_base = config
+#[tauri::command]
+pub fn change_dark_frames_folder_path(
+    state: State<AppState>,
+    base_folder: PathBuf,
+    pattern: PathBuf,
+) -> Result<(), String> {
+    let mut folder_paths = state
+        .config
+        .lock()
+        .map_err(|e| e.to_string())?
         .folder_paths
-        .imaging_session_base_folder
         .clone();
-    let old_pattern = config
+
+    folder_paths.calibration_base_folder = base_folder;
+    folder_paths.dark_frame_pattern = pattern;
+
+    change_folder_paths(&state, &folder_paths)
+}
+
+#[tauri::command]
+pub fn change_bias_frames_folder_path(
+    state: State<AppState>,
+    base_folder: PathBuf,
+    pattern: PathBuf,
+) -> Result<(), String> {
+    let mut folder_paths = state
+        .config
+        .lock()
+        .map_err(|e| e.to_string())?
         .folder_paths
-        .imaging_session_pattern
         .clone();
 
-    config.folder_paths.imaging_session_base_folder = base_folder;
-    config.folder_paths.imaging_session_pattern = pattern;
-
-    if let Err(e) = config
-        .save(local_config.root_directory.clone())
-    {
-        // revert to old values on failure
-        config.folder_paths.imaging_session_base_folder = old_base;
-        config.folder_paths.imaging_session_pattern = old_pattern;
-        return Err(e.to_string());
-    }
+    folder_paths.calibration_base_folder = base_folder;
+    folder_paths.bias_frame_pattern = pattern;
 
-    Ok(())
+    change_folder_paths(&state, &folder_paths)
 }
 
 #[tauri::command]
-pub fn change_dark_frames_folder_path(
+pub fn change_flat_frames_folder_path(
     state: State<AppState>,
     base_folder: PathBuf,
     pattern: PathBuf,
 ) -> Result<(), String> {
-    let mut config = state.config.lock().map_err(|e| e.to_string())?;
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let mut folder_paths = state
+        .config
+        .lock()
+        .map_err(|e| e.to_string())?
+        .folder_paths
+        .clone();
 
-    for dark_frame in db.get_dark_frames().map_err(|e| e.to_string())? {
-        if !dark_frame.1.in_imaging_session {
-            return Err("Feature not implemented: Can't change folder structure of already added dark frames in this version.".to_string());
-        }
-    }
+    folder_paths.calibration_base_folder = base_folder;
+    folder_paths.flat_frame_pattern = pattern;
+
+    change_folder_paths(&state, &folder_paths)
+}
 
-    let old_base = config
+#[tauri::command]
+pub fn change_dark_flat_frames_folder_path(
+    state: State<AppState>,
+    base_folder: PathBuf,
+    pattern: PathBuf,
+) -> Result<(), String> {
+    let mut folder_paths = state
+        .config
+        .lock()
+        .map_err(|e| e.to_string())?
         .folder_paths
-        .calibration_base_folder
         .clone();
-    let old_pattern = config.folder_paths.dark_frame_pattern.clone();
 
-    config.folder_paths.calibration_base_folder = base_folder;
-    config.folder_paths.dark_frame_pattern = pattern;
+    folder_paths.calibration_base_folder = base_folder;
+    folder_paths.dark_flat_frame_pattern = pattern;
+
+    change_folder_paths(&state, &folder_paths)
+}
 
-    if let Err(e) = config
-        .save(state.root_directory.clone())
+// moves the already classified sessions and calibration sets into the new folder structure
+fn change_folder_paths(state: &State<AppState>, folder_paths: &FolderPaths) -> Result<(), String> {
+    if RelayoutPlan::load(&state.root_directory)
+        .map_err(|e| e.to_string())?
+        .is_some()
     {
-        // revert to old values on failure
-        config.folder_paths.calibration_base_folder = old_base;
-        config.folder_paths.dark_frame_pattern = old_pattern;
+        return Err(
+            "A previous change of the folder structure was interrupted, resume it first."
+                .to_string(),
+        );
+    }
+
+    let mut plan = RelayoutPlan::new(state, folder_paths).map_err(|e| e.to_string())?;
+    plan.execute(state).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn save_location(state: State<AppState>, location: Location) -> Result<(), String> {
+    location.save(&state).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_location(state: State<AppState>, location: Location) -> Result<(), String> {
+    location.delete(&state).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn save_calibration_tolerances(
+    state: State<AppState>,
+    tolerances: CalibrationTolerances,
+) -> Result<(), String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+
+    let old_tolerances = config.calibration_tolerances.clone();
+    config.calibration_tolerances = tolerances;
+
+    if let Err(e) = config.save(state.root_directory.clone()) {
+        config.calibration_tolerances = old_tolerances;
         return Err(e.to_string());
     }
 
@@ -122,33 +216,32 @@ pub fn change_dark_frames_folder_path(
 }
 
 #[tauri::command]
-pub fn change_bias_frames_folder_path(
+pub fn save_calibration_validity(
     state: State<AppState>,
-    base_folder: PathBuf,
-    pattern: PathBuf,
+    validity: CalibrationValidity,
 ) -> Result<(), String> {
     let mut config = state.config.lock().map_err(|e| e.to_string())?;
-    let db = state.db.lock().map_err(|e| e.to_string())?;
 
-    if !db.get_bias_frames().map_err(|e| e.to_string())?.is_empty() {
-        return Err("Feature not implemented: Can't change folder structure of already added bias frames in this version.".to_string());
+    let old_validity = config.calibration_validity.clone();
+    config.calibration_validity = validity;
+
+    if let Err(e) = config.save(state.root_directory.clone()) {
+        config.calibration_validity = old_validity;
+        return Err(e.to_string());
     }
 
-    let old_base = config
-        .folder_paths
-        .calibration_base_folder
-        .clone();
-    let old_pattern = config.folder_paths.bias_frame_pattern.clone();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn save_import_mode(state: State<AppState>, import_mode: ImportMode) -> Result<(), String> {
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
 
-    config.folder_paths.calibration_base_folder = base_folder;
-    config.folder_paths.bias_frame_pattern = pattern;
+    let old_import_mode = config.import_mode;
+    config.import_mode = import_mode;
 
-    if let Err(e) = config
-        .save(state.root_directory.clone())
-    {
-        // revert to old values on failure
-        config.folder_paths.calibration_base_folder = old_base;
-        config.folder_paths.bias_frame_pattern = old_pattern;
+    if let Err(e) = config.save(state.root_directory.clone()) {
+        config.import_mode = old_import_mode;
         return Err(e.to_string());
     }
 
@@ -156,11 +249,42 @@ pub fn change_bias_frames_folder_path(
 }
 
 #[tauri::command]
-pub fn save_location(state: State<AppState>, location: Location) -> Result<(), String> {
-    location.save(&state).map_err(|e| e.to_string())
+pub fn save_collision_handling(
+    state: State<AppState>,
+    collision_handling: CollisionHandling,
+) -> Result<(), String> {
+    collision_handling.validate().map_err(|e| e.to_string())?;
+
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+
+    let old_collision_handling = config.collision_handling.clone();
+    config.collision_handling = collision_handling;
+
+    if let Err(e) = config.save(state.root_directory.clone()) {
+        config.collision_handling = old_collision_handling;
+        return Err(e.to_string());
+    }
+
+    Ok(())
 }
 
+// only frames classified from now on are named after the patterns
 #[tauri::command]
-pub fn delete_location(state: State<AppState>, location: Location) -> Result<(), String> {
-    location.delete(&state).map_err(|e| e.to_string())
-}
\ No newline at end of file
+pub fn save_file_patterns(
+    state: State<AppState>,
+    file_patterns: FilePatterns,
+) -> Result<(), String> {
+    file_patterns.validate().map_err(|e| e.to_string())?;
+
+    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+
+    let old_file_patterns = config.folder_paths.file_patterns.clone();
+    config.folder_paths.file_patterns = file_patterns;
+
+    if let Err(e) = config.save(state.root_directory.clone()) {
+        config.folder_paths.file_patterns = old_file_patterns;
+        return Err(e.to_string());
+    }
+
+    Ok(())
+}
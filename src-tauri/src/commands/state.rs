@@ -15,6 +15,8 @@ pub fn load_frontend_app_state(state: State<AppState>) -> Result<String, String>
 
     let dark_frames = db.get_dark_frames().map_err(|e| e.to_string())?;
     let bias_frames = db.get_bias_frames().map_err(|e| e.to_string())?;
+    let flat_frames = db.get_flat_frames().map_err(|e| e.to_string())?;
+    let dark_flat_frames = db.get_dark_flat_frames().map_err(|e| e.to_string())?;
     let imaging_sessions = db.get_imaging_sessions().map_err(|e| e.to_string())?;
 
     let equipment_list = EquipmentList {
@@ -24,6 +26,7 @@ pub fn load_frontend_app_state(state: State<AppState>) -> Result<String, String>
         filters: db.get_filters().map_err(|e| e.to_string())?,
         flatteners: db.get_flatteners().map_err(|e| e.to_string())?,
     };
+    let locations = db.get_locations().map_err(|e| e.to_string())?;
 
     drop(db);
 
@@ -45,6 +48,20 @@ pub fn load_frontend_app_state(state: State<AppState>) -> Result<String, String>
             .map(|f| f.1.calibration_table_row(&state))
             .filter_map(Result::ok),
     );
+    calibration_data.extend(
+        flat_frames
+            .iter()
+            .filter(|f| !f.1.in_imaging_session)
+            .map(|f| f.1.calibration_table_row(&state))
+            .filter_map(Result::ok),
+    );
+    calibration_data.extend(
+        dark_flat_frames
+            .iter()
+            .filter(|f| !f.1.in_imaging_session)
+            .map(|f| f.1.calibration_table_row(&state))
+            .filter_map(Result::ok),
+    );
 
     let table_data = TableData {
         sessions: sessions_data,
@@ -60,6 +77,7 @@ pub fn load_frontend_app_state(state: State<AppState>) -> Result<String, String>
         config: config.clone(),
         table_data,
         equipment_list,
+        locations,
         image_list,
         analytics,
     };
@@ -84,3 +102,11 @@ pub fn remove_close_lock(state: State<AppState>) -> Result<(), String> {
 
     Ok(())
 }
+
+// the backfills of this start that failed, they are only reported once
+#[tauri::command]
+pub fn take_backfill_errors(state: State<AppState>) -> Result<Vec<String>, String> {
+    let mut backfill_errors = state.backfill_errors.lock().map_err(|e| e.to_string())?;
+
+    Ok(std::mem::take(&mut *backfill_errors))
+}
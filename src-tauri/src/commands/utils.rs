@@ -1,5 +1,8 @@
 use crate::file_system::{is_directory_empty, rename_folder_with_overwrite};
+use crate::integrity::IntegrityReport;
+use crate::models::state::AppState;
 use std::path::PathBuf;
+use tauri::{State, Window};
 
 #[tauri::command]
 pub fn open_browser(url: &str) -> Result<(), String> {
@@ -21,3 +24,11 @@ pub fn rename_directory(origin: PathBuf, destination: PathBuf) -> Result<(), Str
         Err(e) => Err(format!("Error checking directory: {}", e)),
     }
 }
+
+#[tauri::command]
+pub async fn verify_library(
+    window: Window,
+    state: State<'_, AppState>,
+) -> Result<IntegrityReport, String> {
+    crate::integrity::verify_library(&state, &window).map_err(|e| e.to_string())
+}
@@ -0,0 +1,794 @@
+use crate::commands::imaging_sessions::{
+    ImagingSessionBase, ImagingSessionCalibration, ImagingSessionDetails, ImagingSessionEdit,
+    ImagingSessionEquipment, ImagingSessionGeneral, ImagingSessionWeather,
+};
+use crate::models::equipment::{EquipmentItem, EquipmentList};
+use crate::models::frontend::state::LogTableRow;
+use crate::models::imaging_frames::light_frame::LightFrame;
+use crate::models::location::Location;
+use crate::models::preferences::Unit;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum LogColumn {
+    DATE,
+    TARGET,
+    LOCATION,
+    BORTLE,
+    FILTER,
+    TELESCOPE,
+    FLATTENER,
+    MOUNT,
+    CAMERA,
+    GAIN,
+    OFFSET,
+    SUBLENGTH,
+    TOTALSUBS,
+    INTEGRATION,
+    CAMERATEMP,
+    OUTSIDETEMP,
+    SEEING,
+    CLOUDCOVER,
+    MOON,
+    NOTES,
+}
+
+impl LogColumn {
+    pub fn all() -> Vec<LogColumn> {
+        vec![
+            LogColumn::DATE,
+            LogColumn::TARGET,
+            LogColumn::LOCATION,
+            LogColumn::BORTLE,
+            LogColumn::FILTER,
+            LogColumn::TELESCOPE,
+            LogColumn::FLATTENER,
+            LogColumn::MOUNT,
+            LogColumn::CAMERA,
+            LogColumn::GAIN,
+            LogColumn::OFFSET,
+            LogColumn::SUBLENGTH,
+            LogColumn::TOTALSUBS,
+            LogColumn::INTEGRATION,
+            LogColumn::CAMERATEMP,
+            LogColumn::OUTSIDETEMP,
+            LogColumn::SEEING,
+            LogColumn::CLOUDCOVER,
+            LogColumn::MOON,
+            LogColumn::NOTES,
+        ]
+    }
+
+    fn header(&self, unit: &Unit) -> String {
+        match self {
+            LogColumn::DATE => "Date".to_string(),
+            LogColumn::TARGET => "Target".to_string(),
+            LogColumn::LOCATION => "Location".to_string(),
+            LogColumn::BORTLE => "Bortle".to_string(),
+            LogColumn::FILTER => "Filter".to_string(),
+            LogColumn::TELESCOPE => "Telescope".to_string(),
+            LogColumn::FLATTENER => "Flattener".to_string(),
+            LogColumn::MOUNT => "Mount".to_string(),
+            LogColumn::CAMERA => "Camera".to_string(),
+            LogColumn::GAIN => "Gain".to_string(),
+            LogColumn::OFFSET => "Offset".to_string(),
+            LogColumn::SUBLENGTH => "Sub Length (s)".to_string(),
+            LogColumn::TOTALSUBS => "Total Subs".to_string(),
+            LogColumn::INTEGRATION => "Integration (s)".to_string(),
+            LogColumn::CAMERATEMP => format!("Camera Temp ({})", unit.temperature_symbol()),
+            LogColumn::OUTSIDETEMP => format!("Outside Temp ({})", unit.temperature_symbol()),
+            LogColumn::SEEING => "Seeing (arcsec)".to_string(),
+            LogColumn::CLOUDCOVER => "Cloud Cover (%)".to_string(),
+            LogColumn::MOON => "Moon (%)".to_string(),
+            LogColumn::NOTES => "Notes".to_string(),
+        }
+    }
+
+    fn value(&self, row: &LogTableRow, date_format: &str, unit: &Unit) -> String {
+        let temperature = |celsius: Option<f64>| {
+            celsius.map_or(String::new(), |c| format!("{:.1}", unit.temperature(c)))
+        };
+
+        match self {
+            LogColumn::DATE => row.date.format(date_format).to_string(),
+            LogColumn::TARGET => row.target.clone(),
+            LogColumn::LOCATION => row.location_name.clone().unwrap_or_default(),
+            LogColumn::BORTLE => row.location_bortle.map_or(String::new(), |b| b.to_string()),
+            LogColumn::FILTER => row.filter.clone(),
+            LogColumn::TELESCOPE => row.telescope.clone(),
+            LogColumn::FLATTENER => row.flattener.clone(),
+            LogColumn::MOUNT => row.mount.clone(),
+            LogColumn::CAMERA => row.camera.clone(),
+            LogColumn::GAIN => row.gain.to_string(),
+            LogColumn::OFFSET => row.offset.map_or(String::new(), |o| o.to_string()),
+            LogColumn::SUBLENGTH => row.sub_length.to_string(),
+            LogColumn::TOTALSUBS => row.total_subs.to_string(),
+            LogColumn::INTEGRATION => (row.sub_length * row.total_subs as f64).to_string(),
+            LogColumn::CAMERATEMP => temperature(row.camera_temp),
+            LogColumn::OUTSIDETEMP => temperature(row.outside_temp),
+            LogColumn::SEEING => row.average_seeing.map_or(String::new(), |s| s.to_string()),
+            LogColumn::CLOUDCOVER => row
+                .average_cloud_cover
+                .map_or(String::new(), |c| c.to_string()),
+            LogColumn::MOON => row.average_moon.to_string(),
+            LogColumn::NOTES => row.notes.clone().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvOptions {
+    pub columns: Vec<LogColumn>,
+    pub delimiter: char,
+    pub date_format: String,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            columns: LogColumn::all(),
+            delimiter: ',',
+            date_format: "%Y-%m-%d".to_string(),
+        }
+    }
+}
+
+impl CsvOptions {
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.columns.is_empty() {
+            return Err("Select at least one column to export.".into());
+        }
+
+        if matches!(self.delimiter, '"' | '\n' | '\r') {
+            return Err(format!("{:?} can't be used as a delimiter.", self.delimiter).into());
+        }
+
+        if StrftimeItems::new(&self.date_format).any(|item| matches!(item, Item::Error)) {
+            return Err(format!("Invalid date format: {}", self.date_format).into());
+        }
+
+        Ok(())
+    }
+}
+
+pub fn write_log(
+    path: &PathBuf,
+    rows: &[LogTableRow],
+    options: &CsvOptions,
+    unit: &Unit,
+) -> Result<(), Box<dyn Error>> {
+    options.validate()?;
+
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    let header: Vec<String> = options
+        .columns
+        .iter()
+        .map(|column| escape(&column.header(unit), options.delimiter))
+        .collect();
+    write_record(&mut writer, &header, options.delimiter)?;
+
+    for row in rows {
+        let record: Vec<String> = options
+            .columns
+            .iter()
+            .map(|column| {
+                escape(
+                    &column.value(row, &options.date_format, unit),
+                    options.delimiter,
+                )
+            })
+            .collect();
+        write_record(&mut writer, &record, options.delimiter)?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn write_record<W: Write>(
+    writer: &mut W,
+    record: &[String],
+    delimiter: char,
+) -> Result<(), Box<dyn Error>> {
+    writer.write_all(record.join(&delimiter.to_string()).as_bytes())?;
+    writer.write_all(b"\r\n")?;
+
+    Ok(())
+}
+
+// quotes a field as described in RFC 4180 if it contains special characters
+fn escape(field: &str, delimiter: char) -> String {
+    if field.contains(delimiter) || field.contains(['"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvImportOptions {
+    // csv header -> imaging session field, headers matching an export are mapped automatically
+    pub columns: HashMap<String, LogColumn>,
+    // values used when a column is missing or empty
+    pub defaults: HashMap<LogColumn, String>,
+    pub delimiter: char,
+    pub date_format: String,
+    pub create_missing: bool,
+}
+
+impl Default for CsvImportOptions {
+    fn default() -> Self {
+        CsvImportOptions {
+            columns: HashMap::new(),
+            defaults: HashMap::new(),
+            delimiter: ',',
+            date_format: "%Y-%m-%d".to_string(),
+            create_missing: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvRowError {
+    pub line: usize,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissingEntry {
+    pub column: LogColumn,
+    pub name: String,
+    pub id: Uuid,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportedSession {
+    pub line: usize,
+    pub session: ImagingSessionEdit,
+    pub total_subs: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct CsvImport {
+    pub total_rows: usize,
+    pub sessions: Vec<ImportedSession>,
+    pub errors: Vec<CsvRowError>,
+    pub missing: Vec<MissingEntry>,
+}
+
+pub fn read_log(
+    path: &PathBuf,
+    options: &CsvImportOptions,
+    unit: &Unit,
+    equipment_list: &EquipmentList,
+    locations: &HashMap<Uuid, Location>,
+    light_frames: &HashMap<Uuid, LightFrame>,
+) -> Result<CsvImport, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    let mut records = read_records(&content, options.delimiter).into_iter();
+
+    let (_, header) = records.next().ok_or("The selected file is empty.")?;
+    let columns: Vec<Option<LogColumn>> = header
+        .iter()
+        .map(|name| map_header(name, options, unit))
+        .collect();
+
+    if columns.iter().all(Option::is_none) {
+        return Err("None of the columns could be mapped to an imaging session field.".into());
+    }
+
+    let mut resolver = Resolver {
+        equipment_list,
+        locations,
+        create_missing: options.create_missing,
+        missing: Vec::new(),
+    };
+    let mut import = CsvImport::default();
+    // target and night of every mapped row -> its line, a repeated row is reported instead of imported twice
+    let mut mapped: HashMap<(String, NaiveDate), usize> = HashMap::new();
+
+    for (line, record) in records {
+        import.total_rows += 1;
+
+        let value = |column: LogColumn| -> Option<String> {
+            columns
+                .iter()
+                .position(|c| *c == Some(column))
+                .and_then(|i| record.get(i))
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .or_else(|| options.defaults.get(&column).cloned())
+        };
+
+        match map_row(&value, options, unit, &mut resolver, light_frames) {
+            Ok((session, total_subs)) => {
+                let key = (
+                    session.general.target.to_lowercase(),
+                    night(
+                        &session.general.date,
+                        locations.get(&session.general.location_id),
+                    ),
+                );
+                if let Some(first_line) = mapped.get(&key) {
+                    import.errors.push(CsvRowError {
+                        line,
+                        reason: format!("Repeats the imaging session of line {}.", first_line),
+                    });
+                    continue;
+                }
+
+                mapped.insert(key, line);
+                import.sessions.push(ImportedSession {
+                    line,
+                    session,
+                    total_subs,
+                });
+            }
+            Err(reason) => import.errors.push(CsvRowError { line, reason }),
+        }
+    }
+
+    import.missing = resolver.missing;
+
+    Ok(import)
+}
+
+fn map_header(name: &str, options: &CsvImportOptions, unit: &Unit) -> Option<LogColumn> {
+    let name = name.trim();
+
+    if let Some(column) = options.columns.get(name) {
+        return Some(*column);
+    }
+
+    LogColumn::all().into_iter().find(|column| {
+        column.header(unit).eq_ignore_ascii_case(name)
+            || format!("{:?}", column).eq_ignore_ascii_case(name)
+    })
+}
+
+fn map_row<F>(
+    value: &F,
+    options: &CsvImportOptions,
+    unit: &Unit,
+    resolver: &mut Resolver,
+    light_frames: &HashMap<Uuid, LightFrame>,
+) -> Result<(ImagingSessionEdit, u32), String>
+where
+    F: Fn(LogColumn) -> Option<String>,
+{
+    let required = |column: LogColumn| {
+        value(column).ok_or(format!("Missing value for {}.", column.header(unit)))
+    };
+    let number = |column: LogColumn| -> Result<Option<f64>, String> {
+        value(column)
+            .map(|v| {
+                v.parse::<f64>()
+                    .map_err(|_| format!("Invalid {}: {}", column.header(unit), v))
+            })
+            .transpose()
+    };
+    let integer = |column: LogColumn| -> Result<Option<u32>, String> {
+        value(column)
+            .map(|v| {
+                v.parse::<u32>()
+                    .map_err(|_| format!("Invalid {}: {}", column.header(unit), v))
+            })
+            .transpose()
+    };
+
+    let date = parse_date(&required(LogColumn::DATE)?, &options.date_format)?;
+    let target = required(LogColumn::TARGET)?;
+    let location = required(LogColumn::LOCATION)?;
+
+    // checked before the names are resolved, so a repeated row doesn't create missing entries
+    let session_night = night(&date, resolver.find_location(&location).map(|(_, l)| l));
+    if light_frames
+        .values()
+        .any(|frame| frame.target.eq_ignore_ascii_case(&target) && frame.night() == session_night)
+    {
+        return Err(format!(
+            "An imaging session of {} in the night of {} already exists.",
+            target,
+            session_night.format("%Y-%m-%d")
+        ));
+    }
+
+    let sub_length = number(LogColumn::SUBLENGTH)?.ok_or(format!(
+        "Missing value for {}.",
+        LogColumn::SUBLENGTH.header(unit)
+    ))?;
+    if sub_length <= 0.0 {
+        return Err(format!("Invalid sub length: {}", sub_length));
+    }
+
+    let total_subs = match integer(LogColumn::TOTALSUBS)? {
+        Some(total_subs) => total_subs,
+        None => number(LogColumn::INTEGRATION)?
+            .map(|integration| (integration / sub_length).round() as u32)
+            .ok_or(format!(
+                "Missing value for {} or {}.",
+                LogColumn::TOTALSUBS.header(unit),
+                LogColumn::INTEGRATION.header(unit)
+            ))?,
+    };
+
+    let camera = required(LogColumn::CAMERA)?;
+    let telescope = required(LogColumn::TELESCOPE)?;
+    let mount = required(LogColumn::MOUNT)?;
+
+    // resolve every name first, so all unknown entries of a row are reported at once
+    let mut unknown = Vec::new();
+    let mut resolve = |column: LogColumn, name: Option<String>| {
+        name.and_then(|name| match resolver.resolve(column, &name) {
+            Ok(id) => Some(id),
+            Err(e) => {
+                unknown.push(e);
+                None
+            }
+        })
+    };
+    let location_id = resolve(LogColumn::LOCATION, Some(location));
+    let camera_id = resolve(LogColumn::CAMERA, Some(camera));
+    let telescope_id = resolve(LogColumn::TELESCOPE, Some(telescope));
+    let mount_id = resolve(LogColumn::MOUNT, Some(mount));
+    let filter_id = resolve(LogColumn::FILTER, value(LogColumn::FILTER));
+    let flattener_id = resolve(LogColumn::FLATTENER, value(LogColumn::FLATTENER));
+
+    if !unknown.is_empty() {
+        return Err(unknown.join(" "));
+    }
+
+    let general = ImagingSessionGeneral {
+        date,
+        target,
+        location_id: location_id.unwrap_or_default(),
+    };
+
+    let details = ImagingSessionDetails {
+        gain: integer(LogColumn::GAIN)?.ok_or(format!(
+            "Missing value for {}.",
+            LogColumn::GAIN.header(unit)
+        ))?,
+        sub_length,
+        offset: integer(LogColumn::OFFSET)?,
+        camera_temp: number(LogColumn::CAMERATEMP)?.map(|t| unit.celsius(t)),
+        notes: value(LogColumn::NOTES),
+    };
+
+    let equipment = ImagingSessionEquipment {
+        camera_id: camera_id.unwrap_or_default(),
+        telescope_id: telescope_id.unwrap_or_default(),
+        mount_id: mount_id.unwrap_or_default(),
+        filter_id,
+        flattener_id,
+    };
+
+    let weather = ImagingSessionWeather {
+        outside_temp: number(LogColumn::OUTSIDETEMP)?.map(|t| unit.celsius(t)),
+        average_seeing: number(LogColumn::SEEING)?,
+        average_cloud_cover: number(LogColumn::CLOUDCOVER)?,
+    };
+
+    let session = ImagingSessionEdit {
+        base: ImagingSessionBase {
+            id: Uuid::new_v4(),
+            frames: vec![],
+        },
+        general,
+        details,
+        equipment,
+        weather,
+        calibration: ImagingSessionCalibration {
+            dark_frame_list_id: None,
+            bias_frame_list_id: None,
+            flat_frame_list_id: None,
+            flat_frames_to_classify: vec![],
+            dark_frames_to_classify: vec![],
+            dark_flat_frame_list_id: None,
+            dark_flat_frames_to_classify: vec![],
+        },
+    };
+
+    Ok((session, total_subs))
+}
+
+fn parse_date(value: &str, format: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDateTime::parse_from_str(value, format) {
+        return Ok(date.and_utc());
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, format) {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+
+    Err(format!("Invalid date: {} (expected {})", value, format))
+}
+
+// the night the import stores for a session, see LightFrame::update_location,
+// locations the import creates need a review, so their sessions keep the UTC date
+fn night(date: &DateTime<Utc>, location: Option<&Location>) -> NaiveDate {
+    match location {
+        Some(location) if !location.needs_review => location.night_of(date),
+        _ => date.date_naive(),
+    }
+}
+
+// resolves equipment and location names to ids, unknown names are collected as missing entries
+struct Resolver<'a> {
+    equipment_list: &'a EquipmentList,
+    locations: &'a HashMap<Uuid, Location>,
+    create_missing: bool,
+    missing: Vec<MissingEntry>,
+}
+
+impl Resolver<'_> {
+    fn find_location(&self, name: &str) -> Option<(&Uuid, &Location)> {
+        self.locations
+            .iter()
+            .find(|(_, l)| l.name.trim().eq_ignore_ascii_case(name))
+    }
+
+    fn resolve(&mut self, column: LogColumn, name: &str) -> Result<Uuid, String> {
+        let list = self.equipment_list;
+        let found = match column {
+            LogColumn::LOCATION => self.find_location(name).map(|(id, _)| *id),
+            LogColumn::CAMERA => find_equipment(&list.cameras, name),
+            LogColumn::TELESCOPE => find_equipment(&list.telescopes, name),
+            LogColumn::MOUNT => find_equipment(&list.mounts, name),
+            LogColumn::FILTER => find_equipment(&list.filters, name),
+            LogColumn::FLATTENER => find_equipment(&list.flatteners, name),
+            _ => return Err(format!("{:?} can't be resolved to an id.", column)),
+        };
+
+        if let Some(id) = found {
+            return Ok(id);
+        }
+
+        let id = match self
+            .missing
+            .iter()
+            .find(|m| m.column == column && m.name.eq_ignore_ascii_case(name))
+        {
+            Some(entry) => entry.id,
+            None => {
+                let entry = MissingEntry {
+                    column,
+                    name: name.to_string(),
+                    id: Uuid::new_v4(),
+                };
+                let id = entry.id;
+                self.missing.push(entry);
+                id
+            }
+        };
+
+        if self.create_missing {
+            Ok(id)
+        } else {
+            Err(format!(
+                "Unknown {}: {}.",
+                format!("{:?}", column).to_lowercase(),
+                name
+            ))
+        }
+    }
+}
+
+fn find_equipment<T: EquipmentItem>(items: &HashMap<Uuid, T>, name: &str) -> Option<Uuid> {
+    items
+        .iter()
+        .find(|(_, item)| {
+            item.view_name().trim().eq_ignore_ascii_case(name)
+                || item.name().trim().eq_ignore_ascii_case(name)
+        })
+        .map(|(id, _)| *id)
+}
+
+// splits the content into records as described in RFC 4180, returns them with their line number
+fn read_records(content: &str, delimiter: char) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut start_line = 1;
+
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            '\r' => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|f| !f.trim().is_empty()) {
+                    records.push((start_line, std::mem::take(&mut record)));
+                }
+                record.clear();
+                line += 1;
+                start_line = line;
+            }
+            c if c == delimiter => record.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+
+    record.push(field);
+    if record.iter().any(|f| !f.trim().is_empty()) {
+        records.push((start_line, record));
+    }
+
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{light_frame, TempDir};
+
+    fn read(content: &str, options: &CsvImportOptions) -> CsvImport {
+        let dir = TempDir::new();
+        let path = dir.write("log.csv", content);
+
+        read_log(
+            &path,
+            options,
+            &Unit::METRIC,
+            &EquipmentList::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn escapes_special_characters() {
+        assert_eq!(escape("M 31", ','), "M 31");
+        assert_eq!(escape("Ha, OIII", ','), "\"Ha, OIII\"");
+        assert_eq!(escape("Ha, OIII", ';'), "Ha, OIII");
+        assert_eq!(
+            escape("the \"good\" night", ','),
+            "\"the \"\"good\"\" night\""
+        );
+        assert_eq!(escape("line\nbreak", ';'), "\"line\nbreak\"");
+    }
+
+    #[test]
+    fn reads_escaped_records() {
+        let fields = ["a;b", "the \"good\"\r\nnight", "", "plain"];
+        let line: Vec<String> = fields.iter().map(|f| escape(f, ';')).collect();
+        let content = format!("\u{feff}{}\r\n\r\nnext\r\n", line.join(";"));
+
+        let records = read_records(&content, ';');
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[0],
+            (1, fields.iter().map(|f| f.to_string()).collect())
+        );
+        assert_eq!(records[1], (4, vec!["next".to_string()]));
+    }
+
+    #[test]
+    fn reads_an_exported_log() {
+        let options = CsvImportOptions {
+            create_missing: true,
+            ..CsvImportOptions::default()
+        };
+        let content = "Date,Target,Location,Telescope,Mount,Camera,Gain,Sub Length (s),Integration (s),Camera Temp (°C),Notes\n\
+            2024-03-09,M 31,Backyard,RedCat 51,EQ6-R,ASI2600MM,100,300,3600,-10,\"clear, calm\"\n\
+            2024-03-09,m 31,Backyard,RedCat 51,EQ6-R,ASI2600MM,100,300,3600,-10,\n\
+            2024-03-10,,Backyard,RedCat 51,EQ6-R,ASI2600MM,100,300,3600,-10,\n\
+            2024-03-11,M 42,Field,RedCat 51,EQ6-R,ASI2600MM,100,abc,3600,-10,\n";
+
+        let import = read(content, &options);
+
+        assert_eq!(import.total_rows, 4);
+        assert_eq!(import.sessions.len(), 1);
+        let session = &import.sessions[0];
+        assert_eq!(session.line, 2);
+        assert_eq!(session.total_subs, 12);
+        assert_eq!(session.session.general.target, "M 31");
+        assert_eq!(session.session.details.camera_temp, Some(-10.0));
+        assert_eq!(
+            session.session.details.notes.as_deref(),
+            Some("clear, calm")
+        );
+
+        let errors: Vec<usize> = import.errors.iter().map(|e| e.line).collect();
+        assert_eq!(errors, vec![3, 4, 5]);
+        assert_eq!(
+            import.errors[0].reason,
+            "Repeats the imaging session of line 2."
+        );
+
+        // every unknown name is reported once, even if it is used by several rows
+        assert_eq!(import.missing.len(), 4);
+    }
+
+    #[test]
+    fn repeated_sessions_are_found_by_their_night() {
+        let mut location = Location::new(Uuid::new_v4(), "Backyard".to_string());
+        location.timezone = "Europe/Berlin".to_string();
+        let mut existing = light_frame(&[]);
+        existing.location_id = location.id;
+        existing.update_location(Some(&location));
+
+        let options = CsvImportOptions {
+            create_missing: true,
+            ..CsvImportOptions::default()
+        };
+        // 00:30 and 02:30 in Berlin still belong to the night of the 9th
+        let content = "Date,Target,Location,Telescope,Mount,Camera,Gain,Sub Length (s),Total Subs
+            2024-03-09T23:30:00Z,M 42,Backyard,RedCat 51,EQ6-R,ASI2600MM,100,300,12
+            2024-03-10T01:30:00Z,M 42,Backyard,RedCat 51,EQ6-R,ASI2600MM,100,300,12
+            2024-03-10T01:30:00Z,M 31,Backyard,RedCat 51,EQ6-R,ASI2600MM,100,300,12
+";
+        let dir = TempDir::new();
+        let path = dir.write("log.csv", content);
+        let import = read_log(
+            &path,
+            &options,
+            &Unit::METRIC,
+            &EquipmentList::new(),
+            &HashMap::from([(location.id, location)]),
+            &HashMap::from([(existing.id, existing)]),
+        )
+        .unwrap();
+
+        assert_eq!(import.sessions.len(), 1);
+        assert_eq!(
+            import.errors[0].reason,
+            "Repeats the imaging session of line 2."
+        );
+        assert_eq!(
+            import.errors[1].reason,
+            "An imaging session of M 31 in the night of 2024-03-09 already exists."
+        );
+    }
+
+    #[test]
+    fn reports_unknown_names_without_create_missing() {
+        let content =
+            "Date,Target,Location,Telescope,Mount,Camera,Gain,Sub Length (s),Total Subs\n\
+            2024-03-09,M 31,Backyard,RedCat 51,EQ6-R,ASI2600MM,100,300,12\n";
+
+        let import = read(content, &CsvImportOptions::default());
+
+        assert!(import.sessions.is_empty());
+        assert_eq!(
+            import.errors[0].reason,
+            "Unknown location: Backyard. Unknown camera: ASI2600MM. Unknown telescope: RedCat 51. Unknown mount: EQ6-R."
+        );
+    }
+}
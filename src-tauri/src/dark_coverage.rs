@@ -0,0 +1,187 @@
+use crate::models::database::Database;
+use crate::models::equipment::EquipmentItem;
+use crate::models::imaging_frames::dark_frame::DarkFrame;
+use crate::models::imaging_frames::imaging_frame::ClassifiableFrame;
+use crate::models::preferences::CalibrationTolerances;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use uuid::Uuid;
+
+// a gain, sub length and temperature combination shot in light frames without a library dark
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissingDarkFrame {
+    pub gain: u32,
+    pub sub_length: f64,
+    pub camera_temp: Option<f64>,
+    pub sessions: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraDarkCoverage {
+    pub camera_id: Uuid,
+    pub camera: String,
+    pub missing: Vec<MissingDarkFrame>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnusedDarkFrame {
+    pub id: Uuid,
+    pub camera: String,
+    pub gain: u32,
+    pub sub_length: f64,
+    pub camera_temp: f64,
+    pub total_subs: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DarkCoverageReport {
+    pub cameras: Vec<CameraDarkCoverage>,
+    pub unused: Vec<UnusedDarkFrame>,
+}
+
+pub fn dark_coverage(
+    db: &Database,
+    tolerances: &CalibrationTolerances,
+) -> Result<DarkCoverageReport, Box<dyn Error>> {
+    let equipment_list = db.get_equipment_list()?;
+    let light_frames = db.get_light_frames()?;
+    let dark_frames = db.get_dark_frames()?;
+    let library_darks: Vec<&DarkFrame> = dark_frames
+        .values()
+        .filter(|frame| !frame.in_imaging_session)
+        .collect();
+
+    let camera_name = |id: &Uuid| {
+        equipment_list
+            .cameras
+            .get(id)
+            .map_or("N/A".to_string(), |camera| camera.view_name())
+    };
+
+    let mut used = vec![];
+    for dark_frame in &library_darks {
+        if !db.get_imaging_sessions_using(&dark_frame.id)?.is_empty() {
+            used.push(dark_frame.id);
+        }
+    }
+
+    let mut cameras: Vec<CameraDarkCoverage> = vec![];
+    for session in db.get_imaging_sessions()?.values() {
+        // sessions with their own darks don't need any from the library
+        let own_darks = session
+            .dark_frame_id
+            .and_then(|id| dark_frames.get(&id))
+            .map_or(false, |frame| frame.in_imaging_session);
+        if own_darks {
+            continue;
+        }
+
+        let light_frame = match light_frames.get(&session.light_frame_id) {
+            Some(light_frame) => light_frame,
+            None => continue,
+        };
+        // sensor temperatures jitter around the set point, so they are grouped in whole degrees
+        let camera_temp = light_frame.camera_temp.map(|t| t.round());
+
+        let covering: Vec<Uuid> = library_darks
+            .iter()
+            .filter(|dark| {
+                covers(
+                    dark,
+                    &light_frame.camera_id,
+                    light_frame.gain,
+                    light_frame.sub_length,
+                    light_frame.camera_temp,
+                    tolerances,
+                )
+            })
+            .map(|dark| dark.id)
+            .collect();
+        if !covering.is_empty() {
+            used.extend(covering);
+            continue;
+        }
+
+        let index = match cameras
+            .iter()
+            .position(|c| c.camera_id == light_frame.camera_id)
+        {
+            Some(index) => index,
+            None => {
+                cameras.push(CameraDarkCoverage {
+                    camera_id: light_frame.camera_id,
+                    camera: camera_name(&light_frame.camera_id),
+                    missing: vec![],
+                });
+                cameras.len() - 1
+            }
+        };
+
+        let missing = &mut cameras[index].missing;
+        match missing.iter_mut().find(|m| {
+            m.gain == light_frame.gain
+                && m.sub_length == light_frame.sub_length
+                && m.camera_temp == camera_temp
+        }) {
+            Some(combination) => combination.sessions.push(session.id),
+            None => missing.push(MissingDarkFrame {
+                gain: light_frame.gain,
+                sub_length: light_frame.sub_length,
+                camera_temp,
+                sessions: vec![session.id],
+            }),
+        }
+    }
+
+    // combinations most sessions depend on come first
+    for camera in cameras.iter_mut() {
+        camera
+            .missing
+            .sort_by(|a, b| b.sessions.len().cmp(&a.sessions.len()));
+    }
+    cameras.sort_by(|a, b| a.camera.cmp(&b.camera));
+
+    // darks that are neither linked to a session nor match the light frames of one
+    let mut unused: Vec<UnusedDarkFrame> = library_darks
+        .iter()
+        .filter(|dark| !used.contains(&dark.id))
+        .map(|dark| UnusedDarkFrame {
+            id: dark.id,
+            camera: camera_name(&dark.camera_id),
+            gain: dark.gain,
+            sub_length: dark.sub_length,
+            camera_temp: dark.camera_temp,
+            total_subs: dark.total_subs(),
+        })
+        .collect();
+    unused.sort_by(|a, b| {
+        a.camera
+            .cmp(&b.camera)
+            .then(a.gain.cmp(&b.gain))
+            .then(a.sub_length.total_cmp(&b.sub_length))
+    });
+
+    Ok(DarkCoverageReport { cameras, unused })
+}
+
+fn covers(
+    dark_frame: &DarkFrame,
+    camera_id: &Uuid,
+    gain: u32,
+    sub_length: f64,
+    camera_temp: Option<f64>,
+    tolerances: &CalibrationTolerances,
+) -> bool {
+    let sub_length_percent = if sub_length > 0.0 {
+        (dark_frame.sub_length - sub_length).abs() / sub_length * 100.0
+    } else {
+        0.0
+    };
+
+    dark_frame.camera_id == *camera_id
+        && dark_frame.gain.abs_diff(gain) <= tolerances.gain
+        && sub_length_percent <= tolerances.sub_length_percent
+        && camera_temp.map_or(true, |temp| {
+            (dark_frame.camera_temp - temp).abs() <= tolerances.camera_temp
+        })
+}
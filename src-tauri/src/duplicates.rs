@@ -0,0 +1,192 @@
+use crate::file_system::hash_file;
+use crate::image::read_metadata;
+use crate::models::database::{Database, FrameFile};
+use crate::models::state::AppState;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::State;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DuplicateMatch {
+    HASH,
+    // library files classified before checksums existed, or changed since
+    NAMEANDDATE,
+}
+
+// a selected file that is already part of the library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateFrame {
+    pub path: PathBuf,
+    pub existing: PathBuf,
+    pub matched_by: DuplicateMatch,
+    // None for files of the calibration library
+    pub session_id: Option<Uuid>,
+    pub owner: String,
+}
+
+// checksums of selected files, the analysis hashes them and the classification reuses them
+#[derive(Default)]
+pub struct HashCache {
+    entries: Mutex<HashMap<PathBuf, (SystemTime, (String, u64))>>,
+}
+
+impl HashCache {
+    // a file that was modified since it was hashed is hashed again
+    pub fn hash(&self, path: &PathBuf) -> Result<(String, u64), Box<dyn Error>> {
+        let modified = fs::metadata(path)?.modified()?;
+        let cached = self
+            .entries
+            .lock()
+            .map_err(|e| e.to_string())?
+            .get(path)
+            .cloned();
+        if let Some((hashed, checksum)) = cached {
+            if hashed == modified {
+                return Ok(checksum);
+            }
+        }
+
+        let checksum = hash_file(path)?;
+        self.entries
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(path.clone(), (modified, checksum.clone()));
+        Ok(checksum)
+    }
+}
+
+// the database is only locked while the library is read, hashing the selection takes a while
+pub fn find_duplicates(
+    frames: &Vec<PathBuf>,
+    state: &State<AppState>,
+) -> Result<Vec<DuplicateFrame>, Box<dyn Error>> {
+    let (library, sessions) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let light_frames = db.get_light_frames()?;
+        let sessions: Vec<(Uuid, PathBuf, String)> = db
+            .get_imaging_sessions()?
+            .into_values()
+            .map(|session| {
+                let owner = match light_frames.get(&session.light_frame_id) {
+                    Some(light_frame) => format!(
+                        "{} ({})",
+                        light_frame.target,
+                        light_frame.night().format("%Y-%m-%d")
+                    ),
+                    None => session.folder_dir.to_string_lossy().to_string(),
+                };
+                (session.id, session.folder_dir, owner)
+            })
+            .collect();
+
+        (db.get_classified_frame_files()?, sessions)
+    };
+
+    let mut by_checksum: HashMap<&(String, u64), &FrameFile> = HashMap::new();
+    let mut by_name: HashMap<OsString, Vec<&FrameFile>> = HashMap::new();
+    for file in &library {
+        if let Some(checksum) = &file.checksum {
+            by_checksum.insert(checksum, file);
+        }
+        if let Some(name) = file.path.file_name() {
+            by_name.entry(name.to_os_string()).or_default().push(file);
+        }
+    }
+    // only files with the size of a library file can have its content
+    let sizes: HashSet<u64> = by_checksum.keys().map(|(_, size)| *size).collect();
+
+    let mut duplicates = vec![];
+    for path in frames {
+        let size = match fs::metadata(path) {
+            Ok(metadata) => metadata.len(),
+            Err(_) => continue,
+        };
+
+        let mut existing = None;
+        if sizes.contains(&size) {
+            if let Ok(checksum) = state.hashes.hash(path) {
+                existing = by_checksum
+                    .get(&checksum)
+                    .map(|file| (*file, DuplicateMatch::HASH));
+            }
+        }
+
+        // library files keep the header date they were classified with
+        if existing.is_none() {
+            let candidates = path.file_name().and_then(|name| by_name.get(name));
+            let date = read_metadata(path).ok().and_then(|metadata| metadata.date);
+            if let (Some(candidates), Some(date)) = (candidates, date) {
+                existing = candidates
+                    .iter()
+                    .find(|file| file.captured_at == Some(date))
+                    .map(|file| (*file, DuplicateMatch::NAMEANDDATE));
+            }
+        }
+
+        if let Some((file, matched_by)) = existing {
+            // frames of a session, calibration frames taken within it included, live in its folder
+            let session = sessions
+                .iter()
+                .find(|(_, folder_dir, _)| file.path.starts_with(folder_dir));
+
+            duplicates.push(DuplicateFrame {
+                path: path.clone(),
+                existing: file.path.clone(),
+                matched_by,
+                session_id: session.map(|(id, _, _)| *id),
+                owner: session.map_or("Calibration Library".to_string(), |(_, _, owner)| {
+                    owner.clone()
+                }),
+            });
+        }
+    }
+
+    Ok(duplicates)
+}
+
+// files with the exact content of a library file are never classified a second time
+pub fn reject_duplicates(
+    frames: &Vec<PathBuf>,
+    state: &State<AppState>,
+) -> Result<(), Box<dyn Error>> {
+    let duplicates: Vec<DuplicateFrame> = find_duplicates(frames, state)?
+        .into_iter()
+        .filter(|duplicate| duplicate.matched_by == DuplicateMatch::HASH)
+        .collect();
+
+    match duplicates.first() {
+        Some(duplicate) => Err(format!(
+            "{} frame(s) are already in the library, e.g. {:?} as {:?} of {}.",
+            duplicates.len(),
+            duplicate.path,
+            duplicate.existing,
+            duplicate.owner
+        )
+        .into()),
+        None => Ok(()),
+    }
+}
+
+// files classified before their header date was stored, duplicates are found by name and date
+pub fn fill_frame_file_dates(db: &mut Database, root: &PathBuf) -> Result<(), Box<dyn Error>> {
+    for file in db.get_classified_frame_files()? {
+        if file.captured_at.is_some() {
+            continue;
+        }
+        if let Some(date) = read_metadata(&root.join(&file.path))
+            .ok()
+            .and_then(|m| m.date)
+        {
+            db.set_frame_file_captured_at(&file.path, &date)?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,13 +1,25 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
 #[cfg(target_os = "windows")]
 use std::os::windows::ffi::OsStrExt;
 use std::path::PathBuf;
 use std::{fs, io};
+use uuid::Uuid;
 
 #[cfg(target_os = "windows")]
 use winapi::um::fileapi::SetFileAttributesW;
 #[cfg(target_os = "windows")]
 use winapi::um::winnt::FILE_ATTRIBUTE_HIDDEN;
 
+pub const TRASH_FOLDER: &str = "Trash";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum FileRemoval {
+    KEEP,
+    TRASH,
+    DELETE,
+}
+
 pub fn set_folder_invisible(path: &PathBuf) {
     for component in path.ancestors() {
         let dir_name = component.file_name().and_then(|os_str| os_str.to_str());
@@ -30,6 +42,14 @@ pub fn set_folder_invisible(path: &PathBuf) {
     }
 }
 
+// BLAKE3 hash in hex and the size of the file
+pub fn hash_file(path: &PathBuf) -> io::Result<(String, u64)> {
+    let mut hasher = blake3::Hasher::new();
+    let size = io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+
+    Ok((hasher.finalize().to_hex().to_string(), size))
+}
+
 pub fn is_directory_empty(path: &PathBuf) -> io::Result<bool> {
     let mut entries = fs::read_dir(path)?;
     Ok(entries.next().is_none())
@@ -54,3 +74,107 @@ pub fn rename_folder_with_overwrite(old_path: &PathBuf, new_path: &PathBuf) -> i
     }
     fs::rename(old_path, new_path)
 }
+
+// moves a folder to a new location, returns false if there was nothing to move
+pub fn move_folder(from: &PathBuf, to: &PathBuf) -> io::Result<bool> {
+    if !from.exists() {
+        return Ok(false);
+    }
+
+    if to.exists() {
+        if !is_directory_empty(to)? {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("Destination {:?} already exists and is not empty.", to),
+            ));
+        }
+        fs::remove_dir(to)?;
+    }
+
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(from, to)?;
+
+    Ok(true)
+}
+
+// removes empty folders from path upwards, stops at root
+pub fn remove_empty_parents(path: &PathBuf, root: &PathBuf) {
+    for dir in path.ancestors() {
+        if dir == root || !dir.starts_with(root) {
+            break;
+        }
+        if !dir.exists() {
+            continue;
+        }
+        if fs::remove_dir(dir).is_err() {
+            break;
+        }
+    }
+}
+
+// the deepest folder containing all paths, None for an empty list
+pub fn common_parent(paths: &[PathBuf]) -> Option<PathBuf> {
+    let mut parent = paths.first()?.parent()?.to_path_buf();
+    for path in &paths[1..] {
+        while !path.starts_with(&parent) {
+            if !parent.pop() {
+                return None;
+            }
+        }
+    }
+    Some(parent)
+}
+
+// replaces the prefix from of path with to, paths outside of from are returned unchanged
+pub fn rebase_path(path: &PathBuf, from: &PathBuf, to: &PathBuf) -> PathBuf {
+    match path.strip_prefix(from) {
+        Ok(rest) => to.join(rest),
+        Err(_) => path.clone(),
+    }
+}
+
+// removes files relative to root, trashed files keep their relative path below Trash/<trash_id>
+pub fn remove_files(
+    root: &PathBuf,
+    files: &Vec<PathBuf>,
+    removal: &FileRemoval,
+    trash_id: &Uuid,
+) -> Result<(), Box<dyn Error>> {
+    let mut errors = Vec::new();
+
+    for file in files {
+        let source = root.join(file);
+
+        let result = match removal {
+            FileRemoval::KEEP => continue,
+            FileRemoval::TRASH => {
+                let mut destination = root.join(TRASH_FOLDER);
+                destination.push(trash_id.to_string());
+                destination.push(file);
+                destination
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|_| fs::rename(&source, &destination))
+            }
+            FileRemoval::DELETE => fs::remove_file(&source),
+        };
+
+        match result {
+            Ok(_) => {
+                if let Some(parent) = source.parent() {
+                    remove_empty_parents(&parent.to_path_buf(), root);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => errors.push(format!("Failed to remove {:?}: {}", source, e)),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors.join("\n").into());
+    }
+
+    Ok(())
+}
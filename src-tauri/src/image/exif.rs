@@ -0,0 +1,81 @@
+use crate::image::ImageMetadata;
+use ::exif::{Exif, Field, In, Reader, Tag};
+use chrono::NaiveDateTime;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+fn get_exif_data(image: &PathBuf) -> Result<Exif, Box<dyn Error>> {
+    let file = File::open(image)?;
+    let exif_reader = Reader::new();
+    let exif = exif_reader.read_from_container(&mut BufReader::new(file))?;
+
+    Ok(exif)
+}
+
+pub fn read_metadata(image: &PathBuf) -> Result<ImageMetadata, Box<dyn Error>> {
+    let exif = get_exif_data(image)?;
+
+    let gain = display_value(&exif, Tag::PhotographicSensitivity).and_then(|v| v.parse().ok());
+    let exposure_time = display_value(&exif, Tag::ExposureTime).and_then(|v| parse_fraction(&v));
+    let focal_length = display_value(&exif, Tag::FocalLength).and_then(|v| parse_fraction(&v));
+
+    // the camera writes its local time without a time zone
+    let date = display_value(&exif, Tag::DateTimeOriginal)
+        .or_else(|| display_value(&exif, Tag::DateTime))
+        .and_then(|v| NaiveDateTime::parse_from_str(&v, "%Y-%m-%d %H:%M:%S").ok());
+
+    let camera = match (
+        string_value(exif.get_field(Tag::Make, In::PRIMARY)),
+        string_value(exif.get_field(Tag::Model, In::PRIMARY)),
+    ) {
+        (Some(make), Some(model)) if !model.starts_with(&make) => {
+            Some(format!("{} {}", make, model))
+        }
+        (make, model) => model.or(make),
+    };
+
+    Ok(ImageMetadata {
+        gain,
+        exposure_time,
+        date,
+        camera,
+        focal_length,
+        local_time: true,
+        ..ImageMetadata::default()
+    })
+}
+
+fn display_value(exif: &Exif, tag: Tag) -> Option<String> {
+    exif.get_field(tag, In::PRIMARY)
+        .map(|field| field.display_value().to_string())
+}
+
+fn string_value(field: Option<&Field>) -> Option<String> {
+    let value = field?
+        .display_value()
+        .to_string()
+        .trim_matches('"')
+        .trim()
+        .to_string();
+
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+// exposure times are stored as rationals, e.g. 1/200
+fn parse_fraction(value: &str) -> Option<f64> {
+    match value.split_once('/') {
+        Some((numerator, denominator)) => {
+            let numerator: f64 = numerator.trim().parse().ok()?;
+            let denominator: f64 = denominator.trim().parse().ok()?;
+
+            Some(numerator / denominator)
+        }
+        None => value.trim().parse().ok(),
+    }
+}
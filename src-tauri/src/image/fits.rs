@@ -0,0 +1,254 @@
+use crate::image::ImageMetadata;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::PathBuf;
+
+const BLOCK_SIZE: usize = 2880;
+const CARD_SIZE: usize = 80;
+
+// headers of camera files fit in a few blocks, the limit protects against files without an END card
+const MAX_HEADER_BLOCKS: usize = 64;
+
+pub fn read_metadata(image: &PathBuf) -> Result<ImageMetadata, Box<dyn Error>> {
+    let keywords = read_header(image)?;
+
+    Ok(metadata_from_keywords(&keywords))
+}
+
+// reads the keywords of the primary header, values are returned without quotes and comments
+pub fn read_header(image: &PathBuf) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    let mut reader = BufReader::new(File::open(image)?);
+    let mut keywords = HashMap::new();
+    let mut block = [0u8; BLOCK_SIZE];
+
+    for block_index in 0..MAX_HEADER_BLOCKS {
+        reader
+            .read_exact(&mut block)
+            .map_err(|_| "Unexpected end of file in FITS header.")?;
+
+        for (card_index, card) in block.chunks(CARD_SIZE).enumerate() {
+            // headers are ASCII only, anything else is replaced to keep the columns aligned
+            let card: String = card
+                .iter()
+                .map(|&b| if b.is_ascii() { b as char } else { '?' })
+                .collect();
+            let keyword = card[..8].trim_end();
+
+            if block_index == 0 && card_index == 0 && keyword != "SIMPLE" {
+                return Err("File is not a FITS file.".into());
+            }
+            if keyword == "END" {
+                return Ok(keywords);
+            }
+            if &card[8..10] != "= " {
+                continue;
+            }
+            if let Some(value) = parse_value(&card[10..]) {
+                keywords.entry(keyword.to_string()).or_insert(value);
+            }
+        }
+    }
+
+    Err("FITS header has no END keyword.".into())
+}
+
+pub fn parse_value(field: &str) -> Option<String> {
+    let field = field.trim_start();
+
+    let value = if let Some(rest) = field.strip_prefix('\'') {
+        // strings are quoted, a quote inside the string is written as two quotes
+        let mut value = String::new();
+        let mut chars = rest.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '\'' {
+                if chars.peek() == Some(&'\'') {
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            value.push(c);
+        }
+        value.trim_end().to_string()
+    } else {
+        field
+            .split('/')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    };
+
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+// maps the common keywords written by capture software, also used for FITS keywords of other formats
+pub fn metadata_from_keywords(keywords: &HashMap<String, String>) -> ImageMetadata {
+    let text = |key: &str| keywords.get(key).cloned();
+    let number = |key: &str| keywords.get(key).and_then(|v| parse_number(v));
+
+    ImageMetadata {
+        gain: number("GAIN").map(|v| v.round() as i32),
+        offset: number("OFFSET").map(|v| v.round() as i32),
+        exposure_time: number("EXPTIME").or_else(|| number("EXPOSURE")),
+        camera_temp: number("CCD-TEMP"),
+        set_temp: number("SET-TEMP"),
+        date: keywords
+            .get("DATE-OBS")
+            .and_then(|date| parse_date(date, keywords.get("TIME-OBS"))),
+        filter: text("FILTER"),
+        target: text("OBJECT"),
+        binning: number("XBINNING").map(|v| v.round() as u32),
+        camera: text("INSTRUME"),
+        telescope: text("TELESCOP"),
+        focal_length: number("FOCALLEN"),
+        frame_type: text("IMAGETYP"),
+        ra: number("RA").or_else(|| {
+            keywords
+                .get("OBJCTRA")
+                .and_then(|v| parse_sexagesimal(v))
+                .map(|hours| hours * 15.0)
+        }),
+        dec: number("DEC").or_else(|| keywords.get("OBJCTDEC").and_then(|v| parse_sexagesimal(v))),
+        // DATE-OBS is UTC by the FITS standard
+        local_time: false,
+    }
+}
+
+// OBJCTRA and OBJCTDEC are written as "12 34 56.7" or "+12:34:56"
+pub(crate) fn parse_sexagesimal(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let negative = value.starts_with('-');
+
+    let mut result = 0.0;
+    let mut factor = 1.0;
+    for part in value
+        .trim_start_matches(['+', '-'])
+        .split([' ', ':'])
+        .filter(|part| !part.is_empty())
+    {
+        result += part.parse::<f64>().ok()? * factor;
+        factor /= 60.0;
+    }
+
+    Some(if negative { -result } else { result })
+}
+
+// FITS allows D as exponent character for double precision values
+fn parse_number(value: &str) -> Option<f64> {
+    value.trim().replace(['D', 'd'], "E").parse().ok()
+}
+
+// DATE-OBS is written in UTC, older files store the time separately in TIME-OBS
+fn parse_date(date: &str, time: Option<&String>) -> Option<NaiveDateTime> {
+    let date = date.trim().trim_end_matches('Z');
+
+    for format in [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M:%S%.f",
+    ] {
+        if let Ok(date_time) = NaiveDateTime::parse_from_str(date, format) {
+            return Some(date_time);
+        }
+    }
+
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    let time = time
+        .and_then(|t| NaiveTime::parse_from_str(t.trim(), "%H:%M:%S%.f").ok())
+        .unwrap_or_default();
+
+    Some(date.and_time(time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDir;
+
+    // writes the cards as a single header block
+    fn fits_file(dir: &TempDir, cards: &[&str]) -> PathBuf {
+        let mut header = String::new();
+        for card in cards.iter().chain(&["END"]) {
+            header.push_str(&format!("{:<80}", card));
+        }
+        let mut bytes = header.into_bytes();
+        bytes.resize(BLOCK_SIZE, b' ');
+
+        dir.write("Light_001.fits", bytes)
+    }
+
+    #[test]
+    fn reads_the_primary_header() {
+        let dir = TempDir::new();
+        let path = fits_file(
+            &dir,
+            &[
+                "SIMPLE  =                    T / file conforms to FITS standard",
+                "EXPTIME =              3.0D+02 / exposure time in seconds",
+                "CCD-TEMP=          -1.0012D+01",
+                "GAIN    =                  100",
+                "DATE-OBS= '2024-03-09T21:15:30.5'",
+                "FILTER  = 'Ha/OIII '           / filter name",
+                "OBJECT  = 'Barnard''s Galaxy'",
+                "OBJCTRA = '19 44 56.6'",
+                "OBJCTDEC= '-14 47 21'",
+            ],
+        );
+        let metadata = read_metadata(&path).unwrap();
+
+        assert_eq!(metadata.exposure_time, Some(300.0));
+        assert_eq!(metadata.camera_temp, Some(-10.012));
+        assert_eq!(metadata.gain, Some(100));
+        assert_eq!(
+            metadata.date,
+            NaiveDateTime::parse_from_str("2024-03-09 21:15:30.5", "%Y-%m-%d %H:%M:%S%.f").ok()
+        );
+        assert_eq!(metadata.filter.as_deref(), Some("Ha/OIII"));
+        assert_eq!(metadata.target.as_deref(), Some("Barnard's Galaxy"));
+        assert!((metadata.ra.unwrap() - 296.2358).abs() < 1e-3);
+        assert!((metadata.dec.unwrap() + 14.7892).abs() < 1e-3);
+        assert!(!metadata.local_time);
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        let dir = TempDir::new();
+        let path = fits_file(&dir, &["NAXIS   =                    2"]);
+        let result = read_header(&path);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parses_card_values() {
+        assert_eq!(parse_value(" 'It''s'  / comment"), Some("It's".to_string()));
+        assert_eq!(parse_value("  1.5E3 / comment"), Some("1.5E3".to_string()));
+        assert_eq!(parse_value("         / only a comment"), None);
+        assert_eq!(parse_number("1.25d-1"), Some(0.125));
+    }
+
+    #[test]
+    fn parses_sexagesimal_coordinates() {
+        assert_eq!(parse_sexagesimal("12 30 00"), Some(12.5));
+        assert_eq!(parse_sexagesimal("-00:30:00"), Some(-0.5));
+        assert_eq!(parse_sexagesimal("+41 16 09.0"), Some(41.269166666666664));
+        assert_eq!(parse_sexagesimal("12h30m"), None);
+    }
+
+    #[test]
+    fn combines_date_and_time_cards() {
+        let time = "01:02:03".to_string();
+        assert_eq!(
+            parse_date("2024-03-09", Some(&time)),
+            NaiveDateTime::parse_from_str("2024-03-09 01:02:03", "%Y-%m-%d %H:%M:%S").ok()
+        );
+    }
+}
@@ -0,0 +1,92 @@
+mod exif;
+pub(crate) mod fits;
+mod xisf;
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::PathBuf;
+
+// metadata read from the image header, independent of the file format
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImageMetadata {
+    pub gain: Option<i32>,
+    pub offset: Option<i32>,
+    pub exposure_time: Option<f64>,
+    pub camera_temp: Option<f64>,
+    pub set_temp: Option<f64>,
+    pub date: Option<NaiveDateTime>,
+    pub filter: Option<String>,
+    pub target: Option<String>,
+    pub binning: Option<u32>,
+    pub camera: Option<String>,
+    pub telescope: Option<String>,
+    pub focal_length: Option<f64>,
+    pub frame_type: Option<String>,
+    // target coordinates in degrees
+    pub ra: Option<f64>,
+    pub dec: Option<f64>,
+    // EXIF dates are the local time of the camera clock, FITS and XISF dates are UTC
+    #[serde(default)]
+    pub local_time: bool,
+}
+
+impl ImageMetadata {
+    // local dates are read in the time zone of the location, or of this computer if it isn't known
+    pub fn date_utc(&self, timezone: Option<&Tz>) -> Option<DateTime<Utc>> {
+        let date = self.date?;
+        if !self.local_time {
+            return Some(date.and_utc());
+        }
+
+        match timezone {
+            Some(tz) => tz
+                .from_local_datetime(&date)
+                .earliest()
+                .map(|d| d.with_timezone(&Utc)),
+            None => Local
+                .from_local_datetime(&date)
+                .earliest()
+                .map(|d| d.with_timezone(&Utc)),
+        }
+    }
+}
+
+pub fn read_metadata(image: &PathBuf) -> Result<ImageMetadata, Box<dyn Error>> {
+    let extension = image
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    match extension.as_str() {
+        "fits" | "fit" | "fts" => fits::read_metadata(image),
+        "xisf" => xisf::read_metadata(image),
+        _ => exif::read_metadata(image),
+    }
+}
+
+pub fn get_gain(image: &PathBuf) -> Result<i32, Box<dyn Error>> {
+    let gain = read_metadata(image)?
+        .gain
+        .ok_or("Gain not found in image header.")?;
+
+    Ok(gain)
+}
+
+pub fn get_exposure_time(image: &PathBuf) -> Result<f64, Box<dyn Error>> {
+    let exposure_time = read_metadata(image)?
+        .exposure_time
+        .ok_or("Exposure time not found in image header.")?;
+
+    Ok(exposure_time)
+}
+
+pub fn get_date(image: &PathBuf) -> Result<String, Box<dyn Error>> {
+    let date = read_metadata(image)?
+        .date
+        .ok_or("Date not found in image header.")?;
+
+    Ok(date.format("%Y-%m-%d %H:%M:%S").to_string())
+}
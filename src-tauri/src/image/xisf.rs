@@ -0,0 +1,254 @@
+use crate::image::fits::{metadata_from_keywords, parse_value};
+use crate::image::ImageMetadata;
+use chrono::{DateTime, NaiveDateTime};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::PathBuf;
+
+const SIGNATURE: &[u8; 8] = b"XISF0100";
+
+// the header is bounded by the length field, the limit protects against corrupted files
+const MAX_HEADER_LENGTH: usize = 16 * 1024 * 1024;
+
+pub fn read_metadata(image: &PathBuf) -> Result<ImageMetadata, Box<dyn Error>> {
+    let header = read_header(image)?;
+
+    let mut keywords = HashMap::new();
+    let mut properties = HashMap::new();
+
+    for element in Element::parse_all(&header) {
+        match element.name.as_str() {
+            "FITSKeyword" => {
+                let name = element.attributes.get("name");
+                let value = element.attributes.get("value").and_then(|v| parse_value(v));
+                if let (Some(name), Some(value)) = (name, value) {
+                    keywords.entry(name.trim().to_string()).or_insert(value);
+                }
+            }
+            "Property" => {
+                let id = element.attributes.get("id");
+                let value = element.attributes.get("value").or(element.text.as_ref());
+                if let (Some(id), Some(value)) = (id, value) {
+                    properties.insert(id.clone(), value.trim().to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut metadata = metadata_from_keywords(&keywords);
+
+    // XISF properties only fill in values the FITS keywords don't provide
+    let text = |id: &str| properties.get(id).filter(|v| !v.is_empty()).cloned();
+    let number = |id: &str| properties.get(id).and_then(|v| v.parse::<f64>().ok());
+
+    // Instrument:Camera:Gain is the conversion gain in e-/ADU, not the gain setting, so there is no fallback
+    metadata.exposure_time = metadata.exposure_time.or(number("Instrument:ExposureTime"));
+    metadata.camera_temp = metadata
+        .camera_temp
+        .or(number("Instrument:Sensor:Temperature"));
+    metadata.set_temp = metadata
+        .set_temp
+        .or(number("Instrument:Sensor:TargetTemperature"));
+    metadata.date = metadata
+        .date
+        .or(text("Observation:Time:Start").and_then(|v| parse_time_point(&v)));
+    metadata.filter = metadata.filter.or(text("Instrument:Filter:Name"));
+    metadata.target = metadata.target.or(text("Observation:Object:Name"));
+    metadata.binning = metadata
+        .binning
+        .or(number("Instrument:Camera:XBinning").map(|v| v.round() as u32));
+    metadata.camera = metadata.camera.or(text("Instrument:Camera:Name"));
+    metadata.telescope = metadata.telescope.or(text("Instrument:Telescope:Name"));
+    // the focal length property is stored in meters
+    metadata.focal_length = metadata
+        .focal_length
+        .or(number("Instrument:Telescope:FocalLength").map(|v| v * 1000.0));
+
+    Ok(metadata)
+}
+
+// the file starts with the signature, the header length and a reserved field followed by the XML header
+fn read_header(image: &PathBuf) -> Result<String, Box<dyn Error>> {
+    let mut reader = BufReader::new(File::open(image)?);
+
+    let mut preamble = [0u8; 16];
+    reader
+        .read_exact(&mut preamble)
+        .map_err(|_| "File is not a XISF file.")?;
+    if &preamble[..8] != SIGNATURE {
+        return Err("File is not a XISF file.".into());
+    }
+
+    let length =
+        u32::from_le_bytes([preamble[8], preamble[9], preamble[10], preamble[11]]) as usize;
+    if length > MAX_HEADER_LENGTH {
+        return Err("XISF header is too large.".into());
+    }
+
+    let mut header = vec![0u8; length];
+    reader
+        .read_exact(&mut header)
+        .map_err(|_| "Unexpected end of file in XISF header.")?;
+
+    Ok(String::from_utf8_lossy(&header).to_string())
+}
+
+// time points are ISO 8601, usually with a UTC offset
+fn parse_time_point(value: &str) -> Option<NaiveDateTime> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+        return Some(date_time.naive_utc());
+    }
+
+    NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y-%m-%dT%H:%M:%S%.f").ok()
+}
+
+// minimal reader for the flat elements of the header, nesting and namespaces are ignored
+struct Element {
+    name: String,
+    attributes: HashMap<String, String>,
+    text: Option<String>,
+}
+
+impl Element {
+    fn parse_all(xml: &str) -> Vec<Element> {
+        let mut elements = vec![];
+        let mut rest = xml;
+
+        while let Some(start) = rest.find('<') {
+            rest = &rest[start + 1..];
+            let end = match rest.find('>') {
+                Some(end) => end,
+                None => break,
+            };
+            let tag = &rest[..end];
+            rest = &rest[end + 1..];
+
+            if tag.starts_with(['/', '?', '!']) {
+                continue;
+            }
+
+            let self_closing = tag.ends_with('/');
+            let tag = tag.trim_end_matches('/');
+            let (name, attributes) = match tag.find(char::is_whitespace) {
+                Some(i) => (&tag[..i], parse_attributes(&tag[i..])),
+                None => (tag, HashMap::new()),
+            };
+
+            let text = if self_closing {
+                None
+            } else {
+                rest.find('<').map(|i| decode_entities(&rest[..i]))
+            };
+
+            elements.push(Element {
+                name: name.to_string(),
+                attributes,
+                text,
+            });
+        }
+
+        elements
+    }
+}
+
+fn parse_attributes(input: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let mut rest = input;
+
+    while let Some(equals) = rest.find('=') {
+        let name = rest[..equals].trim().to_string();
+        let value = rest[equals + 1..].trim_start();
+
+        let quote = match value.chars().next() {
+            Some(c) if c == '"' || c == '\'' => c,
+            _ => break,
+        };
+        let value = &value[1..];
+        let end = match value.find(quote) {
+            Some(end) => end,
+            None => break,
+        };
+
+        attributes.insert(name, decode_entities(&value[..end]));
+        rest = &value[end + 1..];
+    }
+
+    attributes
+}
+
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDir;
+
+    fn xisf_file(dir: &TempDir, header: &str) -> PathBuf {
+        let mut bytes = SIGNATURE.to_vec();
+        bytes.extend((header.len() as u32).to_le_bytes());
+        bytes.extend([0u8; 4]);
+        bytes.extend(header.as_bytes());
+
+        dir.write("Light_001.xisf", bytes)
+    }
+
+    #[test]
+    fn prefers_fits_keywords_over_properties() {
+        let dir = TempDir::new();
+        let path = xisf_file(
+            &dir,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<xisf version="1.0">
+  <Image geometry="16:16:1" sampleFormat="UInt16">
+    <FITSKeyword name="EXPTIME" value="120." comment="Exposure time"/>
+    <FITSKeyword name="OBJECT" value="'M 31'" comment=""/>
+    <Property id="Instrument:ExposureTime" type="Float32" value="60"/>
+    <Property id="Instrument:Camera:Gain" type="Float32" value="0.25"/>
+    <Property id="Instrument:Sensor:Temperature" type="Float32" value="-10"/>
+    <Property id="Instrument:Filter:Name" type="String">H&amp;a</Property>
+    <Property id="Instrument:Telescope:FocalLength" type="Float32" value="0.53"/>
+    <Property id="Observation:Time:Start" type="TimePoint" value="2024-03-09T23:15:30+02:00"/>
+  </Image>
+</xisf>"#,
+        );
+        let metadata = read_metadata(&path).unwrap();
+
+        assert_eq!(metadata.exposure_time, Some(120.0));
+        assert_eq!(metadata.target.as_deref(), Some("M 31"));
+        assert_eq!(metadata.gain, None);
+        assert_eq!(metadata.camera_temp, Some(-10.0));
+        assert_eq!(metadata.filter.as_deref(), Some("H&a"));
+        assert_eq!(metadata.focal_length, Some(530.0));
+        assert_eq!(
+            metadata.date,
+            NaiveDateTime::parse_from_str("2024-03-09 21:15:30", "%Y-%m-%d %H:%M:%S").ok()
+        );
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        let dir = TempDir::new();
+        let path = dir.write("Light_001.xisf", "SIMPLE  =                    T");
+        let result = read_metadata(&path);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parses_attributes_with_both_quotes() {
+        let attributes = parse_attributes(r#" id="a" value='b &lt; c'"#);
+
+        assert_eq!(attributes.get("id").map(String::as_str), Some("a"));
+        assert_eq!(attributes.get("value").map(String::as_str), Some("b < c"));
+    }
+}
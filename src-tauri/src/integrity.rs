@@ -0,0 +1,90 @@
+use crate::file_system::hash_file;
+use crate::image::read_metadata;
+use crate::models::database::FrameFile;
+use crate::models::frontend::process::Process;
+use crate::models::state::AppState;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use tauri::{State, Window};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub verified: u32,
+    // files classified before checksums existed, their current checksum is recorded
+    pub recorded: u32,
+    pub missing: Vec<PathBuf>,
+    pub corrupted: Vec<PathBuf>,
+    pub unreadable: Vec<PathBuf>,
+}
+
+// the database is only locked between files, hashing a library can take hours
+pub fn verify_library(
+    state: &State<AppState>,
+    window: &Window,
+) -> Result<IntegrityReport, Box<dyn Error>> {
+    let files = state
+        .db
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get_classified_frame_files()?;
+
+    let mut process = Process::spawn(
+        window,
+        "Verifying Library",
+        false,
+        Some(0),
+        Some(files.len() as u32),
+    );
+    let mut report = IntegrityReport {
+        verified: 0,
+        recorded: 0,
+        missing: vec![],
+        corrupted: vec![],
+        unreadable: vec![],
+    };
+
+    for file in files {
+        if let Err(e) = verify_file(state, file, &mut report) {
+            process.kill(window, e.to_string());
+            return Err(e);
+        }
+        process.update(window);
+    }
+
+    process.finish(window);
+
+    Ok(report)
+}
+
+fn verify_file(
+    state: &State<AppState>,
+    file: FrameFile,
+    report: &mut IntegrityReport,
+) -> Result<(), Box<dyn Error>> {
+    match (
+        hash_file(&state.root_directory.join(&file.path)),
+        file.checksum,
+    ) {
+        (Err(e), _) if e.kind() == ErrorKind::NotFound => report.missing.push(file.path),
+        (Err(_), _) => report.unreadable.push(file.path),
+        (Ok(checksum), Some(expected)) if checksum == expected => report.verified += 1,
+        (Ok(_), Some(_)) => report.corrupted.push(file.path),
+        (Ok((hash, size)), None) => {
+            let captured_at = file.captured_at.or_else(|| {
+                read_metadata(&state.root_directory.join(&file.path))
+                    .ok()?
+                    .date
+            });
+            state
+                .db
+                .lock()
+                .map_err(|e| e.to_string())?
+                .identify_frame_file(&file.path, &hash, size, captured_at)?;
+            report.recorded += 1;
+        }
+    }
+
+    Ok(())
+}
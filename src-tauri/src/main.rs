@@ -5,21 +5,32 @@ use crate::commands::equipment::{
     check_equipment_duplicate, save_camera, save_filter, save_flattener, save_mount, save_telescope,
 };
 use crate::commands::preferences::{
-    change_bias_frames_folder_path, change_dark_frames_folder_path, delete_location, save_location,
+    change_bias_frames_folder_path, change_dark_flat_frames_folder_path,
+    change_dark_frames_folder_path, change_flat_frames_folder_path, delete_location, save_location,
 };
 use crate::file_system::set_folder_invisible;
-use commands::calibration::{analyze_calibration_frames, classify_bias_frame, classify_dark_frame};
+use commands::astronomy::{get_night_times, get_target_visibility};
+use commands::calibration::{
+    analyze_calibration_frames, classify_bias_frame, classify_dark_flat_frame, classify_dark_frame,
+    classify_flat_frame, delete_calibration_frame, find_dark_flat_frames, get_dark_coverage,
+    match_calibration_frames, match_imaging_session_calibration_frames,
+};
+use commands::catalog::{resolve_target, search_catalog};
 use commands::gallery::{add_new_image, open_image};
 use commands::image::get_date;
 use commands::imaging_sessions::{
-    classify_imaging_session, edit_imaging_session, export_csv, get_image_frames_path,
-    open_imaging_session,
+    analyze_light_frames, classify_imaging_session, delete_imaging_session, edit_imaging_session,
+    export_csv, get_image_frames_path, import_csv, open_imaging_session,
 };
 use commands::preferences::{
-    change_imaging_session_folder_path, save_preferences, setup_astrolog, setup_backup,
+    change_imaging_session_folder_path, pending_relayout, preview_folder_paths, resume_relayout,
+    save_calibration_tolerances, save_calibration_validity, save_collision_handling,
+    save_file_patterns, save_import_mode, save_preferences, setup_astrolog, setup_backup,
+};
+use commands::state::{
+    add_close_lock, load_frontend_app_state, remove_close_lock, take_backfill_errors,
 };
-use commands::state::{add_close_lock, load_frontend_app_state, remove_close_lock};
-use commands::utils::{open_browser, rename_directory};
+use commands::utils::{open_browser, rename_directory, verify_library};
 use models::frontend::process::Process;
 use models::state::AppState;
 use std::env;
@@ -27,12 +38,23 @@ use std::sync::Mutex;
 use tauri::{Emitter, Manager};
 use tauri_plugin_updater::UpdaterExt;
 
+mod analyze;
+mod astronomy;
+mod calibration_match;
+mod catalog;
 mod classify;
 mod commands;
+mod csv;
+mod dark_coverage;
+mod duplicates;
 mod file_store;
 mod file_system;
 mod image;
+mod integrity;
 mod models;
+mod moon;
+#[cfg(test)]
+mod test_utils;
 
 fn main() {
     let account_id = option_env!("ACCOUNT_ID")
@@ -87,33 +109,59 @@ fn main() {
             add_close_lock,
             add_new_image,
             analyze_calibration_frames,
+            analyze_light_frames,
             change_bias_frames_folder_path,
+            change_dark_flat_frames_folder_path,
             change_dark_frames_folder_path,
+            change_flat_frames_folder_path,
             change_imaging_session_folder_path,
             check_equipment_duplicate,
             classify_bias_frame,
+            classify_dark_flat_frame,
             classify_dark_frame,
+            classify_flat_frame,
             classify_imaging_session,
+            delete_calibration_frame,
+            delete_imaging_session,
             delete_location,
             edit_imaging_session,
             export_csv,
+            find_dark_flat_frames,
+            get_dark_coverage,
             get_date,
             get_image_frames_path,
+            get_night_times,
+            get_target_visibility,
+            import_csv,
             load_frontend_app_state,
+            match_calibration_frames,
+            match_imaging_session_calibration_frames,
             open_browser,
             open_image,
             open_imaging_session,
+            pending_relayout,
+            preview_folder_paths,
             remove_close_lock,
             rename_directory,
+            resolve_target,
+            resume_relayout,
+            save_calibration_tolerances,
+            save_calibration_validity,
             save_camera,
+            save_collision_handling,
+            save_file_patterns,
             save_filter,
             save_flattener,
+            save_import_mode,
             save_location,
             save_mount,
             save_preferences,
             save_telescope,
+            search_catalog,
             setup_astrolog,
             setup_backup,
+            take_backfill_errors,
+            verify_library,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,14 +1,19 @@
+use crate::file_system::rebase_path;
 use crate::models::equipment::{Camera, EquipmentList, EquipmentNote, Filter, Flattener, Mount, Telescope};
 use crate::models::imaging_frames::bias_frame::BiasFrame;
 use crate::models::imaging_frames::calibration_type::CalibrationType;
+use crate::models::imaging_frames::dark_flat_frame::DarkFlatFrame;
 use crate::models::imaging_frames::dark_frame::DarkFrame;
 use crate::models::imaging_frames::flat_frame::FlatFrame;
 use crate::models::imaging_frames::light_frame::LightFrame;
 use crate::models::imaging_session::ImagingSession;
-use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection, Result, Row};
+use crate::models::location::{HorizonPoint, Location};
+use crate::models::relayout::{FolderKind, FolderMove};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
 use rusqlite_migration::{Migrations, M};
 use std::collections::HashMap;
+use std::error::Error;
 use std::path::PathBuf;
 use uuid::Uuid;
 
@@ -16,6 +21,13 @@ pub struct Database {
     pub conn: Connection,
 }
 
+// a classified file with the checksum it had when it entered the library
+pub struct FrameFile {
+    pub path: PathBuf,
+    pub checksum: Option<(String, u64)>,
+    pub captured_at: Option<NaiveDateTime>,
+}
+
 impl Database {
     pub fn new(root_directory: &PathBuf) -> Result<Self> {
         let db_path = root_directory.join(".astrolog").join("astrolog.db");
@@ -144,6 +156,80 @@ impl Database {
                 frame_type TEXT NOT NULL
             );",
             ),
+            M::up("ALTER TABLE light_frames ADD COLUMN imported_subs INTEGER;"),
+            // flats were only created within imaging sessions before
+            M::up("ALTER TABLE flat_frames ADD COLUMN in_imaging_session BOOLEAN NOT NULL DEFAULT 1;"),
+            M::up(
+                "CREATE TABLE IF NOT EXISTS dark_flat_frames (
+                id TEXT PRIMARY KEY,
+                camera_id TEXT NOT NULL,
+                gain INTEGER NOT NULL,
+                in_imaging_session BOOLEAN NOT NULL,
+                camera_temp REAL NOT NULL,
+                sub_length REAL NOT NULL
+            );",
+            ),
+            M::up("ALTER TABLE imaging_sessions ADD COLUMN dark_flat_frame_id TEXT;"),
+            M::up(
+                "ALTER TABLE flat_frames ADD COLUMN telescope_id TEXT;
+                ALTER TABLE flat_frames ADD COLUMN flattener_id TEXT;
+                ALTER TABLE flat_frames ADD COLUMN filter_id TEXT;",
+            ),
+            M::up("ALTER TABLE dark_frames ADD COLUMN capture_date TEXT;"),
+            M::up("ALTER TABLE bias_frames ADD COLUMN capture_date TEXT;"),
+            M::up("ALTER TABLE flat_frames ADD COLUMN capture_date TEXT;"),
+            M::up("ALTER TABLE dark_flat_frames ADD COLUMN capture_date TEXT;"),
+            M::up(
+                "ALTER TABLE light_frames ADD COLUMN moon_altitude REAL;
+                ALTER TABLE light_frames ADD COLUMN moon_separation REAL;
+                ALTER TABLE light_frames ADD COLUMN ra REAL;
+                ALTER TABLE light_frames ADD COLUMN dec REAL;",
+            ),
+            M::up(
+                "CREATE TABLE IF NOT EXISTS locations (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                latitude REAL NOT NULL CHECK (latitude BETWEEN -90 AND 90),
+                longitude REAL NOT NULL CHECK (longitude BETWEEN -180 AND 180),
+                elevation REAL NOT NULL,
+                timezone TEXT NOT NULL,
+                bortle INTEGER NOT NULL,
+                sqm REAL
+            );
+            CREATE TABLE IF NOT EXISTS location_horizons (
+                location_id TEXT NOT NULL,
+                azimuth REAL NOT NULL,
+                altitude REAL NOT NULL
+            );",
+            ),
+            M::up("ALTER TABLE light_frames ADD COLUMN night_of TEXT;"),
+            M::up("ALTER TABLE light_frames ADD COLUMN catalog_id TEXT;"),
+            // BLAKE3 checksum of classified files, recorded when they enter the library
+            M::up("ALTER TABLE frame_files ADD COLUMN hash TEXT;"),
+            M::up("ALTER TABLE frame_files ADD COLUMN size INTEGER;"),
+            // backfills that already ran, entries they couldn't fill aren't tried again
+            M::up("CREATE TABLE IF NOT EXISTS backfills (name TEXT PRIMARY KEY);"),
+            M::up("ALTER TABLE locations ADD COLUMN needs_review BOOLEAN NOT NULL DEFAULT 0;"),
+            // header date of classified files, duplicates without a matching checksum are found by name and date
+            M::up("ALTER TABLE frame_files ADD COLUMN captured_at TEXT;"),
+            // dark flats whose headers don't record the temperature or exposure leave them empty,
+            // SQLite can't drop NOT NULL so the table is rebuilt, a 0s exposure was such a missing value
+            M::up(
+                "CREATE TABLE dark_flat_frames_new (
+                id TEXT PRIMARY KEY,
+                camera_id TEXT NOT NULL,
+                gain INTEGER NOT NULL,
+                in_imaging_session BOOLEAN NOT NULL,
+                camera_temp REAL,
+                sub_length REAL,
+                capture_date TEXT
+            );
+            INSERT INTO dark_flat_frames_new
+                SELECT id, camera_id, gain, in_imaging_session, camera_temp, NULLIF(sub_length, 0), capture_date
+                FROM dark_flat_frames;
+            DROP TABLE dark_flat_frames;
+            ALTER TABLE dark_flat_frames_new RENAME TO dark_flat_frames;",
+            ),
         ]);
 
         migrations
@@ -152,6 +238,121 @@ impl Database {
         Ok(Self { conn })
     }
 
+    // runs f in one transaction, the writes below only open savepoints so they nest within it
+    pub fn in_transaction<T, F>(&mut self, f: F) -> std::result::Result<T, Box<dyn Error>>
+    where
+        F: FnOnce(&mut Database) -> std::result::Result<T, Box<dyn Error>>,
+    {
+        self.conn.execute_batch("BEGIN")?;
+
+        match f(self) {
+            Ok(value) => {
+                self.conn.execute_batch("COMMIT")?;
+                Ok(value)
+            }
+            Err(e) => {
+                self.conn.execute_batch("ROLLBACK")?;
+                Err(e)
+            }
+        }
+    }
+
+    // ------------ Locations ------------
+    pub fn insert_location(&mut self, location: &Location) -> Result<()> {
+        let tx = self.conn.savepoint()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO locations (id, name, latitude, longitude, elevation, timezone, bortle, sqm, needs_review)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+            location.id.to_string(),
+            location.name,
+            location.latitude,
+            location.longitude,
+            location.elevation,
+            location.timezone,
+            location.bortle,
+            location.sqm,
+            location.needs_review
+        ],
+        )?;
+
+        tx.execute(
+            "DELETE FROM location_horizons WHERE location_id = ?1",
+            params![location.id.to_string()],
+        )?;
+        for point in &location.horizon {
+            tx.execute(
+                "INSERT INTO location_horizons (location_id, azimuth, altitude) VALUES (?1, ?2, ?3)",
+                params![location.id.to_string(), point.azimuth, point.altitude],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn remove_location(&mut self, id: Uuid) -> Result<()> {
+        let tx = self.conn.savepoint()?;
+        tx.execute("DELETE FROM locations WHERE id = ?1", params![id.to_string()])?;
+        tx.execute(
+            "DELETE FROM location_horizons WHERE location_id = ?1",
+            params![id.to_string()],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn get_location_by_id(&self, id: Uuid) -> Result<Option<Location>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, latitude, longitude, elevation, timezone, bortle, sqm, needs_review FROM locations WHERE id = ?1",
+        )?;
+        let location = stmt
+            .query_row(params![id.to_string()], |row| self.location_from_row(row))
+            .optional()?;
+        Ok(location)
+    }
+
+    pub fn get_locations(&self) -> Result<HashMap<Uuid, Location>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, latitude, longitude, elevation, timezone, bortle, sqm, needs_review FROM locations",
+        )?;
+        let mut rows = stmt.query([])?;
+        let mut result = HashMap::new();
+        while let Some(row) = rows.next()? {
+            let location = self.location_from_row(row)?;
+            result.insert(location.id, location);
+        }
+        Ok(result)
+    }
+
+    fn location_from_row(&self, row: &Row) -> Result<Location> {
+        let id = uuid_from_column(row, 0)?.unwrap_or_else(Uuid::nil);
+
+        let mut stmt = self.conn.prepare(
+            "SELECT azimuth, altitude FROM location_horizons WHERE location_id = ?1 ORDER BY azimuth",
+        )?;
+        let horizon = stmt
+            .query_map(params![id.to_string()], |row| {
+                Ok(HorizonPoint {
+                    azimuth: row.get(0)?,
+                    altitude: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Location {
+            id,
+            name: row.get(1)?,
+            latitude: row.get(2)?,
+            longitude: row.get(3)?,
+            elevation: row.get(4)?,
+            timezone: row.get(5)?,
+            bortle: row.get(6)?,
+            sqm: row.get(7)?,
+            horizon,
+            needs_review: row.get(8)?,
+        })
+    }
+
     // ------------ Equipment ------------
     pub fn get_equipment_list(&self) -> Result<EquipmentList> {
         let list = EquipmentList {
@@ -190,7 +391,7 @@ impl Database {
     }
 
     pub fn insert_camera(&mut self, camera: &Camera) -> Result<()> {
-        let tx = self.conn.transaction()?;
+        let tx = self.conn.savepoint()?;
         tx.execute(
             "INSERT OR REPLACE INTO cameras (id, brand, name, pixel_size, pixel_x, pixel_y, is_monochrome, is_dslr)
          VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
@@ -226,7 +427,7 @@ impl Database {
     }
 
     pub fn remove_camera(&mut self, id: Uuid) -> Result<()> {
-        let tx = self.conn.transaction()?;
+        let tx = self.conn.savepoint()?;
         tx.execute("DELETE FROM cameras WHERE id = ?1", params![id.to_string()])?;
         tx.execute(
             "DELETE FROM equipment_notes WHERE equipment_id = ?1",
@@ -288,7 +489,7 @@ impl Database {
     }
 
     pub fn insert_telescope(&mut self, telescope: &Telescope) -> Result<()> {
-        let tx = self.conn.transaction()?;
+        let tx = self.conn.savepoint()?;
         tx.execute(
             "INSERT OR REPLACE INTO telescopes (id, brand, name, focal_length, aperture) VALUES (?1, ?2, ?3, ?4, ?5)",
             params![
@@ -319,7 +520,7 @@ impl Database {
     }
 
     pub fn remove_telescope(&mut self, id: Uuid) -> Result<()> {
-        let tx = self.conn.transaction()?;
+        let tx = self.conn.savepoint()?;
         tx.execute(
             "DELETE FROM telescopes WHERE id = ?1",
             params![id.to_string()],
@@ -378,7 +579,7 @@ impl Database {
     }
 
     pub fn insert_mount(&mut self, mount: &Mount) -> Result<()> {
-        let tx = self.conn.transaction()?;
+        let tx = self.conn.savepoint()?;
         tx.execute(
             "INSERT OR REPLACE INTO mounts (id, brand, name) VALUES (?1, ?2, ?3)",
             params![mount.id.to_string(), mount.brand, mount.name,],
@@ -403,7 +604,7 @@ impl Database {
     }
 
     pub fn remove_mount(&mut self, id: Uuid) -> Result<()> {
-        let tx = self.conn.transaction()?;
+        let tx = self.conn.savepoint()?;
         tx.execute("DELETE FROM mounts WHERE id = ?1", params![id.to_string()])?;
         tx.execute(
             "DELETE FROM equipment_notes WHERE equipment_id = ?1",
@@ -453,7 +654,7 @@ impl Database {
     }
 
     pub fn insert_filter(&mut self, filter: &Filter) -> Result<()> {
-        let tx = self.conn.transaction()?;
+        let tx = self.conn.savepoint()?;
         tx.execute(
             "INSERT OR REPLACE INTO filters (id, brand, name, filter_type) VALUES (?1, ?2, ?3, ?4)",
             params![
@@ -483,7 +684,7 @@ impl Database {
     }
 
     pub fn remove_filter(&mut self, id: Uuid) -> Result<()> {
-        let tx = self.conn.transaction()?;
+        let tx = self.conn.savepoint()?;
         tx.execute("DELETE FROM filters WHERE id = ?1", params![id.to_string()])?;
         tx.execute(
             "DELETE FROM equipment_notes WHERE equipment_id = ?1",
@@ -537,7 +738,7 @@ impl Database {
     }
 
     pub fn insert_flattener(&mut self, flattener: &Flattener) -> Result<()> {
-        let tx = self.conn.transaction()?;
+        let tx = self.conn.savepoint()?;
         tx.execute(
             "INSERT OR REPLACE INTO flatteners (id, brand, name, factor) VALUES (?1, ?2, ?3, ?4)",
             params![
@@ -567,7 +768,7 @@ impl Database {
     }
 
     pub fn remove_flattener(&mut self, id: Uuid) -> Result<()> {
-        let tx = self.conn.transaction()?;
+        let tx = self.conn.savepoint()?;
         tx.execute(
             "DELETE FROM flatteners WHERE id = ?1",
             params![id.to_string()],
@@ -625,42 +826,166 @@ impl Database {
 
     // ------------ Imaging Sessions ------------
     pub fn insert_imaging_session(&self, imaging_session: &ImagingSession) -> Result<()> {
-        println!("test");
-        self.conn.execute(
-            "INSERT OR REPLACE INTO imaging_sessions (id, folder_dir, light_frame_id, flat_frame_id, dark_frame_id, bias_frame_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![
-                imaging_session.id.to_string(),
-                imaging_session.folder_dir.to_string_lossy(),
-                imaging_session.light_frame_id.to_string(),
-                imaging_session
-                    .flat_frame_id
-                    .as_ref()
-                    .map(|id| id.to_string()),
-                imaging_session
-                    .dark_frame_id
-                    .as_ref()
-                    .map(|id| id.to_string()),
-                imaging_session
-                    .bias_frame_id
-                    .as_ref()
-                    .map(|id| id.to_string()),
-            ],
-        )?;
-        println!("test2");
+        write_imaging_session(&self.conn, imaging_session)
+    }
+
+    // updates a session together with its light frame and moves the classified frame paths
+    // of the session from old_dir to the current folder_dir
+    pub fn update_imaging_session(
+        &mut self,
+        imaging_session: &ImagingSession,
+        light_frame: &LightFrame,
+        old_dir: &PathBuf,
+    ) -> Result<()> {
+        let tx = self.conn.savepoint()?;
+
+        // the files are moved first, so the light frame finds the checksums under its rebased paths
+        for frame_id in [
+            Some(imaging_session.light_frame_id),
+            imaging_session.flat_frame_id,
+            imaging_session.dark_frame_id,
+            imaging_session.dark_flat_frame_id,
+        ]
+            .iter()
+            .flatten()
+        {
+            relocate_frame_files(&tx, frame_id, old_dir, &imaging_session.folder_dir)?;
+        }
+
+        write_imaging_session(&tx, imaging_session)?;
+        write_light_frame(&tx, light_frame)?;
+
+        tx.commit()?;
         Ok(())
     }
 
+    // rewrites the folder of every moved session and calibration set in a single transaction
+    pub fn relocate_folders(&mut self, moves: &Vec<FolderMove>) -> Result<()> {
+        let tx = self.conn.savepoint()?;
+
+        for folder_move in moves {
+            let frame_ids = match folder_move.kind {
+                FolderKind::IMAGINGSESSION => {
+                    tx.execute(
+                        "UPDATE imaging_sessions SET folder_dir = ?1 WHERE id = ?2",
+                        params![
+                            folder_move.new_dir.to_string_lossy(),
+                            folder_move.id.to_string()
+                        ],
+                    )?;
+
+                    let ids: Option<(
+                        String,
+                        Option<String>,
+                        Option<String>,
+                        Option<String>,
+                    )> = tx
+                        .query_row(
+                            "SELECT light_frame_id, flat_frame_id, dark_frame_id, dark_flat_frame_id FROM imaging_sessions WHERE id = ?1",
+                            params![folder_move.id.to_string()],
+                            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+                        )
+                        .optional()?;
+
+                    match ids {
+                        Some((light, flat, dark, dark_flat)) => {
+                            [Some(light), flat, dark, dark_flat]
+                                .into_iter()
+                                .flatten()
+                                .filter_map(|id| Uuid::parse_str(&id).ok())
+                                .collect()
+                        }
+                        None => vec![],
+                    }
+                }
+                FolderKind::DARK
+                | FolderKind::BIAS
+                | FolderKind::FLAT
+                | FolderKind::DARKFLAT => vec![folder_move.id],
+            };
+
+            for frame_id in frame_ids {
+                relocate_frame_files(&tx, &frame_id, &folder_move.old_dir, &folder_move.new_dir)?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    // removes the session with its light frame and the calibration frames taken within the session
     pub fn remove_imaging_session(&mut self, id: Uuid) -> Result<()> {
-        self.conn.execute(
+        let session = self.get_imaging_session_by_id(id)?;
+        let tx = self.conn.savepoint()?;
+
+        if let Some(session) = session {
+            tx.execute(
+                "DELETE FROM frame_files WHERE frame_id = ?1 AND frame_type = 'light'",
+                params![session.light_frame_id.to_string()],
+            )?;
+            tx.execute(
+                "DELETE FROM light_frames WHERE id = ?1",
+                params![session.light_frame_id.to_string()],
+            )?;
+
+            // calibration frames from the library stay untouched
+            let frames = [
+                ("flat_frames", "flat", session.flat_frame_id),
+                ("dark_frames", "dark", session.dark_frame_id),
+                ("dark_flat_frames", "darkflat", session.dark_flat_frame_id),
+            ];
+            for (table, frame_type, frame_id) in frames {
+                let frame_id = match frame_id {
+                    Some(frame_id) => frame_id.to_string(),
+                    None => continue,
+                };
+
+                let in_imaging_session: Option<bool> = tx
+                    .query_row(
+                        &format!("SELECT in_imaging_session FROM {} WHERE id = ?1", table),
+                        params![frame_id],
+                        |row| row.get(0),
+                    )
+                    .optional()?;
+
+                if in_imaging_session == Some(true) {
+                    tx.execute(
+                        "DELETE FROM frame_files WHERE frame_id = ?1 AND frame_type = ?2",
+                        params![frame_id, frame_type],
+                    )?;
+                    tx.execute(
+                        &format!("DELETE FROM {} WHERE id = ?1", table),
+                        params![frame_id],
+                    )?;
+                }
+            }
+        }
+
+        tx.execute(
             "DELETE FROM imaging_sessions WHERE id = ?1",
             params![id.to_string()],
         )?;
+
+        tx.commit()?;
         Ok(())
     }
 
+    // returns the ids of all sessions that link the given calibration frames
+    pub fn get_imaging_sessions_using(&self, calibration_id: &Uuid) -> Result<Vec<Uuid>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id FROM imaging_sessions WHERE dark_frame_id = ?1 OR bias_frame_id = ?1 OR flat_frame_id = ?1 OR dark_flat_frame_id = ?1",
+        )?;
+        let rows = stmt.query_map(params![calibration_id.to_string()], |row| {
+            let id_str: String = row.get(0)?;
+            Ok(Uuid::parse_str(&id_str).unwrap_or_else(|_| Uuid::nil()))
+        })?;
+
+        rows.collect()
+    }
+
     pub fn get_imaging_session_by_id(&self, id: Uuid) -> Result<Option<ImagingSession>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, folder_dir, light_frame_id, flat_frame_id, dark_frame_id, bias_frame_id
+            "SELECT id, folder_dir, light_frame_id, flat_frame_id, dark_frame_id, bias_frame_id, dark_flat_frame_id
          FROM imaging_sessions WHERE id = ?1",
         )?;
         let mut rows = stmt.query([id.to_string()])?;
@@ -674,7 +999,7 @@ impl Database {
 
     pub fn get_imaging_sessions(&self) -> Result<HashMap<Uuid, ImagingSession>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, folder_dir, light_frame_id, flat_frame_id, dark_frame_id, bias_frame_id
+            "SELECT id, folder_dir, light_frame_id, flat_frame_id, dark_frame_id, bias_frame_id, dark_flat_frame_id
          FROM imaging_sessions",
         )?;
         let mut rows = stmt.query([])?;
@@ -703,7 +1028,11 @@ impl Database {
             date: DateTime::parse_from_rfc3339(&row.get::<_, String>("date")?)
                 .unwrap()
                 .with_timezone(&Utc),
+            night_of: row
+                .get::<_, Option<String>>("night_of")?
+                .and_then(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d").ok()),
             target: row.get("target")?,
+            catalog_id: row.get("catalog_id")?,
             location_id: Uuid::parse_str(&row.get::<_, String>("location_id")?).unwrap(),
             gain: row.get("gain")?,
             offset: row.get("offset")?,
@@ -723,9 +1052,63 @@ impl Database {
             average_seeing: row.get("average_seeing")?,
             average_cloud_cover: row.get("average_cloud_cover")?,
             average_moon: row.get("average_moon")?,
+            moon_altitude: row.get("moon_altitude")?,
+            moon_separation: row.get("moon_separation")?,
+            ra: row.get("ra")?,
+            dec: row.get("dec")?,
+            imported_subs: row.get("imported_subs")?,
         })
     }
 
+    pub fn get_classified_frame_files(&self) -> Result<Vec<FrameFile>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, hash, size, captured_at FROM frame_files WHERE classified = 1")?;
+
+        let rows = stmt.query_map([], |row| {
+            let hash: Option<String> = row.get(1)?;
+            let size: Option<i64> = row.get(2)?;
+            Ok(FrameFile {
+                path: PathBuf::from(row.get::<_, String>(0)?),
+                checksum: hash.zip(size).map(|(hash, size)| (hash, size as u64)),
+                captured_at: captured_at_from_column(row, 3)?,
+            })
+        })?;
+
+        rows.collect()
+    }
+
+    // paths are relative to the root directory, as in the frame lists
+    pub fn identify_frame_file(
+        &self,
+        path: &PathBuf,
+        hash: &str,
+        size: u64,
+        captured_at: Option<NaiveDateTime>,
+    ) -> Result<()> {
+        self.conn.execute(
+            "UPDATE frame_files SET hash = ?1, size = ?2, captured_at = ?3 WHERE path = ?4 AND classified = 1",
+            params![
+                hash,
+                size as i64,
+                captured_at.map(|date| date.format(CAPTURED_AT_FORMAT).to_string()),
+                path.to_string_lossy()
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_frame_file_captured_at(&self, path: &PathBuf, captured_at: &NaiveDateTime) -> Result<()> {
+        self.conn.execute(
+            "UPDATE frame_files SET captured_at = ?1 WHERE path = ?2 AND classified = 1",
+            params![
+                captured_at.format(CAPTURED_AT_FORMAT).to_string(),
+                path.to_string_lossy()
+            ],
+        )?;
+        Ok(())
+    }
+
     fn get_frame_files_by_classification(
         &self,
         frame_id: &Uuid,
@@ -756,69 +1139,14 @@ impl Database {
     }
 
     pub fn insert_light_frame(&mut self, frame: &LightFrame) -> Result<()> {
-        let tx = self.conn.transaction()?;
-
-        tx.execute(
-            "INSERT OR REPLACE INTO light_frames (
-            id, date, target, location_id, gain, offset, camera_temp, notes, sub_length,
-            camera_id, telescope_id, mount_id, flattener_id, filter_id,
-            outside_temp, average_seeing, average_cloud_cover, average_moon
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-            rusqlite::params![
-            frame.id.to_string(),
-            frame.date.to_rfc3339(),
-            frame.target,
-            frame.location_id.to_string(),
-            frame.gain,
-            frame.offset,
-            frame.camera_temp,
-            frame.notes,
-            frame.sub_length,
-            frame.camera_id.to_string(),
-            frame.telescope_id.to_string(),
-            frame.mount_id.to_string(),
-            frame.flattener_id.map(|id| id.to_string()),
-            frame.filter_id.map(|id| id.to_string()),
-            frame.outside_temp,
-            frame.average_seeing,
-            frame.average_cloud_cover,
-            frame.average_moon,
-        ],
-        )?;
-
-        tx.execute(
-            "DELETE FROM frame_files WHERE frame_id = ?",
-            rusqlite::params![frame.id.to_string()],
-        )?;
-
-        let insert_file = |path: &PathBuf, classified: bool| -> Result<()> {
-            let id = Uuid::new_v4();
-            tx.execute(
-                "INSERT OR REPLACE INTO frame_files (id, frame_id, path, classified, frame_type) VALUES (?, ?, ?, ?, ?)",
-                rusqlite::params![
-                id.to_string(),
-                frame.id.to_string(),
-                path.to_string_lossy(),
-                classified as i32,
-                "light"
-            ],
-            )?;
-            Ok(())
-        };
-
-        for path in &frame.frames_to_classify {
-            insert_file(path, false)?;
-        }
-        for path in &frame.frames_classified {
-            insert_file(path, true)?;
-        }
-
+        let tx = self.conn.savepoint()?;
+        write_light_frame(&tx, frame)?;
         tx.commit()?;
         Ok(())
     }
 
     pub fn remove_light_frame(&mut self, id: Uuid) -> Result<()> {
-        let tx = self.conn.transaction()?;
+        let tx = self.conn.savepoint()?;
         tx.execute(
             "DELETE FROM frame_files WHERE frame_id = ?1 AND frame_type = 'light'",
             rusqlite::params![id.to_string()],
@@ -858,13 +1186,13 @@ impl Database {
     }
 
     pub fn insert_dark_frame(&mut self, frame: &DarkFrame) -> Result<()> {
-        let tx = self.conn.transaction()?;
+        let tx = self.conn.savepoint()?;
 
         tx.execute(
             "INSERT OR REPLACE INTO dark_frames (
             id, camera_id, gain, in_imaging_session,
-            camera_temp, sub_length
-        ) VALUES (?, ?, ?, ?, ?, ?)",
+            camera_temp, sub_length, capture_date
+        ) VALUES (?, ?, ?, ?, ?, ?, ?)",
             rusqlite::params![
             frame.id.to_string(),
             frame.camera_id.to_string(),
@@ -872,42 +1200,24 @@ impl Database {
             frame.in_imaging_session as i32,
             frame.camera_temp,
             frame.sub_length,
+            frame.capture_date.map(|date| date.to_rfc3339()),
         ],
         )?;
 
-        tx.execute(
-            "DELETE FROM frame_files WHERE frame_id = ?",
-            rusqlite::params![frame.id.to_string()],
+        write_frame_files(
+            &tx,
+            &frame.id,
+            "dark",
+            &frame.frames_to_classify,
+            &frame.frames_classified,
         )?;
 
-        let insert_file = |path: &PathBuf, classified: bool| -> Result<()> {
-            let id = Uuid::new_v4();
-            tx.execute(
-                "INSERT OR REPLACE INTO frame_files (id, frame_id, path, classified, frame_type) VALUES (?, ?, ?, ?, ?)",
-                rusqlite::params![
-                id.to_string(),
-                frame.id.to_string(),
-                path.to_string_lossy(),
-                classified as i32,
-                "dark"
-            ],
-            )?;
-            Ok(())
-        };
-
-        for path in &frame.frames_to_classify {
-            insert_file(path, false)?;
-        }
-        for path in &frame.frames_classified {
-            insert_file(path, true)?;
-        }
-
         tx.commit()?;
         Ok(())
     }
 
     pub fn remove_dark_frame(&mut self, id: Uuid) -> Result<()> {
-        let tx = self.conn.transaction()?;
+        let tx = self.conn.savepoint()?;
 
         tx.execute(
             "DELETE FROM frame_files WHERE frame_id = ?1 AND frame_type = 'dark'",
@@ -925,7 +1235,7 @@ impl Database {
 
     pub fn get_dark_frame_by_id(&self, id: &Uuid) -> Result<Option<DarkFrame>> {
         let mut stmt = self.conn.prepare(
-            "SELECT camera_id, gain, in_imaging_session, camera_temp, sub_length FROM dark_frames WHERE id = ?1",
+            "SELECT camera_id, gain, in_imaging_session, camera_temp, sub_length, capture_date FROM dark_frames WHERE id = ?1",
         )?;
 
         let mut rows = stmt.query([id.to_string()])?;
@@ -943,6 +1253,7 @@ impl Database {
                 calibration_type: CalibrationType::DARK,
                 camera_temp: row.get(3)?,
                 sub_length: row.get(4)?,
+                capture_date: date_from_column(row, 5)?,
             }))
         } else {
             Ok(None)
@@ -951,7 +1262,7 @@ impl Database {
 
     pub fn get_dark_frames(&self) -> Result<HashMap<Uuid, DarkFrame>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, camera_id, gain, in_imaging_session, camera_temp, sub_length FROM dark_frames",
+            "SELECT id, camera_id, gain, in_imaging_session, camera_temp, sub_length, capture_date FROM dark_frames",
         )?;
 
         let frames_iter = stmt.query_map([], |row| {
@@ -972,6 +1283,7 @@ impl Database {
                     calibration_type: CalibrationType::DARK,
                     camera_temp: row.get(4)?,
                     sub_length: row.get(5)?,
+                    capture_date: date_from_column(row, 6)?,
                 },
             ))
         })?;
@@ -986,52 +1298,39 @@ impl Database {
     }
 
     pub fn insert_flat_frame(&mut self, frame: &FlatFrame) -> Result<()> {
-        let tx = self.conn.transaction()?;
+        let tx = self.conn.savepoint()?;
 
         tx.execute(
             "INSERT OR REPLACE INTO flat_frames (
-            id, camera_id, gain
-        ) VALUES (?, ?, ?)",
+            id, camera_id, gain, in_imaging_session, capture_date,
+            telescope_id, flattener_id, filter_id
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
             rusqlite::params![
             frame.id.to_string(),
             frame.camera_id.to_string(),
             frame.gain,
+            frame.in_imaging_session as i32,
+            frame.capture_date.map(|date| date.to_rfc3339()),
+            frame.telescope_id.map(|id| id.to_string()),
+            frame.flattener_id.map(|id| id.to_string()),
+            frame.filter_id.map(|id| id.to_string()),
         ],
         )?;
 
-        tx.execute(
-            "DELETE FROM frame_files WHERE frame_id = ?",
-            rusqlite::params![frame.id.to_string()],
+        write_frame_files(
+            &tx,
+            &frame.id,
+            "flat",
+            &frame.frames_to_classify,
+            &frame.frames_classified,
         )?;
 
-        let insert_file = |path: &PathBuf, classified: bool| -> Result<()> {
-            let id = Uuid::new_v4();
-            tx.execute(
-                "INSERT OR REPLACE INTO frame_files (id, frame_id, path, classified, frame_type) VALUES (?, ?, ?, ?, ?)",
-                rusqlite::params![
-                id.to_string(),
-                frame.id.to_string(),
-                path.to_string_lossy(),
-                classified as i32,
-                "flat"
-            ],
-            )?;
-            Ok(())
-        };
-
-        for path in &frame.frames_to_classify {
-            insert_file(path, false)?;
-        }
-        for path in &frame.frames_classified {
-            insert_file(path, true)?;
-        }
-
         tx.commit()?;
         Ok(())
     }
 
     pub fn remove_flat_frame(&mut self, id: Uuid) -> Result<()> {
-        let tx = self.conn.transaction()?;
+        let tx = self.conn.savepoint()?;
 
         tx.execute(
             "DELETE FROM frame_files WHERE frame_id = ?1 AND frame_type = 'flat'",
@@ -1048,9 +1347,9 @@ impl Database {
     }
 
     pub fn get_flat_frame_by_id(&self, id: &Uuid) -> Result<Option<FlatFrame>> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT camera_id, gain FROM flat_frames WHERE id = ?1")?;
+        let mut stmt = self.conn.prepare(
+            "SELECT camera_id, gain, in_imaging_session, capture_date, telescope_id, flattener_id, filter_id FROM flat_frames WHERE id = ?1",
+        )?;
 
         let mut rows = stmt.query([id.to_string()])?;
         if let Some(row) = rows.next()? {
@@ -1063,6 +1362,12 @@ impl Database {
                 gain: row.get(1)?,
                 frames_to_classify,
                 frames_classified,
+                in_imaging_session: row.get(2)?,
+                calibration_type: CalibrationType::FLAT,
+                capture_date: date_from_column(row, 3)?,
+                telescope_id: uuid_from_column(row, 4)?,
+                flattener_id: uuid_from_column(row, 5)?,
+                filter_id: uuid_from_column(row, 6)?,
             }))
         } else {
             Ok(None)
@@ -1070,9 +1375,9 @@ impl Database {
     }
 
     pub fn get_flat_frames(&self) -> Result<HashMap<Uuid, FlatFrame>> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT id, camera_id, gain FROM flat_frames")?;
+        let mut stmt = self.conn.prepare(
+            "SELECT id, camera_id, gain, in_imaging_session, capture_date, telescope_id, flattener_id, filter_id FROM flat_frames",
+        )?;
 
         let frames_iter = stmt.query_map([], |row| {
             let id_str: String = row.get(0)?;
@@ -1088,6 +1393,12 @@ impl Database {
                     gain: row.get(2)?,
                     frames_to_classify,
                     frames_classified,
+                    in_imaging_session: row.get(3)?,
+                    calibration_type: CalibrationType::FLAT,
+                    capture_date: date_from_column(row, 4)?,
+                    telescope_id: uuid_from_column(row, 5)?,
+                    flattener_id: uuid_from_column(row, 6)?,
+                    filter_id: uuid_from_column(row, 7)?,
                 },
             ))
         })?;
@@ -1101,53 +1412,147 @@ impl Database {
         Ok(map)
     }
 
-    pub fn insert_bias_frame(&mut self, frame: &BiasFrame) -> Result<()> {
-        let tx = self.conn.transaction()?;
+    pub fn insert_dark_flat_frame(&mut self, frame: &DarkFlatFrame) -> Result<()> {
+        let tx = self.conn.savepoint()?;
 
         tx.execute(
-            "INSERT OR REPLACE INTO bias_frames (
-            id, camera_id, gain
-        ) VALUES (?, ?, ?)",
+            "INSERT OR REPLACE INTO dark_flat_frames (
+            id, camera_id, gain, in_imaging_session,
+            camera_temp, sub_length, capture_date
+        ) VALUES (?, ?, ?, ?, ?, ?, ?)",
             rusqlite::params![
             frame.id.to_string(),
             frame.camera_id.to_string(),
             frame.gain,
+            frame.in_imaging_session as i32,
+            frame.camera_temp,
+            frame.sub_length,
+            frame.capture_date.map(|date| date.to_rfc3339()),
         ],
         )?;
 
+        write_frame_files(
+            &tx,
+            &frame.id,
+            "darkflat",
+            &frame.frames_to_classify,
+            &frame.frames_classified,
+        )?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn remove_dark_flat_frame(&mut self, id: Uuid) -> Result<()> {
+        let tx = self.conn.savepoint()?;
+
+        tx.execute(
+            "DELETE FROM frame_files WHERE frame_id = ?1 AND frame_type = 'darkflat'",
+            rusqlite::params![id.to_string()],
+        )?;
+
         tx.execute(
-            "DELETE FROM frame_files WHERE frame_id = ?",
-            rusqlite::params![frame.id.to_string()],
+            "DELETE FROM dark_flat_frames WHERE id = ?1",
+            rusqlite::params![id.to_string()],
         )?;
 
-        let insert_file = |path: &PathBuf, classified: bool| -> Result<()> {
-            let id = Uuid::new_v4();
-            tx.execute(
-                "INSERT OR REPLACE INTO frame_files (id, frame_id, path, classified, frame_type) VALUES (?, ?, ?, ?, ?)",
-                rusqlite::params![
-                id.to_string(),
-                frame.id.to_string(),
-                path.to_string_lossy(),
-                classified as i32,
-                "bias"
-            ],
-            )?;
-            Ok(())
-        };
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn get_dark_flat_frame_by_id(&self, id: &Uuid) -> Result<Option<DarkFlatFrame>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT camera_id, gain, in_imaging_session, camera_temp, sub_length, capture_date FROM dark_flat_frames WHERE id = ?1",
+        )?;
 
-        for path in &frame.frames_to_classify {
-            insert_file(path, false)?;
+        let mut rows = stmt.query([id.to_string()])?;
+        if let Some(row) = rows.next()? {
+            let frames_to_classify = self.get_frame_files_by_classification(&id, false, "darkflat")?;
+            let frames_classified = self.get_frame_files_by_classification(&id, true, "darkflat")?;
+            Ok(Some(DarkFlatFrame {
+                id: *id,
+                camera_id: Uuid::parse_str(&row.get::<_, String>(0)?)
+                    .unwrap_or_else(|_| Uuid::nil()),
+                gain: row.get(1)?,
+                frames_to_classify,
+                frames_classified,
+                in_imaging_session: row.get(2)?,
+                calibration_type: CalibrationType::DARKFLAT,
+                camera_temp: row.get(3)?,
+                sub_length: row.get(4)?,
+                capture_date: date_from_column(row, 5)?,
+            }))
+        } else {
+            Ok(None)
         }
-        for path in &frame.frames_classified {
-            insert_file(path, true)?;
+    }
+
+    pub fn get_dark_flat_frames(&self) -> Result<HashMap<Uuid, DarkFlatFrame>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, camera_id, gain, in_imaging_session, camera_temp, sub_length, capture_date FROM dark_flat_frames",
+        )?;
+
+        let frames_iter = stmt.query_map([], |row| {
+            let id_str: String = row.get(0)?;
+            let id = Uuid::parse_str(&id_str).unwrap();
+            let frames_to_classify = self.get_frame_files_by_classification(&id, false, "darkflat")?;
+            let frames_classified = self.get_frame_files_by_classification(&id, true, "darkflat")?;
+            Ok((
+                id,
+                DarkFlatFrame {
+                    id,
+                    camera_id: Uuid::parse_str(&row.get::<_, String>(1)?)
+                        .unwrap_or_else(|_| Uuid::nil()),
+                    gain: row.get(2)?,
+                    frames_to_classify,
+                    frames_classified,
+                    in_imaging_session: row.get(3)?,
+                    calibration_type: CalibrationType::DARKFLAT,
+                    camera_temp: row.get(4)?,
+                    sub_length: row.get(5)?,
+                    capture_date: date_from_column(row, 6)?,
+                },
+            ))
+        })?;
+
+        let mut map = HashMap::new();
+        for result in frames_iter {
+            let (id, frame) = result?;
+            map.insert(id, frame);
         }
 
+        Ok(map)
+    }
+
+    pub fn insert_bias_frame(&mut self, frame: &BiasFrame) -> Result<()> {
+        let tx = self.conn.savepoint()?;
+
+        tx.execute(
+            "INSERT OR REPLACE INTO bias_frames (
+            id, camera_id, gain, capture_date
+        ) VALUES (?, ?, ?, ?)",
+            rusqlite::params![
+            frame.id.to_string(),
+            frame.camera_id.to_string(),
+            frame.gain,
+            frame.capture_date.map(|date| date.to_rfc3339()),
+        ],
+        )?;
+
+        write_frame_files(
+            &tx,
+            &frame.id,
+            "bias",
+            &frame.frames_to_classify,
+            &frame.frames_classified,
+        )?;
+
         tx.commit()?;
         Ok(())
     }
 
     pub fn remove_bias_frame(&mut self, id: Uuid) -> Result<()> {
-        let tx = self.conn.transaction()?;
+        let tx = self.conn.savepoint()?;
 
         tx.execute(
             "DELETE FROM frame_files WHERE frame_id = ?1 AND frame_type = 'bias'",
@@ -1166,7 +1571,7 @@ impl Database {
     pub fn get_bias_frame_by_id(&self, id: &Uuid) -> Result<Option<BiasFrame>> {
         let mut stmt = self
             .conn
-            .prepare("SELECT camera_id, gain FROM bias_frames WHERE id = ?1")?;
+            .prepare("SELECT camera_id, gain, capture_date FROM bias_frames WHERE id = ?1")?;
 
         let mut rows = stmt.query([id.to_string()])?;
         if let Some(row) = rows.next()? {
@@ -1180,6 +1585,7 @@ impl Database {
                 frames_to_classify,
                 frames_classified,
                 calibration_type: CalibrationType::BIAS,
+                capture_date: date_from_column(row, 2)?,
             }))
         } else {
             Ok(None)
@@ -1189,7 +1595,7 @@ impl Database {
     pub fn get_bias_frames(&self) -> Result<HashMap<Uuid, BiasFrame>> {
         let mut stmt = self
             .conn
-            .prepare("SELECT id, camera_id, gain FROM bias_frames")?;
+            .prepare("SELECT id, camera_id, gain, capture_date FROM bias_frames")?;
 
         let frames_iter = stmt.query_map([], |row| {
             let id_str: String = row.get(0)?;
@@ -1206,6 +1612,7 @@ impl Database {
                     frames_to_classify,
                     frames_classified,
                     calibration_type: CalibrationType::BIAS,
+                    capture_date: date_from_column(row, 3)?,
                 },
             ))
         })?;
@@ -1218,6 +1625,59 @@ impl Database {
 
         Ok(map)
     }
+
+    // ids are unique across the calibration tables, so only the table holding the set is updated
+    pub fn update_capture_date(&self, id: &Uuid, capture_date: &DateTime<Utc>) -> Result<()> {
+        for table in ["dark_frames", "bias_frames", "flat_frames", "dark_flat_frames"] {
+            self.conn.execute(
+                &format!("UPDATE {} SET capture_date = ?1 WHERE id = ?2", table),
+                params![capture_date.to_rfc3339(), id.to_string()],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn backfill_done(&self, name: &str) -> Result<bool> {
+        self.conn
+            .query_row(
+                "SELECT 1 FROM backfills WHERE name = ?1",
+                params![name],
+                |_| Ok(()),
+            )
+            .optional()
+            .map(|row| row.is_some())
+    }
+
+    pub fn mark_backfill_done(&self, name: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO backfills (name) VALUES (?1)",
+            params![name],
+        )?;
+        Ok(())
+    }
+}
+
+// header dates have no time zone, they are compared as they were read
+const CAPTURED_AT_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+fn captured_at_from_column(row: &Row, index: usize) -> Result<Option<NaiveDateTime>> {
+    Ok(row
+        .get::<_, Option<String>>(index)?
+        .and_then(|date| NaiveDateTime::parse_from_str(&date, CAPTURED_AT_FORMAT).ok()))
+}
+
+fn date_from_column(row: &Row, index: usize) -> Result<Option<DateTime<Utc>>> {
+    Ok(row
+        .get::<_, Option<String>>(index)?
+        .and_then(|date| DateTime::parse_from_rfc3339(&date).ok())
+        .map(|date| date.with_timezone(&Utc)))
+}
+
+fn uuid_from_column(row: &Row, index: usize) -> Result<Option<Uuid>> {
+    Ok(row
+        .get::<_, Option<String>>(index)?
+        .and_then(|id| Uuid::parse_str(&id).ok()))
 }
 
 fn imaging_session_from_row(row: &Row) -> Result<ImagingSession> {
@@ -1237,5 +1697,216 @@ fn imaging_session_from_row(row: &Row) -> Result<ImagingSession> {
             .get::<_, Option<String>>(5)?
             .as_deref()
             .and_then(|s| Uuid::parse_str(s).ok()),
+        dark_flat_frame_id: row
+            .get::<_, Option<String>>(6)?
+            .as_deref()
+            .and_then(|s| Uuid::parse_str(s).ok()),
     })
 }
+
+fn write_imaging_session(conn: &Connection, imaging_session: &ImagingSession) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO imaging_sessions (id, folder_dir, light_frame_id, flat_frame_id, dark_frame_id, bias_frame_id, dark_flat_frame_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            imaging_session.id.to_string(),
+            imaging_session.folder_dir.to_string_lossy(),
+            imaging_session.light_frame_id.to_string(),
+            imaging_session
+                .flat_frame_id
+                .as_ref()
+                .map(|id| id.to_string()),
+            imaging_session
+                .dark_frame_id
+                .as_ref()
+                .map(|id| id.to_string()),
+            imaging_session
+                .bias_frame_id
+                .as_ref()
+                .map(|id| id.to_string()),
+            imaging_session
+                .dark_flat_frame_id
+                .as_ref()
+                .map(|id| id.to_string()),
+        ],
+    )?;
+    Ok(())
+}
+
+fn write_light_frame(tx: &Connection, frame: &LightFrame) -> Result<()> {
+    tx.execute(
+        "INSERT OR REPLACE INTO light_frames (
+        id, date, target, location_id, gain, offset, camera_temp, notes, sub_length,
+        camera_id, telescope_id, mount_id, flattener_id, filter_id,
+        outside_temp, average_seeing, average_cloud_cover, average_moon, imported_subs,
+        moon_altitude, moon_separation, ra, dec, night_of, catalog_id
+    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        rusqlite::params![
+        frame.id.to_string(),
+        frame.date.to_rfc3339(),
+        frame.target,
+        frame.location_id.to_string(),
+        frame.gain,
+        frame.offset,
+        frame.camera_temp,
+        frame.notes,
+        frame.sub_length,
+        frame.camera_id.to_string(),
+        frame.telescope_id.to_string(),
+        frame.mount_id.to_string(),
+        frame.flattener_id.map(|id| id.to_string()),
+        frame.filter_id.map(|id| id.to_string()),
+        frame.outside_temp,
+        frame.average_seeing,
+        frame.average_cloud_cover,
+        frame.average_moon,
+        frame.imported_subs,
+        frame.moon_altitude,
+        frame.moon_separation,
+        frame.ra,
+        frame.dec,
+        frame.night_of.map(|date| date.format("%Y-%m-%d").to_string()),
+        frame.catalog_id,
+    ],
+    )?;
+
+    write_frame_files(
+        &tx,
+        &frame.id,
+        "light",
+        &frame.frames_to_classify,
+        &frame.frames_classified,
+    )?;
+
+    Ok(())
+}
+
+// rewrites the files of a frame, the checksums of files that stay part of it are kept
+fn write_frame_files(
+    conn: &Connection,
+    frame_id: &Uuid,
+    frame_type: &str,
+    frames_to_classify: &Vec<PathBuf>,
+    frames_classified: &Vec<PathBuf>,
+) -> Result<()> {
+    let mut stmt = conn.prepare(
+        "SELECT path, hash, size, captured_at FROM frame_files WHERE frame_id = ?1 AND hash IS NOT NULL",
+    )?;
+    let checksums = stmt
+        .query_map(params![frame_id.to_string()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                (
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<i64>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                ),
+            ))
+        })?
+        .collect::<Result<HashMap<_, _>>>()?;
+
+    conn.execute(
+        "DELETE FROM frame_files WHERE frame_id = ?",
+        params![frame_id.to_string()],
+    )?;
+
+    let insert_file = |path: &PathBuf, classified: bool| -> Result<()> {
+        let path = path.to_string_lossy().to_string();
+        let checksum = if classified { checksums.get(&path) } else { None };
+        conn.execute(
+            "INSERT OR REPLACE INTO frame_files (id, frame_id, path, classified, frame_type, hash, size, captured_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                Uuid::new_v4().to_string(),
+                frame_id.to_string(),
+                path,
+                classified as i32,
+                frame_type,
+                checksum.map(|(hash, _, _)| hash),
+                checksum.and_then(|(_, size, _)| *size),
+                checksum.and_then(|(_, _, captured_at)| captured_at.as_ref()),
+            ],
+        )?;
+        Ok(())
+    };
+
+    for path in frames_to_classify {
+        insert_file(path, false)?;
+    }
+    for path in frames_classified {
+        insert_file(path, true)?;
+    }
+
+    Ok(())
+}
+
+fn relocate_frame_files(
+    conn: &Connection,
+    frame_id: &Uuid,
+    from: &PathBuf,
+    to: &PathBuf,
+) -> Result<()> {
+    let mut stmt = conn.prepare("SELECT id, path FROM frame_files WHERE frame_id = ?1")?;
+    let files = stmt
+        .query_map(params![frame_id.to_string()], |row| {
+            Ok((row.get::<_, String>(0)?, PathBuf::from(row.get::<_, String>(1)?)))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    for (id, path) in files {
+        let new_path = rebase_path(&path, from, to);
+        if new_path != path {
+            conn.execute(
+                "UPDATE frame_files SET path = ?1 WHERE id = ?2",
+                params![new_path.to_string_lossy(), id],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{light_frame, TempDir};
+
+    #[test]
+    fn moving_a_session_keeps_the_checksums_of_its_files() {
+        let root = TempDir::new();
+        let mut db = Database::new(root.path()).unwrap();
+
+        let old_dir = PathBuf::from("Sessions/2024-03-09/M 31");
+        let new_dir = PathBuf::from("Sessions/2024-03-09/Andromeda");
+        let mut light_frame = light_frame(&["Sessions/2024-03-09/M 31/Light/Light_001.fits"]);
+        let mut session = ImagingSession {
+            id: Uuid::new_v4(),
+            folder_dir: old_dir.clone(),
+            light_frame_id: light_frame.id,
+            flat_frame_id: None,
+            dark_frame_id: None,
+            bias_frame_id: None,
+            dark_flat_frame_id: None,
+        };
+        let captured_at =
+            NaiveDateTime::parse_from_str("2024-03-09 21:15:30", "%Y-%m-%d %H:%M:%S").unwrap();
+        db.insert_light_frame(&light_frame).unwrap();
+        db.insert_imaging_session(&session).unwrap();
+        db.identify_frame_file(
+            &light_frame.frames_classified[0],
+            "abc",
+            42,
+            Some(captured_at),
+        )
+        .unwrap();
+
+        // what editing the session does after its folder was moved
+        light_frame.frames_classified = vec![new_dir.join("Light/Light_001.fits")];
+        session.folder_dir = new_dir.clone();
+        db.update_imaging_session(&session, &light_frame, &old_dir)
+            .unwrap();
+
+        let files = db.get_classified_frame_files().unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, new_dir.join("Light/Light_001.fits"));
+        assert_eq!(files[0].checksum, Some(("abc".to_string(), 42)));
+        assert_eq!(files[0].captured_at, Some(captured_at));
+    }
+}
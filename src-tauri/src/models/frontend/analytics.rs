@@ -1,6 +1,7 @@
+use crate::astronomy::night_times;
 use crate::models::imaging_frames::imaging_frame::ClassifiableFrame;
 use crate::models::state::AppState;
-use chrono::{DateTime, Duration, Utc};
+use chrono::{Duration, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::error::Error;
@@ -33,7 +34,7 @@ impl Analytics {
 
     fn get_info_cards(db: &Database) -> Result<InfoCards, Box<dyn Error>> {
         let now = Utc::now();
-        let cutoff = now - Duration::days(30);
+        let cutoff = (now - Duration::days(30)).date_naive();
 
         let mut exposure_time: u32 = 0;
         let mut recent_exposure_time: u32 = 0;
@@ -59,19 +60,19 @@ impl Analytics {
                 seeing_count += 1;
             }
 
-            unique_targets.insert(light_frame.target.clone());
+            unique_targets.insert(light_frame.target_key());
 
-            if light_frame.date > cutoff {
+            if light_frame.night() > cutoff {
                 recent_exposure_time += frame_exposure;
                 recent_imaging_sessions += 1;
-                recent_unique_targets.insert(light_frame.target.clone());
+                recent_unique_targets.insert(light_frame.target_key());
 
                 if let Some(seeing) = light_frame.average_seeing {
                     recent_seeing_total += seeing;
                     recent_seeing_count += 1;
                 }
             } else {
-                previous_unique_targets.insert(light_frame.target.clone());
+                previous_unique_targets.insert(light_frame.target_key());
             }
         }
 
@@ -144,14 +145,23 @@ impl Analytics {
 
     fn get_sessions_chart(db: &Database) -> Result<Vec<SessionsChartData>, Box<dyn Error>> {
         let mut data: Vec<SessionsChartData> = Vec::new();
+        let locations = db.get_locations()?;
 
+        // sessions of the same night are summed up, even if they started on different dates
         for light_frame in db.get_light_frames()?.values() {
-            let chart_data_point = SessionsChartData {
-                date: light_frame.date,
-                seconds: (light_frame.sub_length * light_frame.total_subs() as f64) as u32,
-            };
-
-            data.push(chart_data_point);
+            let seconds = (light_frame.sub_length * light_frame.total_subs() as f64) as u32;
+
+            match data.iter_mut().find(|d| d.date == light_frame.night()) {
+                Some(chart_data_point) => chart_data_point.seconds += seconds,
+                None => data.push(SessionsChartData {
+                    date: light_frame.night(),
+                    seconds,
+                    // the darkness available at the location of the first session that night
+                    dark_seconds: locations.get(&light_frame.location_id).map(|location| {
+                        (night_times(location, light_frame.night()).dark_hours * 3600.0) as u32
+                    }),
+                }),
+            }
         }
 
         Ok(data)
@@ -160,8 +170,9 @@ impl Analytics {
 
 #[derive(Serialize, Deserialize, Debug)]
 struct SessionsChartData {
-    date: DateTime<Utc>,
+    date: NaiveDate,
     seconds: u32,
+    dark_seconds: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -1,4 +1,6 @@
+use crate::models::preferences::{CollisionStrategy, ImportMode};
 use serde::Serialize;
+use std::path::PathBuf;
 use tauri::{Emitter, Window};
 use uuid::Uuid;
 
@@ -11,6 +13,24 @@ pub struct Process {
     step: Option<u32>,
     max: Option<u32>,
     error: Option<String>,
+    transfer: Option<FileTransfer>,
+    // every collision of the process, the finished event holds all of them
+    collisions: Vec<FileCollision>,
+}
+
+// how a single frame ended up in the library
+#[derive(Serialize, Clone)]
+pub struct FileTransfer {
+    file: PathBuf,
+    mode: ImportMode,
+}
+
+#[derive(Serialize, Clone)]
+pub struct FileCollision {
+    file: PathBuf,
+    // None if the file was skipped
+    destination: Option<PathBuf>,
+    strategy: CollisionStrategy,
 }
 
 impl Process {
@@ -29,6 +49,8 @@ impl Process {
             step,
             max,
             error: None,
+            transfer: None,
+            collisions: vec![],
         };
         window.emit("process", &process).unwrap();
 
@@ -42,6 +64,38 @@ impl Process {
         window.emit("process", &self).unwrap();
     }
 
+    // the transfer is only part of the event of its own step
+    pub fn transferred(&mut self, window: &Window, file: &PathBuf, mode: ImportMode) {
+        self.transfer = Some(FileTransfer {
+            file: file.clone(),
+            mode,
+        });
+        self.update(window);
+        self.transfer = None;
+    }
+
+    pub fn collision(
+        &mut self,
+        file: &PathBuf,
+        destination: Option<&PathBuf>,
+        strategy: CollisionStrategy,
+    ) {
+        self.collisions.push(FileCollision {
+            file: file.clone(),
+            destination: destination.cloned(),
+            strategy,
+        });
+    }
+
+    // the frames that were left out of the library
+    pub fn skipped(&self) -> Vec<PathBuf> {
+        self.collisions
+            .iter()
+            .filter(|collision| collision.destination.is_none())
+            .map(|collision| collision.file.clone())
+            .collect()
+    }
+
     pub fn kill(mut self, window: &Window, error_msg: String) {
         self.error = Some(error_msg);
         self.finish(window);
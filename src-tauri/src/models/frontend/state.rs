@@ -1,15 +1,18 @@
+use crate::calibration_match::session_warnings;
 use crate::models::equipment::{EquipmentItem, EquipmentList};
 use crate::models::frontend::analytics::Analytics;
 use crate::models::gallery_image_list::GalleryImage;
 use crate::models::imaging_frames::calibration_type::CalibrationType;
 use crate::models::imaging_frames::imaging_frame::ClassifiableFrame;
 use crate::models::imaging_session::ImagingSession;
+use crate::models::location::Location;
 use crate::models::preferences::{Config, LocalConfig};
 use crate::models::state::AppState;
 use chrono::{DateTime, Utc};
 use serde::de::Error;
 use serde::ser::SerializeMap;
 use serde::{Deserialize, Serialize, Serializer};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,6 +21,7 @@ pub struct FrontendAppState {
     pub config: Config,
     pub table_data: TableData,
     pub equipment_list: EquipmentList,
+    pub locations: HashMap<Uuid, Location>,
     pub image_list: Vec<GalleryImage>,
     pub analytics: Option<Analytics>,
 }
@@ -30,26 +34,30 @@ pub struct TableData {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LogTableRow {
-    id: Uuid,
-    date: DateTime<Utc>,
-    target: String,
-    location_name: Option<String>,
-    location_bortle: Option<u32>,
-    sub_length: f64,
-    total_subs: u32,
-    filter: String,
-    gain: u32,
-    offset: Option<u32>,
-    camera_temp: Option<f64>,
-    outside_temp: Option<f64>,
-    average_seeing: Option<f64>,
-    average_cloud_cover: Option<f64>,
-    average_moon: f64,
-    telescope: String,
-    flattener: String,
-    mount: String,
-    camera: String,
-    notes: Option<String>,
+    pub id: Uuid,
+    pub date: DateTime<Utc>,
+    pub target: String,
+    pub catalog_id: Option<String>,
+    pub location_name: Option<String>,
+    pub location_bortle: Option<u32>,
+    pub sub_length: f64,
+    pub total_subs: u32,
+    pub filter: String,
+    pub gain: u32,
+    pub offset: Option<u32>,
+    pub camera_temp: Option<f64>,
+    pub outside_temp: Option<f64>,
+    pub average_seeing: Option<f64>,
+    pub average_cloud_cover: Option<f64>,
+    pub average_moon: f64,
+    pub moon_altitude: Option<f64>,
+    pub moon_separation: Option<f64>,
+    pub telescope: String,
+    pub flattener: String,
+    pub mount: String,
+    pub camera: String,
+    pub notes: Option<String>,
+    pub calibration_warnings: Vec<String>,
 }
 
 impl LogTableRow {
@@ -85,13 +93,16 @@ impl LogTableRow {
                     .flatten()
                     .map_or("N/A".to_string(), |camera| camera.view_name().clone());
 
-                let location = app_state
-                    .config
-                    .lock()
-                    .unwrap() // TODO
-                    .locations
-                    .get(&light_frame.location_id)
-                    .cloned();
+                let location = db.get_location_by_id(light_frame.location_id).ok().flatten();
+                let config = app_state.config.lock().unwrap(); // TODO
+                let calibration_warnings = session_warnings(
+                    &db,
+                    imaging_session,
+                    &light_frame,
+                    &config.calibration_tolerances,
+                    &config.calibration_validity,
+                )
+                .unwrap_or_default();
                 let location_name = location.as_ref().map(|loc| loc.name.clone());
                 let location_bortle = location.as_ref().map(|loc| loc.bortle);
 
@@ -99,6 +110,7 @@ impl LogTableRow {
                     id: imaging_session.id,
                     date: light_frame.date.clone(),
                     target: light_frame.target.clone(),
+                    catalog_id: light_frame.catalog_id.clone(),
                     location_name,
                     location_bortle,
                     sub_length: light_frame.sub_length,
@@ -111,11 +123,14 @@ impl LogTableRow {
                     average_seeing: light_frame.average_seeing,
                     average_cloud_cover: light_frame.average_cloud_cover,
                     average_moon: light_frame.average_moon,
+                    moon_altitude: light_frame.moon_altitude,
+                    moon_separation: light_frame.moon_separation,
                     telescope: telescope_name,
                     flattener: flattener_name,
                     mount: mount_name,
                     camera: camera_name,
                     notes: light_frame.notes.clone(),
+                    calibration_warnings,
                 })
             }
             None => None,
@@ -132,4 +147,6 @@ pub struct CalibrationTableRow {
     pub sub_length: Option<f64>,
     pub camera_temp: Option<f64>,
     pub total_subs: u32,
+    pub capture_date: Option<DateTime<Utc>>,
+    pub expired: bool,
 }
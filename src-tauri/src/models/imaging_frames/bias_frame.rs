@@ -1,9 +1,11 @@
-use crate::models::equipment::{Camera, EquipmentItem};
+use crate::models::equipment::{EquipmentItem, EquipmentList};
 use crate::models::frontend::state::CalibrationTableRow;
 use crate::models::imaging_frames::calibration_type::CalibrationType;
 use crate::models::imaging_frames::imaging_frame::ImagingSessionFrame;
 use crate::models::imaging_frames::imaging_frame::{CalibrationFrame, ClassifiableFrame};
+use crate::models::preferences::FolderPaths;
 use crate::models::state::AppState;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::path::PathBuf;
@@ -21,6 +23,9 @@ pub struct BiasFrame {
 
     #[serde(skip_serializing, skip_deserializing)]
     pub calibration_type: CalibrationType,
+
+    #[serde(default)]
+    pub capture_date: Option<DateTime<Utc>>,
 }
 
 impl ClassifiableFrame for BiasFrame {
@@ -44,6 +49,10 @@ impl ClassifiableFrame for BiasFrame {
         &mut self.frames_classified
     }
 
+    fn file_pattern(&self, folder_paths: &FolderPaths) -> String {
+        folder_paths.file_patterns.bias.clone()
+    }
+
     fn add_to_database(&self, db: &mut Database) -> Result<(), Box<dyn Error>> {
         Ok(db.insert_bias_frame(&self)?)
     }
@@ -70,6 +79,12 @@ impl CalibrationFrame for BiasFrame {
         state: &State<AppState>,
     ) -> Result<CalibrationTableRow, Box<dyn Error>> {
         let db = state.db.lock().map_err(|e| e.to_string())?;
+        let validity = state
+            .config
+            .lock()
+            .map_err(|e| e.to_string())?
+            .calibration_validity
+            .clone();
 
         let camera_name = db
             .get_camera_by_id(self.camera_id)?
@@ -83,15 +98,18 @@ impl CalibrationFrame for BiasFrame {
             sub_length: None,
             camera_temp: None,
             total_subs: self.total_subs(),
+            capture_date: self.capture_date,
+            expired: validity.expired(&CalibrationType::BIAS, &self.capture_date, &Utc::now()),
         };
 
         Ok(row)
     }
 
-    fn get_field_value(&self, field: &str, camera: &Option<Camera>) -> String {
+    fn get_field_value(&self, field: &str, equipment_list: &EquipmentList) -> String {
         match field {
-            "CAMERA" => camera
-                .clone()
+            "CAMERA" => equipment_list
+                .cameras
+                .get(&self.camera_id)
                 .map_or("None".to_string(), |c| c.view_name().to_string()),
             "TOTALSUBS" => self.total_subs().to_string(),
             "GAIN" => self.gain.to_string(),
@@ -99,19 +117,15 @@ impl CalibrationFrame for BiasFrame {
         }
     }
 
-    fn build_path(&self, state: &State<AppState>) -> Result<PathBuf, Box<dyn Error>> {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-        let config = state.config.lock().map_err(|e| e.to_string())?;
-
-        let mut base = config
-            .folder_paths
-            .calibration_base_folder
-            .clone();
+    fn build_path_with(
+        &self,
+        folder_paths: &FolderPaths,
+        equipment_list: &EquipmentList,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let mut base = folder_paths.calibration_base_folder.clone();
         base.push("Bias");
-        let pattern = config.folder_paths.bias_frame_pattern.clone();
-        let camera = db.get_camera_by_id(self.camera_id)?;
-        let get_field_value =
-            |field_name: &str| self.get_field_value(field_name, &camera);
+        let pattern = folder_paths.bias_frame_pattern.clone();
+        let get_field_value = |field_name: &str| self.get_field_value(field_name, equipment_list);
 
         crate::classify::build_path(&base, &pattern, get_field_value)
     }
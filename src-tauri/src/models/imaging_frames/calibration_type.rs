@@ -6,6 +6,8 @@ pub enum CalibrationType {
     DEFAULT,
     DARK,
     BIAS,
+    FLAT,
+    DARKFLAT,
 }
 
 impl Default for CalibrationType {
@@ -19,6 +21,8 @@ impl fmt::Display for CalibrationType {
         match self {
             CalibrationType::DARK => write!(f, "DARK"),
             CalibrationType::BIAS => write!(f, "BIAS"),
+            CalibrationType::FLAT => write!(f, "FLAT"),
+            CalibrationType::DARKFLAT => write!(f, "DARKFLAT"),
             CalibrationType::DEFAULT => write!(f, "DEFAULT"),
         }
     }
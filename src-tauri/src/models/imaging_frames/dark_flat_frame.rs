@@ -0,0 +1,171 @@
+use crate::models::equipment::{EquipmentItem, EquipmentList};
+use crate::models::frontend::state::CalibrationTableRow;
+use crate::models::imaging_frames::calibration_type::CalibrationType;
+use crate::models::imaging_frames::flat_frame::FlatFrame;
+use crate::models::imaging_frames::imaging_frame::{
+    CalibrationFrame, ClassifiableFrame, ImagingSessionFrame,
+};
+use crate::models::preferences::FolderPaths;
+use crate::models::state::AppState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::PathBuf;
+use tauri::State;
+use uuid::Uuid;
+use crate::models::database::Database;
+
+// flat exposures are short, so a small absolute difference is already noticeable
+const SUB_LENGTH_TOLERANCE: f64 = 0.05;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DarkFlatFrame {
+    pub id: Uuid,
+    pub camera_id: Uuid,
+    pub gain: u32,
+    pub frames_to_classify: Vec<PathBuf>,
+    pub frames_classified: Vec<PathBuf>,
+    pub in_imaging_session: bool,
+
+    #[serde(skip_serializing, skip_deserializing)]
+    pub calibration_type: CalibrationType,
+
+    // None if the headers don't record them
+    pub camera_temp: Option<f64>,
+    pub sub_length: Option<f64>,
+    #[serde(default)]
+    pub capture_date: Option<DateTime<Utc>>,
+}
+
+impl DarkFlatFrame {
+    // dark flats calibrate flats taken with the same camera, gain and exposure
+    pub fn matches(&self, flat_frame: &FlatFrame, flat_sub_length: f64) -> bool {
+        self.camera_id == flat_frame.camera_id
+            && self.gain == flat_frame.gain
+            && self.sub_length.is_some_and(|sub_length| {
+                (sub_length - flat_sub_length).abs() <= SUB_LENGTH_TOLERANCE
+            })
+    }
+}
+
+impl ClassifiableFrame for DarkFlatFrame {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn frames_to_classify(&self) -> &Vec<PathBuf> {
+        &self.frames_to_classify
+    }
+
+    fn frames_to_classify_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.frames_to_classify
+    }
+
+    fn frames_classified(&self) -> &Vec<PathBuf> {
+        &self.frames_classified
+    }
+
+    fn frames_classified_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.frames_classified
+    }
+
+    fn file_pattern(&self, folder_paths: &FolderPaths) -> String {
+        folder_paths.file_patterns.dark_flat.clone()
+    }
+
+    fn add_to_database(&self, db: &mut Database) -> Result<(), Box<dyn Error>> {
+        Ok(db.insert_dark_flat_frame(&self)?)
+    }
+
+    fn remove_from_database(&self, db: &mut Database) -> Result<(), Box<dyn Error>> {
+        Ok(db.remove_dark_flat_frame(self.id)?)
+    }
+}
+
+impl ImagingSessionFrame for DarkFlatFrame {
+    fn build_path(&self, base: &PathBuf) -> Result<PathBuf, Box<dyn Error>> {
+        let mut path = base.clone();
+
+        path.push("DarkFlat");
+
+        Ok(path)
+    }
+}
+
+impl CalibrationFrame for DarkFlatFrame {
+    fn camera_id(&self) -> &Uuid {
+        &self.camera_id
+    }
+
+    fn gain(&self) -> &u32 {
+        &self.gain
+    }
+    fn calibration_type(&self) -> CalibrationType {
+        CalibrationType::DARKFLAT
+    }
+
+    fn calibration_table_row(
+        &self,
+        state: &State<AppState>,
+    ) -> Result<CalibrationTableRow, Box<dyn Error>> {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let validity = state
+            .config
+            .lock()
+            .map_err(|e| e.to_string())?
+            .calibration_validity
+            .clone();
+
+        let camera_name = db
+            .get_camera_by_id(self.camera_id)?
+            .map_or("N/A".to_string(), |camera| camera.view_name().clone());
+
+        let row = CalibrationTableRow {
+            id: self.id,
+            camera: camera_name,
+            calibration_type: CalibrationType::DARKFLAT,
+            gain: self.gain,
+            sub_length: self.sub_length,
+            camera_temp: self.camera_temp,
+            total_subs: self.total_subs(),
+            capture_date: self.capture_date,
+            expired: validity.expired(&CalibrationType::DARKFLAT, &self.capture_date, &Utc::now()),
+        };
+
+        Ok(row)
+    }
+
+    fn get_field_value(&self, field: &str, equipment_list: &EquipmentList) -> String {
+        match field {
+            "CAMERA" => equipment_list
+                .cameras
+                .get(&self.camera_id)
+                .map_or("None".to_string(), |c| c.view_name().to_string()),
+            "SUBLENGTH" => self
+                .sub_length
+                .map_or("None".to_string(), |sub_length| sub_length.to_string()),
+            "TOTALSUBS" => self.total_subs().to_string(),
+            "GAIN" => self.gain.to_string(),
+            "CAMERATEMP" => self
+                .camera_temp
+                .map_or("None".to_string(), |camera_temp| camera_temp.to_string()),
+            "DATE" => self
+                .capture_date
+                .map_or("None".to_string(), |d| d.format("%Y-%m-%d").to_string()),
+            _ => field.to_string(),
+        }
+    }
+
+    fn build_path_with(
+        &self,
+        folder_paths: &FolderPaths,
+        equipment_list: &EquipmentList,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let mut base = folder_paths.calibration_base_folder.clone();
+        base.push("DarkFlat");
+        let pattern = folder_paths.dark_flat_frame_pattern.clone();
+        let get_field_value = |field_name: &str| self.get_field_value(field_name, equipment_list);
+
+        crate::classify::build_path(&base, &pattern, get_field_value)
+    }
+}
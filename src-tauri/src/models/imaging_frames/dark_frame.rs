@@ -1,9 +1,12 @@
-use crate::models::equipment::{Camera, EquipmentItem};
+use crate::analyze::dark_frame_values;
+use crate::models::equipment::{EquipmentItem, EquipmentList};
 use crate::models::frontend::state::CalibrationTableRow;
 use crate::models::imaging_frames::calibration_type::CalibrationType;
 use crate::models::imaging_frames::imaging_frame::ImagingSessionFrame;
 use crate::models::imaging_frames::imaging_frame::{CalibrationFrame, ClassifiableFrame};
+use crate::models::preferences::FolderPaths;
 use crate::models::state::AppState;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::path::PathBuf;
@@ -25,6 +28,8 @@ pub struct DarkFrame {
 
     pub camera_temp: f64,
     pub sub_length: f64,
+    #[serde(default)]
+    pub capture_date: Option<DateTime<Utc>>,
 }
 
 impl ClassifiableFrame for DarkFrame {
@@ -48,6 +53,10 @@ impl ClassifiableFrame for DarkFrame {
         &mut self.frames_classified
     }
 
+    fn file_pattern(&self, folder_paths: &FolderPaths) -> String {
+        folder_paths.file_patterns.dark.clone()
+    }
+
     fn add_to_database(&self, db: &mut Database) -> Result<(), Box<dyn Error>> {
         Ok(db.insert_dark_frame(&self)?)
     }
@@ -84,6 +93,12 @@ impl CalibrationFrame for DarkFrame {
         state: &State<AppState>,
     ) -> Result<CalibrationTableRow, Box<dyn Error>> {
         let db = state.db.lock().map_err(|e| e.to_string())?;
+        let validity = state
+            .config
+            .lock()
+            .map_err(|e| e.to_string())?
+            .calibration_validity
+            .clone();
 
         println!("id. {}", self.id);
         println!("camera_id: {}", self.camera_id);
@@ -104,15 +119,18 @@ impl CalibrationFrame for DarkFrame {
             sub_length: Some(self.sub_length),
             camera_temp: Some(self.camera_temp),
             total_subs: self.total_subs(),
+            capture_date: self.capture_date,
+            expired: validity.expired(&CalibrationType::DARK, &self.capture_date, &Utc::now()),
         };
 
         Ok(row)
     }
 
-    fn get_field_value(&self, field: &str, camera: &Option<Camera>) -> String {
+    fn get_field_value(&self, field: &str, equipment_list: &EquipmentList) -> String {
         match field {
-            "CAMERA" => camera
-                .clone()
+            "CAMERA" => equipment_list
+                .cameras
+                .get(&self.camera_id)
                 .map_or("None".to_string(), |c| c.view_name().to_string()),
             "SUBLENGTH" => self.sub_length.to_string(),
             "TOTALSUBS" => self.total_subs().to_string(),
@@ -122,20 +140,52 @@ impl CalibrationFrame for DarkFrame {
         }
     }
 
-    fn build_path(&self, state: &State<AppState>) -> Result<PathBuf, Box<dyn Error>> {
-        let config = state.config.lock().map_err(|e| e.to_string())?;
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-
-        let mut base = config
-            .folder_paths
-            .calibration_base_folder
-            .clone();
+    fn build_path_with(
+        &self,
+        folder_paths: &FolderPaths,
+        equipment_list: &EquipmentList,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let mut base = folder_paths.calibration_base_folder.clone();
         base.push("Dark");
-        let pattern = config.folder_paths.dark_frame_pattern.clone();
-        let camera = db.get_camera_by_id(self.camera_id)?;
-        let get_field_value =
-            |field_name: &str| self.get_field_value(field_name, &camera);
+        let pattern = folder_paths.dark_frame_pattern.clone();
+        let get_field_value = |field_name: &str| self.get_field_value(field_name, equipment_list);
 
         crate::classify::build_path(&base, &pattern, get_field_value)
     }
 }
+
+// darks taken within a session were stored without their gain and temperature before
+pub fn fill_dark_frame_values(db: &mut Database, root: &PathBuf) -> Result<(), Box<dyn Error>> {
+    let dark_frames: Vec<DarkFrame> = db
+        .get_dark_frames()?
+        .into_values()
+        .filter(|frame| frame.in_imaging_session)
+        .collect();
+
+    for mut dark_frame in dark_frames {
+        let first_frame = match dark_frame.frames_classified.first() {
+            Some(path) => vec![root.join(path)],
+            None => continue,
+        };
+        let (gain, camera_temp) = dark_frame_values(&first_frame);
+
+        let light_frame = match db.get_imaging_sessions_using(&dark_frame.id)?.first() {
+            Some(id) => match db.get_imaging_session_by_id(*id)? {
+                Some(session) => db.get_light_frame_by_id(session.light_frame_id)?,
+                None => None,
+            },
+            None => None,
+        };
+
+        dark_frame.gain = gain
+            .or(light_frame.as_ref().map(|l| l.gain))
+            .unwrap_or(dark_frame.gain);
+        dark_frame.camera_temp = camera_temp
+            .or(light_frame.as_ref().and_then(|l| l.camera_temp))
+            .unwrap_or(dark_frame.camera_temp);
+
+        db.insert_dark_frame(&dark_frame)?;
+    }
+
+    Ok(())
+}
@@ -1,7 +1,16 @@
-use crate::models::imaging_frames::imaging_frame::{ClassifiableFrame, ImagingSessionFrame};
+use crate::models::equipment::{EquipmentItem, EquipmentList};
+use crate::models::frontend::state::CalibrationTableRow;
+use crate::models::imaging_frames::calibration_type::CalibrationType;
+use crate::models::imaging_frames::imaging_frame::{
+    CalibrationFrame, ClassifiableFrame, ImagingSessionFrame,
+};
+use crate::models::preferences::FolderPaths;
+use crate::models::state::AppState;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::path::PathBuf;
+use tauri::State;
 use uuid::Uuid;
 use crate::models::database::Database;
 
@@ -12,6 +21,20 @@ pub struct FlatFrame {
     pub gain: u32,
     pub frames_to_classify: Vec<PathBuf>,
     pub frames_classified: Vec<PathBuf>,
+    pub in_imaging_session: bool,
+
+    #[serde(skip_serializing, skip_deserializing)]
+    pub calibration_type: CalibrationType,
+
+    #[serde(default)]
+    pub capture_date: Option<DateTime<Utc>>,
+    // the optical train the flats were taken with, as far as it is known
+    #[serde(default)]
+    pub telescope_id: Option<Uuid>,
+    #[serde(default)]
+    pub flattener_id: Option<Uuid>,
+    #[serde(default)]
+    pub filter_id: Option<Uuid>,
 }
 
 impl ClassifiableFrame for FlatFrame {
@@ -35,6 +58,10 @@ impl ClassifiableFrame for FlatFrame {
         &mut self.frames_classified
     }
 
+    fn file_pattern(&self, folder_paths: &FolderPaths) -> String {
+        folder_paths.file_patterns.flat.clone()
+    }
+
     fn add_to_database(&self, db: &mut Database) -> Result<(), Box<dyn Error>> {
         Ok(db.insert_flat_frame(&self)?)
     }
@@ -53,3 +80,85 @@ impl ImagingSessionFrame for FlatFrame {
         Ok(path)
     }
 }
+
+impl CalibrationFrame for FlatFrame {
+    fn camera_id(&self) -> &Uuid {
+        &self.camera_id
+    }
+
+    fn gain(&self) -> &u32 {
+        &self.gain
+    }
+    fn calibration_type(&self) -> CalibrationType {
+        CalibrationType::FLAT
+    }
+
+    fn calibration_table_row(
+        &self,
+        state: &State<AppState>,
+    ) -> Result<CalibrationTableRow, Box<dyn Error>> {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let validity = state
+            .config
+            .lock()
+            .map_err(|e| e.to_string())?
+            .calibration_validity
+            .clone();
+
+        let camera_name = db
+            .get_camera_by_id(self.camera_id)?
+            .map_or("N/A".to_string(), |camera| camera.view_name().clone());
+
+        let row = CalibrationTableRow {
+            id: self.id,
+            camera: camera_name,
+            calibration_type: CalibrationType::FLAT,
+            gain: self.gain,
+            sub_length: None,
+            camera_temp: None,
+            total_subs: self.total_subs(),
+            capture_date: self.capture_date,
+            // without a session to compare the train with, flats kept per train don't expire
+            expired: !validity.flat_same_train
+                && validity.expired(&CalibrationType::FLAT, &self.capture_date, &Utc::now()),
+        };
+
+        Ok(row)
+    }
+
+    fn get_field_value(&self, field: &str, equipment_list: &EquipmentList) -> String {
+        match field {
+            "CAMERA" => equipment_list
+                .cameras
+                .get(&self.camera_id)
+                .map_or("None".to_string(), |c| c.view_name().to_string()),
+            "TOTALSUBS" => self.total_subs().to_string(),
+            "GAIN" => self.gain.to_string(),
+            "TELESCOPE" => self
+                .telescope_id
+                .and_then(|id| equipment_list.telescopes.get(&id))
+                .map_or("None".to_string(), |t| t.view_name().to_string()),
+            "FILTER" => self
+                .filter_id
+                .and_then(|id| equipment_list.filters.get(&id))
+                .map_or("None".to_string(), |f| f.view_name().to_string()),
+            "DATE" => self
+                .capture_date
+                .map_or("None".to_string(), |d| d.format("%Y-%m-%d").to_string()),
+            _ => field.to_string(),
+        }
+    }
+
+    fn build_path_with(
+        &self,
+        folder_paths: &FolderPaths,
+        equipment_list: &EquipmentList,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let mut base = folder_paths.calibration_base_folder.clone();
+        base.push("Flat");
+        let pattern = folder_paths.flat_frame_pattern.clone();
+        let get_field_value = |field_name: &str| self.get_field_value(field_name, equipment_list);
+
+        crate::classify::build_path(&base, &pattern, get_field_value)
+    }
+}
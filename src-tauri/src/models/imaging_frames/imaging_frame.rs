@@ -1,9 +1,13 @@
-use crate::models::equipment::{Camera};
+use crate::analyze::capture_date;
+use crate::models::equipment::EquipmentList;
 use crate::models::frontend::process::Process;
 use crate::models::frontend::state::CalibrationTableRow;
 use crate::models::imaging_frames::calibration_type::CalibrationType;
+use crate::models::preferences::FolderPaths;
 use crate::models::state::AppState;
+use chrono_tz::Tz;
 use std::any::Any;
+use std::collections::HashMap;
 use std::error::Error;
 use std::fs;
 use std::path::PathBuf;
@@ -17,6 +21,7 @@ pub trait ClassifiableFrame: Clone {
     fn frames_to_classify_mut(&mut self) -> &mut Vec<PathBuf>;
     fn frames_classified(&self) -> &Vec<PathBuf>;
     fn frames_classified_mut(&mut self) -> &mut Vec<PathBuf>;
+    fn file_pattern(&self, folder_paths: &FolderPaths) -> String;
 
     fn add_to_database(&self, db: &mut Database) -> Result<(), Box<dyn Error>>;
     fn remove_from_database(&self, db: &mut Database) -> Result<(), Box<dyn Error>>;
@@ -38,6 +43,21 @@ pub trait ClassifiableFrame: Clone {
         self.remove_from_database(&mut db)
     }
 
+    // skipped frames are no longer part of the frame, neither as files nor in the counts
+    fn remove_skipped(
+        &mut self,
+        skipped: &[PathBuf],
+        state: &State<AppState>,
+    ) -> Result<(), Box<dyn Error>> {
+        let before = self.frames_to_classify().len();
+        self.frames_to_classify_mut().retain(|p| !skipped.contains(p));
+        if self.frames_to_classify().len() == before {
+            return Ok(());
+        }
+
+        self.add(state)
+    }
+
     // fn edit(&self, state: &State<Mutex<AppState>>) -> Result<(), Box<dyn Error>> {
     //     Ok(())
     // }
@@ -90,9 +110,13 @@ pub trait ImagingSessionFrame: ClassifiableFrame + Clone {
         window: &Window,
         process: &mut Process,
         base: &PathBuf,
+        // the values of the session for tokens the headers have no value for
+        field_value: &dyn Fn(&str) -> String,
     ) -> Result<(), Box<dyn Error>> {
         let path = self.build_path(base)?;
         let frames = self.frames_to_classify().clone();
+        let file_pattern =
+            self.file_pattern(&state.config.lock().map_err(|e| e.to_string())?.folder_paths);
         let helper = |base: &PathBuf,
                       file_name: &PathBuf,
                       frame: &PathBuf,
@@ -101,9 +125,19 @@ pub trait ImagingSessionFrame: ClassifiableFrame + Clone {
             self.classify_helper(base, file_name, frame, destination, state)
         };
 
-        crate::classify::classify(&path, &frames, state, helper, window, process)?;
+        let result = crate::classify::classify(
+            &path,
+            &frames,
+            &file_pattern,
+            field_value,
+            state,
+            helper,
+            window,
+            process,
+        );
+        self.remove_skipped(&process.skipped(), state)?;
 
-        Ok(())
+        result
     }
 }
 
@@ -120,8 +154,21 @@ pub trait CalibrationFrame: ClassifiableFrame + Clone + Any {
         &self,
         state: &State<AppState>,
     ) -> Result<CalibrationTableRow, Box<dyn Error>>;
-    fn get_field_value(&self, field: &str, camera: &Option<Camera>) -> String;
-    fn build_path(&self, state: &State<AppState>) -> Result<PathBuf, Box<dyn Error>>;
+    fn get_field_value(&self, field: &str, equipment_list: &EquipmentList) -> String;
+    fn build_path_with(
+        &self,
+        folder_paths: &FolderPaths,
+        equipment_list: &EquipmentList,
+    ) -> Result<PathBuf, Box<dyn Error>>;
+
+    fn build_path(&self, state: &State<AppState>) -> Result<PathBuf, Box<dyn Error>> {
+        let config = state.config.lock().map_err(|e| e.to_string())?;
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+
+        let equipment_list = db.get_equipment_list()?;
+
+        self.build_path_with(&config.folder_paths, &equipment_list)
+    }
 
     fn classify(
         &mut self,
@@ -131,6 +178,15 @@ pub trait CalibrationFrame: ClassifiableFrame + Clone + Any {
     ) -> Result<(), Box<dyn Error>> {
         let base = self.build_path(state)?;
         let frames = self.frames_to_classify().clone();
+        let file_pattern =
+            self.file_pattern(&state.config.lock().map_err(|e| e.to_string())?.folder_paths);
+        let equipment_list = state
+            .db
+            .lock()
+            .map_err(|e| e.to_string())?
+            .get_equipment_list()?;
+        let frame = self.clone();
+        let field_value = |field: &str| frame.get_field_value(field, &equipment_list);
         let helper = |base: &PathBuf,
                       file_name: &PathBuf,
                       frame: &PathBuf,
@@ -139,8 +195,142 @@ pub trait CalibrationFrame: ClassifiableFrame + Clone + Any {
             self.classify_helper(base, file_name, frame, destination, state)
         };
 
-        crate::classify::classify(&base, &frames, state, helper, window, process)?;
+        let result = crate::classify::classify(
+            &base,
+            &frames,
+            &file_pattern,
+            &field_value,
+            state,
+            helper,
+            window,
+            process,
+        );
+        self.remove_skipped(&process.skipped(), state)?;
 
-        Ok(())
+        result
     }
 }
+
+// sets classified before capture dates were recorded are dated by their first file
+pub fn fill_capture_dates(db: &Database, root: &PathBuf) -> Result<(), Box<dyn Error>> {
+    let timezones = session_timezones(db)?;
+    let mut undated: Vec<(Uuid, Option<PathBuf>)> = vec![];
+    undated.extend(
+        db.get_dark_frames()?
+            .values()
+            .filter(|frame| frame.capture_date.is_none())
+            .map(|frame| (frame.id, frame.frames_classified.first().cloned())),
+    );
+    undated.extend(
+        db.get_bias_frames()?
+            .values()
+            .filter(|frame| frame.capture_date.is_none())
+            .map(|frame| (frame.id, frame.frames_classified.first().cloned())),
+    );
+    undated.extend(
+        db.get_flat_frames()?
+            .values()
+            .filter(|frame| frame.capture_date.is_none())
+            .map(|frame| (frame.id, frame.frames_classified.first().cloned())),
+    );
+    undated.extend(
+        db.get_dark_flat_frames()?
+            .values()
+            .filter(|frame| frame.capture_date.is_none())
+            .map(|frame| (frame.id, frame.frames_classified.first().cloned())),
+    );
+
+    for (id, first_frame) in undated {
+        let date =
+            first_frame.and_then(|path| capture_date(&[root.join(path)], timezones.get(&id)));
+        if let Some(date) = date {
+            db.update_capture_date(&id, &date)?;
+        }
+    }
+
+    Ok(())
+}
+
+// the first capture_dates backfill read local header dates of session calibration as UTC
+pub fn redate_session_calibration(db: &Database, root: &PathBuf) -> Result<(), Box<dyn Error>> {
+    let timezones = session_timezones(db)?;
+    let mut frames: Vec<(Uuid, Option<PathBuf>)> = vec![];
+    frames.extend(
+        db.get_dark_frames()?
+            .values()
+            .map(|frame| (frame.id, frame.frames_classified.first().cloned())),
+    );
+    frames.extend(
+        db.get_flat_frames()?
+            .values()
+            .map(|frame| (frame.id, frame.frames_classified.first().cloned())),
+    );
+    frames.extend(
+        db.get_dark_flat_frames()?
+            .values()
+            .map(|frame| (frame.id, frame.frames_classified.first().cloned())),
+    );
+
+    for (id, first_frame) in frames {
+        let Some(timezone) = timezones.get(&id) else {
+            continue;
+        };
+        if let Some(date) =
+            first_frame.and_then(|path| capture_date(&[root.join(path)], Some(timezone)))
+        {
+            db.update_capture_date(&id, &date)?;
+        }
+    }
+
+    Ok(())
+}
+
+// calibration taken within a session shares the timezone of its location, library sets have none
+fn session_timezones(db: &Database) -> Result<HashMap<Uuid, Tz>, Box<dyn Error>> {
+    let locations = db.get_locations()?;
+    let light_frames = db.get_light_frames()?;
+    let in_session: Vec<Uuid> = db
+        .get_dark_frames()?
+        .values()
+        .filter(|frame| frame.in_imaging_session)
+        .map(|frame| frame.id)
+        .chain(
+            db.get_flat_frames()?
+                .values()
+                .filter(|frame| frame.in_imaging_session)
+                .map(|frame| frame.id),
+        )
+        .chain(
+            db.get_dark_flat_frames()?
+                .values()
+                .filter(|frame| frame.in_imaging_session)
+                .map(|frame| frame.id),
+        )
+        .collect();
+
+    let mut timezones = HashMap::new();
+    for session in db.get_imaging_sessions()?.values() {
+        let timezone = light_frames
+            .get(&session.light_frame_id)
+            .and_then(|light_frame| locations.get(&light_frame.location_id))
+            .filter(|location| !location.needs_review)
+            .map(|location| location.tz());
+        let Some(timezone) = timezone else {
+            continue;
+        };
+
+        for id in [
+            session.dark_frame_id,
+            session.flat_frame_id,
+            session.dark_flat_frame_id,
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|id| in_session.contains(id))
+        {
+            timezones.insert(id, timezone);
+        }
+    }
+
+    Ok(timezones)
+}
@@ -1,12 +1,18 @@
+use crate::catalog::{normalize, Catalog};
 use crate::commands::imaging_sessions::ImagingSessionEdit;
 use crate::models::equipment::{EquipmentItem, EquipmentList};
+use crate::image::read_metadata;
 use crate::models::imaging_frames::imaging_frame::{ClassifiableFrame, ImagingSessionFrame};
+use crate::models::location::{night_of, Location};
+use crate::models::preferences::FolderPaths;
+use crate::moon::moon_conditions;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::path::PathBuf;
 use uuid::Uuid;
 use crate::models::database::Database;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LightFrame {
@@ -15,7 +21,13 @@ pub struct LightFrame {
     pub frames_classified: Vec<PathBuf>,
 
     pub date: DateTime<Utc>,
+    // the local date the night of the session started on
+    #[serde(default)]
+    pub night_of: Option<NaiveDate>,
     pub target: String,
+    // designation of the catalog entry the target resolved to
+    #[serde(default)]
+    pub catalog_id: Option<String>,
     pub location_id: Uuid,
 
     pub gain: u32,
@@ -35,17 +47,40 @@ pub struct LightFrame {
     pub average_cloud_cover: Option<f64>,
 
     pub average_moon: f64,
+    #[serde(default)]
+    pub moon_altitude: Option<f64>,
+    #[serde(default)]
+    pub moon_separation: Option<f64>,
+
+    // target coordinates in degrees
+    #[serde(default)]
+    pub ra: Option<f64>,
+    #[serde(default)]
+    pub dec: Option<f64>,
+
+    // sub count of sessions that were imported without frame files
+    #[serde(default)]
+    pub imported_subs: Option<u32>,
 }
 
 impl LightFrame {
     pub fn from(session: &ImagingSessionEdit) -> LightFrame {
-        LightFrame {
+        // the target coordinates aren't part of the session form, they are read from the first sub
+        let metadata = session
+            .base
+            .frames
+            .first()
+            .and_then(|path| read_metadata(path).ok());
+
+        let mut light_frame = LightFrame {
             id: Uuid::new_v4(),
             frames_to_classify: session.base.frames.clone(),
             frames_classified: vec![],
 
             date: session.general.date.clone(),
+            night_of: None,
             target: session.general.target.clone(),
+            catalog_id: None,
             location_id: session.general.location_id.clone(),
 
             gain: session.details.gain,
@@ -64,13 +99,101 @@ impl LightFrame {
             average_seeing: session.weather.average_seeing,
             average_cloud_cover: session.weather.average_cloud_cover,
 
-            average_moon: 0.0, // TODO
+            average_moon: 0.0,
+            moon_altitude: None,
+            moon_separation: None,
+
+            ra: metadata.as_ref().and_then(|m| m.ra),
+            dec: metadata.as_ref().and_then(|m| m.dec),
+
+            imported_subs: None,
+        };
+        light_frame.update_location(None);
+
+        light_frame
+    }
+
+    // coordinates from the headers are kept, they point at the actual framing of the session
+    pub fn link_target(&mut self, catalog: &Catalog) {
+        let from_catalog = match self.catalog_id.as_ref().and_then(|id| catalog.get(id)) {
+            Some(linked) => self.ra == Some(linked.ra) && self.dec == Some(linked.dec),
+            None => self.ra.is_none() || self.dec.is_none(),
+        };
+
+        let entry = catalog.resolve(&self.target);
+        self.catalog_id = entry.map(|entry| entry.id.clone());
+        if from_catalog {
+            self.ra = entry.map(|entry| entry.ra);
+            self.dec = entry.map(|entry| entry.dec);
+        }
+    }
+
+    // the night needs the timezone of the location, without a location it falls back to UTC,
+    // the folder is named after the night, so it is only computed when the session is added or edited,
+    // the timezone of a location that needs review is a placeholder, so the night is left as it is
+    pub fn update_location(&mut self, location: Option<&Location>) {
+        match location {
+            Some(location) if location.needs_review => {}
+            Some(location) => self.night_of = Some(location.night_of(&self.date)),
+            None => self.night_of = Some(night_of(&self.date, &Tz::UTC)),
         }
+
+        self.update_moon(location);
+    }
+
+    // the moon is averaged over the subs, without a reviewed location the altitude stays unknown
+    pub fn update_moon(&mut self, location: Option<&Location>) {
+        let exposure_ms = self.sub_length * self.total_subs() as f64 * 1000.0;
+        let end = self.date + Duration::milliseconds(exposure_ms as i64);
+        let observer = location
+            .filter(|location| !location.needs_review)
+            .map(|location| (location.latitude, location.longitude));
+        let target = self.ra.zip(self.dec);
+
+        let moon = moon_conditions(self.date, end, observer, target);
+        self.average_moon = moon.illumination;
+        self.moon_altitude = moon.altitude;
+        self.moon_separation = moon.separation;
+    }
+
+    // applies the general, details, equipment and weather fields of an edited session
+    pub fn edit(&mut self, session: &ImagingSessionEdit) {
+        self.date = session.general.date;
+        self.target = session.general.target.clone();
+        self.location_id = session.general.location_id;
+
+        self.gain = session.details.gain;
+        self.offset = session.details.offset;
+        self.camera_temp = session.details.camera_temp;
+        self.notes = session.details.notes.clone();
+        self.sub_length = session.details.sub_length;
+
+        self.telescope_id = session.equipment.telescope_id;
+        self.flattener_id = session.equipment.flattener_id;
+        self.mount_id = session.equipment.mount_id;
+        self.camera_id = session.equipment.camera_id;
+        self.filter_id = session.equipment.filter_id;
+
+        self.outside_temp = session.weather.outside_temp;
+        self.average_seeing = session.weather.average_seeing;
+        self.average_cloud_cover = session.weather.average_cloud_cover;
+    }
+
+    // sessions from before the night was stored are named after their UTC date
+    pub fn night(&self) -> NaiveDate {
+        self.night_of.unwrap_or_else(|| self.date.date_naive())
+    }
+
+    // "M31" and "Andromeda Galaxy" are the same target, unknown names are compared loosely
+    pub fn target_key(&self) -> String {
+        self.catalog_id
+            .clone()
+            .unwrap_or_else(|| normalize(&self.target))
     }
 
     pub fn get_field_value(&self, field: &str, equipment_list: &EquipmentList) -> String {
         match field {
-            "DATE" => self.date.format("%Y-%m-%d").to_string(),
+            "DATE" => self.night().format("%Y-%m-%d").to_string(),
             "TARGET" => self.target.clone(),
             "SITE" => "site".to_string(),
             "CAMERA" => equipment_list
@@ -119,6 +242,18 @@ impl ClassifiableFrame for LightFrame {
         &mut self.frames_classified
     }
 
+    fn file_pattern(&self, folder_paths: &FolderPaths) -> String {
+        folder_paths.file_patterns.light.clone()
+    }
+
+    fn total_subs(&self) -> u32 {
+        let mut size = self.imported_subs.unwrap_or(0);
+        size += self.frames_to_classify.len() as u32;
+        size += self.frames_classified.len() as u32;
+
+        size
+    }
+
     fn add_to_database(&self, db: &mut Database) -> Result<(), Box<dyn Error>> {
         Ok(db.insert_light_frame(&self)?)
     }
@@ -1,5 +1,6 @@
 pub mod bias_frame;
 pub mod calibration_type;
+pub mod dark_flat_frame;
 pub mod dark_frame;
 pub mod flat_frame;
 pub mod imaging_frame;
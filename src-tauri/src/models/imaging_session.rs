@@ -1,14 +1,22 @@
-use crate::commands::imaging_sessions::ImagingSessionCalibration;
+use crate::analyze::{analyze_calibration_frames, capture_date, dark_frame_values};
+use crate::commands::imaging_sessions::{ImagingSessionCalibration, ImagingSessionEdit};
+use crate::file_system::{
+    move_folder, rebase_path, remove_empty_parents, remove_files, FileRemoval,
+};
+use crate::models::equipment::EquipmentList;
 use crate::models::frontend::process::Process;
 use crate::models::imaging_frames::imaging_frame::{ClassifiableFrame, ImagingSessionFrame};
 use crate::models::imaging_frames::light_frame::LightFrame;
+use crate::models::preferences::FolderPaths;
 use crate::models::state::AppState;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
+use std::fs;
 use std::path::PathBuf;
 use tauri::{State, Window};
 use uuid::Uuid;
 use crate::models::imaging_frames::calibration_type::CalibrationType;
+use crate::models::imaging_frames::dark_flat_frame::DarkFlatFrame;
 use crate::models::imaging_frames::dark_frame::DarkFrame;
 use crate::models::imaging_frames::flat_frame::FlatFrame;
 
@@ -20,6 +28,7 @@ pub struct ImagingSession {
     pub flat_frame_id: Option<Uuid>,
     pub dark_frame_id: Option<Uuid>,
     pub bias_frame_id: Option<Uuid>,
+    pub dark_flat_frame_id: Option<Uuid>,
 }
 
 impl ImagingSession {
@@ -30,8 +39,16 @@ impl ImagingSession {
         id: &Uuid,
     ) -> Result<Self, Box<dyn Error>> {
         let mut imaging_session = ImagingSession::from(state, &light_frame, &calibration, id)?;
+        // the camera clock of EXIF files runs in the time zone of the location
+        let timezone = state
+            .db
+            .lock()
+            .map_err(|e| e.to_string())?
+            .get_location_by_id(light_frame.location_id)?
+            .map(|location| location.tz());
         let mut flat_frame = None;
         let mut dark_frame = None;
+        let mut dark_flat_frame = None;
 
         if calibration.flat_frames_to_classify.len() > 0 {
             let id = Uuid::new_v4();
@@ -42,6 +59,12 @@ impl ImagingSession {
                 gain: light_frame.gain,
                 frames_to_classify: calibration.flat_frames_to_classify.clone(),
                 frames_classified: vec![],
+                in_imaging_session: true,
+                calibration_type: CalibrationType::FLAT,
+                capture_date: capture_date(&calibration.flat_frames_to_classify, timezone.as_ref()),
+                telescope_id: Some(light_frame.telescope_id),
+                flattener_id: light_frame.flattener_id,
+                filter_id: light_frame.filter_id,
             };
 
             imaging_session.flat_frame_id = Some(id);
@@ -50,23 +73,53 @@ impl ImagingSession {
 
         if calibration.dark_frames_to_classify.len() > 0 {
             let id = Uuid::new_v4();
+            // headers without the values fall back to the light frame, mismatches show up as calibration warnings
+            let (gain, camera_temp) = dark_frame_values(&calibration.dark_frames_to_classify);
 
             let frame = DarkFrame {
                 id,
                 camera_id: light_frame.camera_id,
-                gain: 0,
+                gain: gain.unwrap_or(light_frame.gain),
                 frames_to_classify: calibration.dark_frames_to_classify.clone(),
                 frames_classified: vec![],
                 in_imaging_session: true,
                 calibration_type: CalibrationType::DARK,
-                camera_temp: 0.0,
+                camera_temp: camera_temp.or(light_frame.camera_temp).unwrap_or_default(),
                 sub_length: light_frame.sub_length,
+                capture_date: capture_date(&calibration.dark_frames_to_classify, timezone.as_ref()),
             };
 
             imaging_session.dark_frame_id = Some(id);
             dark_frame = Some(frame);
         }
 
+        if !calibration.dark_flat_frames_to_classify.is_empty() {
+            let id = Uuid::new_v4();
+            // the exposure matches the flats, so the values are taken from the files themselves,
+            // the gain falls back to the light frame, other values the headers lack stay empty
+            let analyzed =
+                analyze_calibration_frames(&calibration.dark_flat_frames_to_classify, vec![]);
+
+            let frame = DarkFlatFrame {
+                id,
+                camera_id: light_frame.camera_id,
+                gain: analyzed
+                    .gain
+                    .and_then(|gain| u32::try_from(gain).ok())
+                    .unwrap_or(light_frame.gain),
+                frames_to_classify: calibration.dark_flat_frames_to_classify.clone(),
+                frames_classified: vec![],
+                in_imaging_session: true,
+                calibration_type: CalibrationType::DARKFLAT,
+                camera_temp: analyzed.camera_temp,
+                sub_length: analyzed.sub_length,
+                capture_date: capture_date(&calibration.dark_flat_frames_to_classify, timezone.as_ref()),
+            };
+
+            imaging_session.dark_flat_frame_id = Some(id);
+            dark_flat_frame = Some(frame);
+        }
+
         // Step 1: imaging session
         if let Err(e) = imaging_session.add(state) {
             imaging_session.remove(state).ok();
@@ -103,6 +156,22 @@ impl ImagingSession {
             }
         }
 
+        // Step 5: dark flat frame
+        if let Some(ref dark_flat) = dark_flat_frame {
+            if let Err(e) = dark_flat.add(state) {
+                dark_flat.remove(state).ok();
+                if let Some(ref dark) = dark_frame {
+                    dark.remove(state).ok();
+                }
+                if let Some(ref flat) = flat_frame {
+                    flat.remove(state).ok();
+                }
+                light_frame.remove(state).ok();
+                imaging_session.remove(state).ok();
+                return Err(e);
+            }
+        }
+
         Ok(imaging_session)
     }
 
@@ -122,9 +191,111 @@ impl ImagingSession {
         Ok(())
     }
 
-    // pub fn edit(&self, state: &State<Mutex<AppState>>) -> Result<(), Box<dyn Error>> {
-    //     Ok(())
-    // }
+    // calibration frames stay linked as they are, only the light frame fields are edited
+    pub fn edit(
+        state: &State<AppState>,
+        session: &ImagingSessionEdit,
+    ) -> Result<ImagingSession, Box<dyn Error>> {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let mut imaging_session = db
+            .get_imaging_session_by_id(session.base.id)?
+            .ok_or("Imaging session not found.")?;
+        let mut light_frame = db
+            .get_light_frame_by_id(imaging_session.light_frame_id)?
+            .ok_or("light_frame_id not found")?;
+        drop(db);
+
+        light_frame.edit(session);
+        let location = state
+            .db
+            .lock()
+            .map_err(|e| e.to_string())?
+            .get_location_by_id(light_frame.location_id)?;
+        light_frame.link_target(&state.catalog);
+        light_frame.update_location(location.as_ref());
+
+        let old_dir = imaging_session.folder_dir.clone();
+        let new_dir = ImagingSession::build_path(&light_frame, state)?;
+        let old_path = state.root_directory.join(&old_dir);
+        let new_path = state.root_directory.join(&new_dir);
+
+        let mut moved = false;
+        if old_dir != new_dir {
+            moved = move_folder(&old_path, &new_path)
+                .map_err(|e| format!("Couldn't move imaging session folder: {}", e))?;
+
+            for path in light_frame.frames_classified.iter_mut() {
+                *path = rebase_path(path, &old_dir, &new_dir);
+            }
+            imaging_session.folder_dir = new_dir;
+        }
+
+        let mut db = state.db.lock().map_err(|e| e.to_string())?;
+        if let Err(e) = db.update_imaging_session(&imaging_session, &light_frame, &old_dir) {
+            // revert the folder move
+            if moved {
+                fs::rename(&new_path, &old_path).ok();
+            }
+            return Err(e.into());
+        }
+        drop(db);
+
+        if moved {
+            remove_empty_parents(&old_path, &state.root_directory);
+        }
+
+        Ok(imaging_session)
+    }
+
+    // removes the session with all linked frames, library calibration frames are kept
+    pub fn delete(
+        state: &State<AppState>,
+        id: Uuid,
+        removal: &FileRemoval,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut db = state.db.lock().map_err(|e| e.to_string())?;
+        let imaging_session = db
+            .get_imaging_session_by_id(id)?
+            .ok_or("Imaging session not found.")?;
+
+        let mut files = Vec::new();
+        if let Some(light_frame) = db.get_light_frame_by_id(imaging_session.light_frame_id)? {
+            files.extend(light_frame.frames_classified);
+        }
+        if let Some(flat_id) = imaging_session.flat_frame_id {
+            if let Some(flat_frame) = db.get_flat_frame_by_id(&flat_id)? {
+                if flat_frame.in_imaging_session {
+                    files.extend(flat_frame.frames_classified);
+                }
+            }
+        }
+        if let Some(dark_id) = imaging_session.dark_frame_id {
+            if let Some(dark_frame) = db.get_dark_frame_by_id(&dark_id)? {
+                if dark_frame.in_imaging_session {
+                    files.extend(dark_frame.frames_classified);
+                }
+            }
+        }
+        if let Some(dark_flat_id) = imaging_session.dark_flat_frame_id {
+            if let Some(dark_flat_frame) = db.get_dark_flat_frame_by_id(&dark_flat_id)? {
+                if dark_flat_frame.in_imaging_session {
+                    files.extend(dark_flat_frame.frames_classified);
+                }
+            }
+        }
+
+        db.remove_imaging_session(id)?;
+        drop(db);
+
+        remove_files(&state.root_directory, &files, removal, &id).map_err(|e| {
+            format!(
+                "Imaging session deleted, but some files couldn't be removed:\n{}",
+                e
+            )
+        })?;
+
+        Ok(())
+    }
 
     pub fn from(
         state: &State<AppState>,
@@ -138,9 +309,10 @@ impl ImagingSession {
             id: id.clone(),
             folder_dir,
             light_frame_id: light_frame.id.clone(),
-            flat_frame_id: None,
+            flat_frame_id: calibration.flat_frame_list_id,
             dark_frame_id: calibration.dark_frame_list_id,
             bias_frame_id: calibration.bias_frame_list_id,
+            dark_flat_frame_id: calibration.dark_flat_frame_list_id,
         };
 
         Ok(imaging_session)
@@ -153,18 +325,21 @@ impl ImagingSession {
         let config = state.config.lock().map_err(|e| e.to_string())?;
         let db = state.db.lock().map_err(|e| e.to_string())?;
 
-        let base_folder = config
-            .folder_paths
-            .imaging_session_base_folder
-            .clone();
-        let pattern_path = config
-            .folder_paths
-            .imaging_session_pattern
-            .clone();
         let equipment_list = db.get_equipment_list()?;
 
+        ImagingSession::build_path_with(light_frame, &config.folder_paths, &equipment_list)
+    }
+
+    pub fn build_path_with(
+        light_frame: &LightFrame,
+        folder_paths: &FolderPaths,
+        equipment_list: &EquipmentList,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let base_folder = folder_paths.imaging_session_base_folder.clone();
+        let pattern_path = folder_paths.imaging_session_pattern.clone();
+
         let get_field_value =
-            |field_name: &str| light_frame.get_field_value(field_name, &equipment_list);
+            |field_name: &str| light_frame.get_field_value(field_name, equipment_list);
 
         let path = crate::classify::build_path(&base_folder, &pattern_path, get_field_value)?;
 
@@ -195,6 +370,12 @@ impl ImagingSession {
             .map(|id| db.get_flat_frame_by_id(id))
             .transpose()?
             .flatten();
+        let dark_flat_frame = self
+            .dark_flat_frame_id
+            .as_ref()
+            .map(|id| db.get_dark_flat_frame_by_id(id))
+            .transpose()?
+            .flatten();
         drop(db);
 
         let mut len = light_frame.total_subs();
@@ -204,6 +385,9 @@ impl ImagingSession {
         if let Some(ref frame) = flat_frame {
             len += frame.total_subs();
         }
+        if let Some(ref frame) = dark_flat_frame {
+            len += frame.total_subs();
+        }
 
         let mut process = Process::spawn(
             &window,
@@ -216,22 +400,42 @@ impl ImagingSession {
         let mut errors = Vec::new();
 
         let base = ImagingSession::build_path(&light_frame, state)?;
-        if let Err(e) = light_frame.classify(state, window, &mut process, &base) {
+        let equipment_list = state
+            .db
+            .lock()
+            .map_err(|e| e.to_string())?
+            .get_equipment_list()?;
+        let session = light_frame.clone();
+        let field_value = |field: &str| session.get_field_value(field, &equipment_list);
+
+        if let Err(e) = light_frame.classify(state, window, &mut process, &base, &field_value) {
             errors.push(format!("Light frame error: {}", e));
         }
 
         if let Some(mut frame) = dark_frame {
-            if let Err(e) = frame.classify(state, window, &mut process, &self.folder_dir) {
+            if let Err(e) =
+                frame.classify(state, window, &mut process, &self.folder_dir, &field_value)
+            {
                 errors.push(format!("Dark frame error: {}", e));
             }
         }
 
         if let Some(mut frame) = flat_frame {
-            if let Err(e) = frame.classify(state, window, &mut process, &self.folder_dir) {
+            if let Err(e) =
+                frame.classify(state, window, &mut process, &self.folder_dir, &field_value)
+            {
                 errors.push(format!("Flat frame error: {}", e));
             }
         }
 
+        if let Some(mut frame) = dark_flat_frame {
+            if let Err(e) =
+                frame.classify(state, window, &mut process, &self.folder_dir, &field_value)
+            {
+                errors.push(format!("Dark flat frame error: {}", e));
+            }
+        }
+
         if errors.is_empty() {
             process.finish(window);
             Ok(())
@@ -0,0 +1,317 @@
+use crate::image::read_metadata;
+use crate::models::database::Database;
+use crate::models::imaging_frames::light_frame::LightFrame;
+use crate::models::preferences::Config;
+use crate::models::state::AppState;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::PathBuf;
+use tauri::State;
+use uuid::Uuid;
+
+// the lowest altitude that is still free of obstructions when looking towards the azimuth
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HorizonPoint {
+    pub azimuth: f64,
+    pub altitude: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Location {
+    pub id: Uuid,
+    pub name: String,
+    // decimal degrees, north and east are positive
+    pub latitude: f64,
+    pub longitude: f64,
+    // meters above sea level
+    pub elevation: f64,
+    // IANA name, e.g. "Europe/Berlin"
+    pub timezone: String,
+    // 0 if the bortle class is unknown
+    pub bortle: u32,
+    // sky quality in mag/arcsec²
+    pub sqm: Option<f64>,
+    #[serde(default)]
+    pub horizon: Vec<HorizonPoint>,
+    // moved from the old config without a timezone or with dropped coordinates or created by a CSV import,
+    // cleared once it is saved again
+    #[serde(default)]
+    pub needs_review: bool,
+}
+
+impl Location {
+    pub fn new(id: Uuid, name: String) -> Location {
+        Location {
+            id,
+            name,
+            latitude: 0.0,
+            longitude: 0.0,
+            elevation: 0.0,
+            timezone: "UTC".to_string(),
+            bortle: 0,
+            sqm: None,
+            horizon: vec![],
+            needs_review: false,
+        }
+    }
+
+    pub fn tz(&self) -> Tz {
+        self.timezone.parse().unwrap_or(Tz::UTC)
+    }
+
+    pub fn night_of(&self, date: &DateTime<Utc>) -> NaiveDate {
+        night_of(date, &self.tz())
+    }
+
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.name.trim().is_empty() {
+            return Err("The location needs a name.".into());
+        }
+        if !(-90.0..=90.0).contains(&self.latitude) {
+            return Err("The latitude must be between -90° and 90°.".into());
+        }
+        if !(-180.0..=180.0).contains(&self.longitude) {
+            return Err("The longitude must be between -180° and 180°.".into());
+        }
+        if !(-500.0..=9000.0).contains(&self.elevation) {
+            return Err("The elevation must be between -500 m and 9000 m.".into());
+        }
+        if self.timezone.parse::<Tz>().is_err() {
+            return Err(format!("Unknown timezone: {}", self.timezone).into());
+        }
+        if self.bortle > 9 {
+            return Err("The bortle class must be between 1 and 9.".into());
+        }
+        if let Some(sqm) = self.sqm {
+            if !(16.0..=23.0).contains(&sqm) {
+                return Err("The SQM value must be between 16 and 23 mag/arcsec².".into());
+            }
+        }
+        for point in &self.horizon {
+            if !(0.0..=360.0).contains(&point.azimuth) || !(-90.0..=90.0).contains(&point.altitude)
+            {
+                return Err(
+                    "Horizon points need an azimuth between 0° and 360° and an altitude between -90° and 90°."
+                        .into(),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn save(&self, state: &State<AppState>) -> Result<(), Box<dyn Error>> {
+        self.validate()?;
+
+        let mut db = state.db.lock().map_err(|e| e.to_string())?;
+
+        for existing_location in db.get_locations()?.values() {
+            if existing_location.name == self.name && existing_location.id != self.id {
+                return Err("A location with the same name already exists.".into());
+            }
+        }
+
+        let mut location = self.clone();
+        location.needs_review = false;
+        location
+            .horizon
+            .sort_by(|a, b| a.azimuth.total_cmp(&b.azimuth));
+        db.insert_location(&location)?;
+
+        // the moon altitude of the sessions shot here depends on the coordinates, their night is kept
+        // until the session is edited, because the folder is named after it
+        for mut light_frame in db.get_light_frames()?.into_values() {
+            if light_frame.location_id == self.id {
+                light_frame.update_moon(Some(&location));
+                db.insert_light_frame(&light_frame)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn delete(&self, state: &State<AppState>) -> Result<(), Box<dyn Error>> {
+        let mut db = state.db.lock().map_err(|e| e.to_string())?;
+
+        // Check if any frame is using this location
+        if db
+            .get_light_frames()?
+            .values()
+            .any(|frame| frame.location_id == self.id)
+        {
+            return Err(
+                "Can't delete location: This location is used in an imaging session!".into(),
+            );
+        }
+
+        db.remove_location(self.id)?;
+
+        Ok(())
+    }
+}
+
+// a night runs from noon to noon local time, so sessions past midnight keep the date they started on
+pub fn night_of(date: &DateTime<Utc>, tz: &Tz) -> NaiveDate {
+    (date.with_timezone(tz) - Duration::hours(12)).date_naive()
+}
+
+// locations used to be stored in config.json, where x, y and height had no defined meaning
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LegacyLocation {
+    id: Uuid,
+    name: String,
+    x: f64,
+    y: f64,
+    height: f64,
+    bortle: u32,
+}
+
+impl LegacyLocation {
+    fn to_location(&self) -> Location {
+        let mut location = Location::new(self.id, self.name.clone());
+        // the old config had no timezone, UTC only stands in until the location is reviewed
+        location.needs_review = true;
+
+        // the frontend validated x as a longitude and y as a latitude, out of range values are dropped
+        if (-90.0..=90.0).contains(&self.y) {
+            location.latitude = self.y;
+        } else {
+            eprintln!("Dropped the latitude {} of location {}", self.y, self.name);
+        }
+        if (-180.0..=180.0).contains(&self.x) {
+            location.longitude = self.x;
+        } else {
+            eprintln!("Dropped the longitude {} of location {}", self.x, self.name);
+        }
+        location.elevation = self.height;
+        location.bortle = self.bortle.min(9);
+
+        location
+    }
+}
+
+// moves the locations of config.json into the database, config.json is only rewritten once all are stored
+pub fn migrate_config_locations(
+    config: &mut Config,
+    db: &mut Database,
+    dir: PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    if config.legacy_locations.is_empty() {
+        return Ok(());
+    }
+
+    for legacy_location in config.legacy_locations.values() {
+        if db.get_location_by_id(legacy_location.id)?.is_none() {
+            db.insert_location(&legacy_location.to_location())?;
+        }
+    }
+
+    let legacy_locations = std::mem::take(&mut config.legacy_locations);
+    if let Err(e) = config.save(dir) {
+        config.legacy_locations = legacy_locations;
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+// sessions added before the night and moon were computed still have neither,
+// their night is the date their folder was named after
+pub fn fill_location_values(db: &mut Database, root: &PathBuf) -> Result<(), Box<dyn Error>> {
+    let locations = db.get_locations()?;
+    let light_frames: Vec<LightFrame> = db
+        .get_light_frames()?
+        .into_values()
+        .filter(|frame| {
+            frame.night_of.is_none() || (frame.average_moon == 0.0 && frame.moon_altitude.is_none())
+        })
+        .collect();
+
+    for mut light_frame in light_frames {
+        if let Some(path) = light_frame.frames_classified.first() {
+            if let Ok(metadata) = read_metadata(&root.join(path)) {
+                light_frame.ra = light_frame.ra.or(metadata.ra);
+                light_frame.dec = light_frame.dec.or(metadata.dec);
+            }
+        }
+
+        light_frame.night_of = Some(light_frame.night());
+        light_frame.update_moon(locations.get(&light_frame.location_id));
+        db.insert_light_frame(&light_frame)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::light_frame;
+    use chrono::TimeZone;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn sessions_past_midnight_keep_their_night() {
+        let mut location = Location::new(Uuid::new_v4(), "Backyard".to_string());
+        location.timezone = "Europe/Berlin".to_string();
+
+        // 23:30 and 02:30 local time in summer
+        let evening = Utc.with_ymd_and_hms(2024, 7, 12, 21, 30, 0).unwrap();
+        let morning = Utc.with_ymd_and_hms(2024, 7, 13, 0, 30, 0).unwrap();
+        // 13:00 local time starts the next night
+        let afternoon = Utc.with_ymd_and_hms(2024, 7, 13, 11, 0, 0).unwrap();
+
+        assert_eq!(location.night_of(&evening), date(2024, 7, 12));
+        assert_eq!(location.night_of(&morning), date(2024, 7, 12));
+        assert_eq!(location.night_of(&afternoon), date(2024, 7, 13));
+    }
+
+    #[test]
+    fn the_night_follows_the_local_date() {
+        // 00:30 UTC is the evening before in New York and the late morning after in Sydney
+        let utc = Utc.with_ymd_and_hms(2024, 1, 10, 0, 30, 0).unwrap();
+
+        assert_eq!(night_of(&utc, &Tz::America__New_York), date(2024, 1, 9));
+        assert_eq!(night_of(&utc, &Tz::Australia__Sydney), date(2024, 1, 9));
+        assert_eq!(night_of(&utc, &Tz::UTC), date(2024, 1, 9));
+
+        let noon_in_sydney = Utc.with_ymd_and_hms(2024, 1, 10, 1, 30, 0).unwrap();
+        assert_eq!(
+            night_of(&noon_in_sydney, &Tz::Australia__Sydney),
+            date(2024, 1, 10)
+        );
+    }
+
+    #[test]
+    fn unknown_timezones_fall_back_to_utc() {
+        let mut location = Location::new(Uuid::new_v4(), "Backyard".to_string());
+        location.timezone = "Mars/Olympus_Mons".to_string();
+
+        assert_eq!(location.tz(), Tz::UTC);
+        assert!(location.validate().is_err());
+    }
+
+    #[test]
+    fn sessions_wait_for_the_review_of_their_location() {
+        let mut location = Location::new(Uuid::new_v4(), "Backyard".to_string());
+        location.needs_review = true;
+        let mut light_frame = light_frame(&[]);
+
+        light_frame.update_location(Some(&location));
+        assert_eq!(light_frame.night_of, None);
+        assert_eq!(light_frame.moon_altitude, None);
+
+        location.timezone = "Asia/Tokyo".to_string();
+        location.latitude = 35.7;
+        location.longitude = 139.7;
+        location.needs_review = false;
+        light_frame.update_location(Some(&location));
+        assert_eq!(light_frame.night_of, Some(date(2024, 3, 9)));
+        assert!(light_frame.moon_altitude.is_some());
+    }
+}
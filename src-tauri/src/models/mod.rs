@@ -3,6 +3,8 @@ pub mod frontend;
 pub mod gallery_image_list;
 pub mod imaging_frames;
 pub mod imaging_session;
+pub mod location;
 pub mod preferences;
+pub mod relayout;
 pub mod state;
 pub mod database;
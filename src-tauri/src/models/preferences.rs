@@ -1,11 +1,12 @@
 use crate::file_store;
-use crate::models::state::AppState;
+use crate::models::imaging_frames::calibration_type::CalibrationType;
+use crate::models::location::LegacyLocation;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::to_string_pretty;
 use std::collections::HashMap;
 use std::error::Error;
 use std::path::PathBuf;
-use tauri::State;
 use uuid::Uuid;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -14,6 +15,29 @@ pub enum Unit {
     IMPERIAL,
 }
 
+impl Unit {
+    pub fn temperature(&self, celsius: f64) -> f64 {
+        match self {
+            Unit::METRIC => celsius,
+            Unit::IMPERIAL => celsius * 9.0 / 5.0 + 32.0,
+        }
+    }
+
+    pub fn celsius(&self, temperature: f64) -> f64 {
+        match self {
+            Unit::METRIC => temperature,
+            Unit::IMPERIAL => (temperature - 32.0) * 5.0 / 9.0,
+        }
+    }
+
+    pub fn temperature_symbol(&self) -> &'static str {
+        match self {
+            Unit::METRIC => "°C",
+            Unit::IMPERIAL => "°F",
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LocalConfig {
     pub schema_version: u32,
@@ -52,7 +76,16 @@ impl LocalConfig {
 pub struct Config {
     pub schema_version: u32,
     pub folder_paths: FolderPaths,
-    pub locations: HashMap<Uuid, Location>,
+    #[serde(rename = "locations", default, skip_serializing_if = "HashMap::is_empty")]
+    pub legacy_locations: HashMap<Uuid, LegacyLocation>,
+    #[serde(default)]
+    pub calibration_tolerances: CalibrationTolerances,
+    #[serde(default)]
+    pub calibration_validity: CalibrationValidity,
+    #[serde(default)]
+    pub import_mode: ImportMode,
+    #[serde(default)]
+    pub collision_handling: CollisionHandling,
 }
 
 impl Config {
@@ -65,12 +98,19 @@ impl Config {
             calibration_base_folder: PathBuf::new(),
             dark_frame_pattern: PathBuf::new(),
             bias_frame_pattern: PathBuf::new(),
+            flat_frame_pattern: PathBuf::new(),
+            dark_flat_frame_pattern: PathBuf::new(),
+            file_patterns: FilePatterns::default(),
         };
 
         Config {
             schema_version: 1,
             folder_paths,
-            locations: HashMap::new(),
+            legacy_locations: HashMap::new(),
+            calibration_tolerances: CalibrationTolerances::default(),
+            calibration_validity: CalibrationValidity::default(),
+            import_mode: ImportMode::default(),
+            collision_handling: CollisionHandling::default(),
         }
     }
 
@@ -89,6 +129,62 @@ impl Config {
     }
 }
 
+// how classified frames get from the capture drive into the library
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ImportMode {
+    #[default]
+    COPY,
+    // copies, verifies the copy and deletes the source afterwards
+    MOVE,
+    // both fall back to a copy where the filesystem can't do them
+    HARDLINK,
+    REFLINK,
+}
+
+// what happens when a classified file would overwrite one that is already there
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum CollisionStrategy {
+    SKIP,
+    // Light_001.fits becomes Light_001_1.fits
+    #[default]
+    SUFFIX,
+    PATTERN,
+    // nothing of the selection is classified
+    ABORT,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CollisionHandling {
+    pub strategy: CollisionStrategy,
+    // new file name without the extension, a suffix is still added if it is taken as well
+    pub pattern: String,
+}
+
+impl CollisionHandling {
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.strategy != CollisionStrategy::PATTERN {
+            return Ok(());
+        }
+        if !self.pattern.contains("$$") {
+            return Err("The file name pattern needs at least one $$TOKEN$$.".into());
+        }
+        if self.pattern.contains(['/', '\\']) {
+            return Err("The file name pattern can't contain folders.".into());
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for CollisionHandling {
+    fn default() -> Self {
+        CollisionHandling {
+            strategy: CollisionStrategy::default(),
+            pattern: "$$NAME$$_$$TIMESTAMP$$".to_string(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct FolderPaths {
     pub imaging_session_base_folder: PathBuf,
@@ -97,65 +193,122 @@ pub struct FolderPaths {
     pub calibration_base_folder: PathBuf,
     pub dark_frame_pattern: PathBuf,
     pub bias_frame_pattern: PathBuf,
+    #[serde(default)]
+    pub flat_frame_pattern: PathBuf,
+    #[serde(default)]
+    pub dark_flat_frame_pattern: PathBuf,
+    #[serde(default)]
+    pub file_patterns: FilePatterns,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Location {
-    id: Uuid,
-    pub name: String,
-    x: f64,
-    y: f64,
-    height: f64,
-    pub bortle: u32,
-}
-
-impl Location {
-    pub fn save(&self, state: &State<AppState>) -> Result<(), Box<dyn Error>> {
-        let mut config = state.config.lock().map_err(|e| e.to_string())?;
-
-        for existing_location in config.locations.values() {
-            if existing_location.name == self.name && existing_location.id != self.id {
-                return Err("A location with the same name already exists.".into());
-            }
-        }
-
-        let old_locations = config.locations.clone();
-
-        config.locations.insert(self.id, self.clone());
+// names of the classified subs without their extension, empty patterns keep the original names
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FilePatterns {
+    pub light: String,
+    pub dark: String,
+    pub bias: String,
+    pub flat: String,
+    pub dark_flat: String,
+}
 
-        if let Err(e) = config.save(state.root_directory.clone()) {
-            config.locations = old_locations;
-            return Err(e);
+impl FilePatterns {
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        for pattern in [
+            &self.light,
+            &self.dark,
+            &self.bias,
+            &self.flat,
+            &self.dark_flat,
+        ] {
+            if pattern.contains(['/', '\\']) {
+                return Err("File name patterns can't contain folders.".into());
+            }
+            if pattern.contains(['<', '>', ':', '"', '|', '?', '*']) {
+                return Err(r#"File name patterns can't contain < > : " | ? *"#.into());
+            }
+            // an empty pattern keeps the original name
+            if !pattern.is_empty()
+                && !pattern.contains("$$SEQUENCE$$")
+                && !pattern.contains("$$TIMESTAMP$$")
+            {
+                return Err(
+                    "File name patterns need $$SEQUENCE$$ or $$TIMESTAMP$$ to tell the frames apart."
+                        .into(),
+                );
+            }
         }
 
         Ok(())
     }
+}
+
+// how far a library calibration set may differ from a session and still be suggested for it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CalibrationTolerances {
+    pub gain: u32,
+    pub offset: u32,
+    pub sub_length_percent: f64,
+    pub camera_temp: f64,
+}
 
-    pub fn delete(&self, state: &State<AppState>) -> Result<(), Box<dyn Error>> {
-        let mut config = state.config.lock().map_err(|e| e.to_string())?;
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-
-        // Check if any frame is using this location
-        if db
-            .get_light_frames()
-            .map_err(|e| e.to_string())?
-            .values()
-            .any(|frame| frame.location_id == self.id)
-        {
-            return Err(
-                "Can't delete location: This location is used in an imaging session!".into(),
-            );
+impl Default for CalibrationTolerances {
+    fn default() -> Self {
+        CalibrationTolerances {
+            gain: 0,
+            offset: 0,
+            sub_length_percent: 1.0,
+            camera_temp: 2.0,
         }
+    }
+}
 
-        let old_locations = config.locations.clone();
+// days a calibration set stays valid after its capture date, None means it never expires
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CalibrationValidity {
+    pub dark_days: Option<u32>,
+    pub bias_days: Option<u32>,
+    // 0 means the flats have to be taken the same night
+    pub flat_days: Option<u32>,
+    pub dark_flat_days: Option<u32>,
+    // flats taken with the session's telescope and filter stay valid regardless of their age
+    pub flat_same_train: bool,
+}
 
-        config.locations.remove(&self.id);
+impl CalibrationValidity {
+    pub fn max_days(&self, calibration_type: &CalibrationType) -> Option<u32> {
+        match calibration_type {
+            CalibrationType::DARK => self.dark_days,
+            CalibrationType::BIAS => self.bias_days,
+            CalibrationType::FLAT => self.flat_days,
+            CalibrationType::DARKFLAT => self.dark_flat_days,
+            CalibrationType::DEFAULT => None,
+        }
+    }
 
-        if let Err(e) = config.save(state.root_directory.clone()) {
-            config.locations = old_locations;
-            return Err(e);
+    // sets with an unknown capture date can't expire
+    pub fn expired(
+        &self,
+        calibration_type: &CalibrationType,
+        capture_date: &Option<DateTime<Utc>>,
+        at: &DateTime<Utc>,
+    ) -> bool {
+        match (self.max_days(calibration_type), capture_date) {
+            (Some(max_days), Some(capture_date)) => {
+                (*at - *capture_date).num_days().abs() > max_days as i64
+            }
+            _ => false,
         }
+    }
+}
 
-        Ok(())
+impl Default for CalibrationValidity {
+    fn default() -> Self {
+        CalibrationValidity {
+            dark_days: Some(180),
+            bias_days: Some(365),
+            flat_days: Some(0),
+            dark_flat_days: Some(180),
+            flat_same_train: true,
+        }
     }
 }
@@ -0,0 +1,363 @@
+use crate::file_store;
+use crate::file_system::{common_parent, is_directory_empty, move_folder, remove_empty_parents};
+use crate::models::database::Database;
+use crate::models::imaging_frames::imaging_frame::CalibrationFrame;
+use crate::models::imaging_session::ImagingSession;
+use crate::models::preferences::FolderPaths;
+use crate::models::state::AppState;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use tauri::State;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum FolderKind {
+    IMAGINGSESSION,
+    DARK,
+    BIAS,
+    FLAT,
+    DARKFLAT,
+}
+
+// folders are moved in two steps through a staging folder, so nested or swapped paths can't clash
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum MoveState {
+    PENDING,
+    STAGED,
+    MOVED,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FolderMove {
+    pub id: Uuid,
+    pub kind: FolderKind,
+    pub old_dir: PathBuf,
+    pub new_dir: PathBuf,
+    pub state: MoveState,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FolderCollision {
+    pub new_dir: PathBuf,
+    pub old_dirs: Vec<PathBuf>,
+    pub exists: bool,
+}
+
+// the plan is stored in the metadata folder while it is executed, so an interrupted re-layout can be resumed
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RelayoutPlan {
+    pub folder_paths: FolderPaths,
+    pub moves: Vec<FolderMove>,
+    pub collisions: Vec<FolderCollision>,
+    // folders with classified files that aren't where the database expects them
+    #[serde(default)]
+    pub missing: Vec<PathBuf>,
+}
+
+impl RelayoutPlan {
+    pub fn new(
+        state: &State<AppState>,
+        folder_paths: &FolderPaths,
+    ) -> Result<RelayoutPlan, Box<dyn Error>> {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+
+        let moves = RelayoutPlan::build_moves(&db, folder_paths)?;
+        let collisions = RelayoutPlan::find_collisions(&state.root_directory, &moves);
+        let missing = moves
+            .iter()
+            .filter(|m| {
+                m.state == MoveState::PENDING && !state.root_directory.join(&m.old_dir).exists()
+            })
+            .map(|m| m.old_dir.clone())
+            .collect();
+
+        Ok(RelayoutPlan {
+            folder_paths: folder_paths.clone(),
+            moves,
+            collisions,
+            missing,
+        })
+    }
+
+    // the old folders are taken from where the files are, not rebuilt from the old patterns
+    fn build_moves(db: &Database, new: &FolderPaths) -> Result<Vec<FolderMove>, Box<dyn Error>> {
+        let equipment_list = db.get_equipment_list()?;
+        let mut moves = vec![];
+
+        let mut push =
+            |id: Uuid, kind: FolderKind, old_dir: PathBuf, new_dir: PathBuf, state: MoveState| {
+                if old_dir != new_dir {
+                    moves.push(FolderMove {
+                        id,
+                        kind,
+                        old_dir,
+                        new_dir,
+                        state,
+                    });
+                }
+            };
+
+        for session in db.get_imaging_sessions()?.values() {
+            let light_frame = match db.get_light_frame_by_id(session.light_frame_id)? {
+                Some(light_frame) => light_frame,
+                None => continue,
+            };
+            let new_dir = ImagingSession::build_path_with(&light_frame, new, &equipment_list)?;
+            // a session without classified lights, e.g. an imported one, only changes in the database
+            let state = if light_frame.frames_classified.is_empty() {
+                MoveState::MOVED
+            } else {
+                MoveState::PENDING
+            };
+
+            push(
+                session.id,
+                FolderKind::IMAGINGSESSION,
+                session.folder_dir.clone(),
+                new_dir,
+                state,
+            );
+        }
+
+        // darks taken within a session are stored in the session folder
+        for dark_frame in db.get_dark_frames()?.values() {
+            if dark_frame.in_imaging_session {
+                continue;
+            }
+            // a set without classified files has no folder
+            let old_dir = match common_parent(&dark_frame.frames_classified) {
+                Some(old_dir) => old_dir,
+                None => continue,
+            };
+
+            push(
+                dark_frame.id,
+                FolderKind::DARK,
+                old_dir,
+                dark_frame.build_path_with(new, &equipment_list)?,
+                MoveState::PENDING,
+            );
+        }
+
+        for bias_frame in db.get_bias_frames()?.values() {
+            // a set without classified files has no folder
+            let old_dir = match common_parent(&bias_frame.frames_classified) {
+                Some(old_dir) => old_dir,
+                None => continue,
+            };
+
+            push(
+                bias_frame.id,
+                FolderKind::BIAS,
+                old_dir,
+                bias_frame.build_path_with(new, &equipment_list)?,
+                MoveState::PENDING,
+            );
+        }
+
+        // flats taken within a session are stored in the session folder
+        for flat_frame in db.get_flat_frames()?.values() {
+            if flat_frame.in_imaging_session {
+                continue;
+            }
+            // a set without classified files has no folder
+            let old_dir = match common_parent(&flat_frame.frames_classified) {
+                Some(old_dir) => old_dir,
+                None => continue,
+            };
+
+            push(
+                flat_frame.id,
+                FolderKind::FLAT,
+                old_dir,
+                flat_frame.build_path_with(new, &equipment_list)?,
+                MoveState::PENDING,
+            );
+        }
+
+        // dark flats taken within a session are stored in the session folder
+        for dark_flat_frame in db.get_dark_flat_frames()?.values() {
+            if dark_flat_frame.in_imaging_session {
+                continue;
+            }
+            // a set without classified files has no folder
+            let old_dir = match common_parent(&dark_flat_frame.frames_classified) {
+                Some(old_dir) => old_dir,
+                None => continue,
+            };
+
+            push(
+                dark_flat_frame.id,
+                FolderKind::DARKFLAT,
+                old_dir,
+                dark_flat_frame.build_path_with(new, &equipment_list)?,
+                MoveState::PENDING,
+            );
+        }
+
+        Ok(moves)
+    }
+
+    // two entries ending up in the same folder, or a target that is already used by other files
+    fn find_collisions(root: &PathBuf, moves: &Vec<FolderMove>) -> Vec<FolderCollision> {
+        let mut targets: HashMap<&PathBuf, Vec<PathBuf>> = HashMap::new();
+        for folder_move in moves {
+            targets
+                .entry(&folder_move.new_dir)
+                .or_default()
+                .push(folder_move.old_dir.clone());
+        }
+
+        let mut collisions = vec![];
+        for (new_dir, old_dirs) in targets {
+            let vacated = moves.iter().any(|m| &m.old_dir == new_dir);
+            let full_path = root.join(new_dir);
+            let exists =
+                !vacated && full_path.is_dir() && !is_directory_empty(&full_path).unwrap_or(true);
+
+            if old_dirs.len() > 1 || exists {
+                collisions.push(FolderCollision {
+                    new_dir: new_dir.clone(),
+                    old_dirs,
+                    exists,
+                });
+            }
+        }
+
+        collisions.sort_by(|a, b| a.new_dir.cmp(&b.new_dir));
+        collisions
+    }
+
+    // moves the folders, rewrites the paths in the database and saves the new folder paths
+    pub fn execute(&mut self, state: &State<AppState>) -> Result<(), Box<dyn Error>> {
+        if !self.collisions.is_empty() {
+            return Err(format!(
+                "Can't change folder structure: {} folder(s) would collide.",
+                self.collisions.len()
+            )
+            .into());
+        }
+        if !self.missing.is_empty() {
+            return Err(format!(
+                "Can't change folder structure: {} folder(s) are missing: {:?}",
+                self.missing.len(),
+                self.missing
+            )
+            .into());
+        }
+
+        let root = &state.root_directory;
+        let mut config = state.config.lock().map_err(|e| e.to_string())?;
+
+        self.save(root)?;
+
+        if let Err(e) = self.move_folders(root) {
+            return Err(format!("Re-layout interrupted, it can be resumed: {}", e).into());
+        }
+
+        {
+            let mut db = state.db.lock().map_err(|e| e.to_string())?;
+            if let Err(e) = db.relocate_folders(&self.moves) {
+                self.revert(root)?;
+                return Err(e.into());
+            }
+        }
+
+        config.folder_paths = self.folder_paths.clone();
+        config.save(root.clone())?;
+
+        for folder_move in &self.moves {
+            remove_empty_parents(&root.join(&folder_move.old_dir), root);
+        }
+        let _ = fs::remove_dir_all(RelayoutPlan::staging_dir(root));
+        RelayoutPlan::remove(root)?;
+
+        Ok(())
+    }
+
+    // every step is saved, a missing source only counts as done if its destination is there
+    fn move_folders(&mut self, root: &PathBuf) -> Result<(), Box<dyn Error>> {
+        for i in 0..self.moves.len() {
+            if self.moves[i].state == MoveState::PENDING {
+                let staged = self.staged_dir(root, i);
+                let old_dir = root.join(&self.moves[i].old_dir);
+                if !move_folder(&old_dir, &staged)? && !staged.exists() {
+                    return Err(format!("{:?} is missing.", old_dir).into());
+                }
+                self.moves[i].state = MoveState::STAGED;
+                self.save(root)?;
+            }
+        }
+
+        for i in 0..self.moves.len() {
+            if self.moves[i].state == MoveState::STAGED {
+                let staged = self.staged_dir(root, i);
+                let new_dir = root.join(&self.moves[i].new_dir);
+                if !move_folder(&staged, &new_dir)? && !new_dir.exists() {
+                    return Err(format!(
+                        "{:?} is missing, it was never moved to {:?}.",
+                        staged, new_dir
+                    )
+                    .into());
+                }
+                self.moves[i].state = MoveState::MOVED;
+                self.save(root)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn revert(&mut self, root: &PathBuf) -> Result<(), Box<dyn Error>> {
+        for i in 0..self.moves.len() {
+            move_folder(
+                &root.join(&self.moves[i].new_dir),
+                &self.staged_dir(root, i),
+            )?;
+        }
+        for i in 0..self.moves.len() {
+            move_folder(
+                &self.staged_dir(root, i),
+                &root.join(&self.moves[i].old_dir),
+            )?;
+            remove_empty_parents(&root.join(&self.moves[i].new_dir), root);
+        }
+
+        let _ = fs::remove_dir_all(RelayoutPlan::staging_dir(root));
+        RelayoutPlan::remove(root)
+    }
+
+    fn staged_dir(&self, root: &PathBuf, index: usize) -> PathBuf {
+        RelayoutPlan::staging_dir(root).join(self.moves[index].id.to_string())
+    }
+
+    fn staging_dir(root: &PathBuf) -> PathBuf {
+        root.join(".astrolog").join("relayout")
+    }
+
+    fn filename(root: &PathBuf) -> PathBuf {
+        root.join(".astrolog").join("relayout.json")
+    }
+
+    pub fn load(root: &PathBuf) -> Result<Option<RelayoutPlan>, Box<dyn Error>> {
+        let filename = RelayoutPlan::filename(root);
+        if !filename.exists() {
+            return Ok(None);
+        }
+        Ok(Some(file_store::load(&filename)?))
+    }
+
+    fn save(&self, root: &PathBuf) -> Result<(), Box<dyn Error>> {
+        file_store::save(
+            &RelayoutPlan::filename(root),
+            &serde_json::to_string_pretty(self)?,
+        )
+    }
+
+    fn remove(root: &PathBuf) -> Result<(), Box<dyn Error>> {
+        Ok(fs::remove_file(RelayoutPlan::filename(root))?)
+    }
+}
@@ -3,14 +3,29 @@ use std::path::PathBuf;
 use crate::models::preferences::{Config, LocalConfig};
 use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Manager};
+use crate::catalog::Catalog;
+use crate::duplicates::HashCache;
 use crate::models::database::Database;
+use crate::models::location::migrate_config_locations;
+use crate::duplicates::fill_frame_file_dates;
+use crate::catalog::link_catalog_targets;
+use crate::models::imaging_frames::dark_frame::fill_dark_frame_values;
+use crate::models::imaging_frames::imaging_frame::{
+    fill_capture_dates, redate_session_calibration,
+};
+use crate::models::location::fill_location_values;
+use std::error::Error;
 
 pub struct AppState {
     pub root_directory: PathBuf,
     pub local_config: Arc<Mutex<LocalConfig>>,
     pub config: Arc<Mutex<Config>>,
     pub db: Arc<Mutex<Database>>,
+    pub catalog: Catalog,
+    pub hashes: HashCache,
     pub close_lock: Arc<Mutex<bool>>,
+    // failed backfills of this start, shown once the frontend has loaded
+    pub backfill_errors: Arc<Mutex<Vec<String>>>,
 }
 
 impl AppState {
@@ -42,14 +57,66 @@ impl AppState {
             local_config.root_directory.clone()
         };
 
-        let db = Database::new(&root_directory).unwrap();
+        let mut db = Database::new(&root_directory).unwrap();
+
+        if let Err(err) =
+            migrate_config_locations(&mut config, &mut db, local_config.root_directory.clone())
+        {
+            eprintln!("Error moving locations into the database: {}", err);
+        }
+
+        let catalog = Catalog::load(&local_config.root_directory);
+
+        let backfill_errors = run_backfills(&mut db, &local_config.root_directory, &catalog);
 
         AppState {
             root_directory: local_config.root_directory.clone(),
             local_config: Arc::new(Mutex::new(local_config)),
             config: Arc::new(Mutex::new(config)),
             db: Arc::new(Mutex::new(db)),
+            catalog,
+            hashes: HashCache::default(),
             close_lock: Arc::new(Mutex::new(false)),
+            backfill_errors: Arc::new(Mutex::new(backfill_errors)),
         }
     }
 }
+
+// values older entries were stored without, every backfill runs once per library
+fn run_backfills(db: &mut Database, root: &PathBuf, catalog: &Catalog) -> Vec<String> {
+    let mut errors = vec![];
+    let mut run = |name: &str, fill: &dyn Fn(&mut Database) -> Result<(), Box<dyn Error>>| {
+        if let Err(err) = run_backfill(db, name, fill) {
+            errors.push(format!(
+                "Updating the {} of the library failed: {}",
+                name.replace('_', " "),
+                err
+            ));
+        }
+    };
+
+    run("capture_dates", &|db| fill_capture_dates(db, root));
+    run("session_capture_dates", &|db| {
+        redate_session_calibration(db, root)
+    });
+    run("dark_frame_values", &|db| fill_dark_frame_values(db, root));
+    run("location_values", &|db| fill_location_values(db, root));
+    run("catalog_targets", &|db| link_catalog_targets(db, catalog));
+    run("frame_file_dates", &|db| fill_frame_file_dates(db, root));
+
+    errors
+}
+
+// a backfill is recorded once it went through, one that fails is tried again at the next start
+fn run_backfill(
+    db: &mut Database,
+    name: &str,
+    fill: &dyn Fn(&mut Database) -> Result<(), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    if !db.backfill_done(name)? {
+        fill(db)?;
+        db.mark_backfill_done(name)?;
+    }
+
+    Ok(())
+}
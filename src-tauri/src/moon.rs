@@ -0,0 +1,153 @@
+use crate::astronomy::{cos, horizontal_at, julian_day, obliquity, sin, sun_longitude};
+use chrono::{DateTime, Duration, Utc};
+
+// the positions follow the low precision formulae of the Astronomical Almanac,
+// which are accurate to a few tenths of a degree and need no ephemeris files
+
+const SAMPLE_MINUTES: i64 = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoonConditions {
+    // illuminated fraction of the disk in percent
+    pub illumination: f64,
+    // altitude above the horizon in degrees, only known with an observer location
+    pub altitude: Option<f64>,
+    // angular distance to the target in degrees, only known with target coordinates
+    pub separation: Option<f64>,
+}
+
+struct MoonPosition {
+    longitude: f64,
+    latitude: f64,
+    parallax: f64,
+    ra: f64,
+    dec: f64,
+}
+
+// averages over the session, which is sampled every ten minutes from start to end
+pub fn moon_conditions(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    observer: Option<(f64, f64)>,
+    target: Option<(f64, f64)>,
+) -> MoonConditions {
+    let mut samples = vec![start];
+    let mut time = start + Duration::minutes(SAMPLE_MINUTES);
+    while time < end {
+        samples.push(time);
+        time += Duration::minutes(SAMPLE_MINUTES);
+    }
+    if end > start {
+        samples.push(end);
+    }
+
+    let mut illumination = 0.0;
+    let mut altitude = 0.0;
+    let mut separation = 0.0;
+    for date in &samples {
+        let jd = julian_day(date);
+        let moon = moon_position(jd);
+
+        illumination += illuminated_fraction(&moon, jd) * 100.0;
+        if let Some((latitude, longitude)) = observer {
+            altitude += topocentric_altitude(&moon, jd, latitude, longitude);
+        }
+        if let Some((ra, dec)) = target {
+            separation += angular_separation(moon.ra, moon.dec, ra, dec);
+        }
+    }
+
+    let count = samples.len() as f64;
+    MoonConditions {
+        illumination: illumination / count,
+        altitude: observer.map(|_| altitude / count),
+        separation: target.map(|_| separation / count),
+    }
+}
+
+fn moon_position(jd: f64) -> MoonPosition {
+    let t = (jd - 2_451_545.0) / 36_525.0;
+
+    let longitude = 218.32 + 481_267.881 * t + 6.29 * sin(135.0 + 477_198.87 * t)
+        - 1.27 * sin(259.3 - 413_335.36 * t)
+        + 0.66 * sin(235.7 + 890_534.22 * t)
+        + 0.21 * sin(269.9 + 954_397.74 * t)
+        - 0.19 * sin(357.5 + 35_999.05 * t)
+        - 0.11 * sin(186.5 + 966_404.03 * t);
+    let latitude = 5.13 * sin(93.3 + 483_202.02 * t) + 0.28 * sin(228.2 + 960_400.89 * t)
+        - 0.28 * sin(318.3 + 6_003.15 * t)
+        - 0.17 * sin(217.6 - 407_332.21 * t);
+    let parallax = 0.9508
+        + 0.0518 * cos(135.0 + 477_198.87 * t)
+        + 0.0095 * cos(259.3 - 413_335.36 * t)
+        + 0.0078 * cos(235.7 + 890_534.22 * t)
+        + 0.0028 * cos(269.9 + 954_397.74 * t);
+
+    let longitude = longitude.rem_euclid(360.0);
+    let epsilon = obliquity(jd);
+    let ra = (sin(longitude) * cos(epsilon) - latitude.to_radians().tan() * sin(epsilon))
+        .atan2(cos(longitude))
+        .to_degrees()
+        .rem_euclid(360.0);
+    let dec = (sin(latitude) * cos(epsilon) + cos(latitude) * sin(epsilon) * sin(longitude))
+        .asin()
+        .to_degrees();
+
+    MoonPosition {
+        longitude,
+        latitude,
+        parallax,
+        ra,
+        dec,
+    }
+}
+
+// the phase angle is taken as the supplement of the elongation from the sun
+fn illuminated_fraction(moon: &MoonPosition, jd: f64) -> f64 {
+    let cos_elongation = cos(moon.latitude) * cos(moon.longitude - sun_longitude(jd));
+
+    (1.0 - cos_elongation) / 2.0
+}
+
+fn topocentric_altitude(moon: &MoonPosition, jd: f64, latitude: f64, longitude: f64) -> f64 {
+    let (altitude, _) = horizontal_at(moon.ra, moon.dec, jd, latitude, longitude);
+
+    // seen from the surface the moon stands lower by up to its horizontal parallax
+    altitude - moon.parallax * cos(altitude)
+}
+
+fn angular_separation(ra_a: f64, dec_a: f64, ra_b: f64, dec_b: f64) -> f64 {
+    (sin(dec_a) * sin(dec_b) + cos(dec_a) * cos(dec_b) * cos(ra_a - ra_b))
+        .clamp(-1.0, 1.0)
+        .acos()
+        .to_degrees()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn full_moon_is_fully_illuminated() {
+        // full moon of 2024-04-23 23:49 UTC
+        let full = Utc.with_ymd_and_hms(2024, 4, 23, 23, 49, 0).unwrap();
+        let conditions = moon_conditions(full, full, None, None);
+
+        assert!(conditions.illumination > 99.0);
+        assert_eq!(conditions.altitude, None);
+        assert_eq!(conditions.separation, None);
+    }
+
+    #[test]
+    fn eclipse_moon_stands_before_the_sun() {
+        // totality of the solar eclipse of 2024-04-08 in Dallas, the sun stood at RA 1h09m, Dec +7.6°
+        let start = Utc.with_ymd_and_hms(2024, 4, 8, 18, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 4, 8, 18, 40, 0).unwrap();
+        let conditions = moon_conditions(start, end, Some((32.78, -96.8)), Some((17.3, 7.6)));
+
+        assert!(conditions.illumination < 1.0);
+        assert!((conditions.altitude.unwrap() - 63.0).abs() < 3.0);
+        assert!(conditions.separation.unwrap() < 1.0);
+    }
+}
@@ -0,0 +1,70 @@
+use crate::models::imaging_frames::light_frame::LightFrame;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+// a directory below the system temp dir, removed again when the test ends
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new() -> TempDir {
+        let path = std::env::temp_dir().join(format!("astrolog-{}", Uuid::new_v4()));
+        fs::create_dir_all(&path).unwrap();
+
+        TempDir { path }
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.path.join(path)
+    }
+
+    // writes a file below the directory, missing folders are created
+    pub fn write<C: AsRef<[u8]>>(&self, name: &str, content: C) -> PathBuf {
+        let path = self.path.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, content).unwrap();
+
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.path).ok();
+    }
+}
+
+// a session of M 31 on the evening of 2024-03-09 with fresh equipment and location ids
+pub fn light_frame(frames_classified: &[&str]) -> LightFrame {
+    serde_json::from_value(serde_json::json!({
+        "id": Uuid::new_v4(),
+        "frames_to_classify": [],
+        "frames_classified": frames_classified,
+        "date": "2024-03-09T21:00:00Z",
+        "target": "M 31",
+        "location_id": Uuid::new_v4(),
+        "gain": 100,
+        "offset": null,
+        "camera_temp": -10.0,
+        "notes": null,
+        "sub_length": 300.0,
+        "camera_id": Uuid::new_v4(),
+        "telescope_id": Uuid::new_v4(),
+        "mount_id": Uuid::new_v4(),
+        "flattener_id": null,
+        "filter_id": null,
+        "outside_temp": null,
+        "average_seeing": null,
+        "average_cloud_cover": null,
+        "average_moon": 0.0,
+    }))
+    .unwrap()
+}
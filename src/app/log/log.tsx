@@ -6,7 +6,7 @@ import { AstrophotographyLog } from '@/components/astrophotographyLog/astrophoto
 import { Button } from '@/components/ui/button';
 import { useEffect, useState } from 'react';
 import NewImagingSession, { newImagingSession } from '@/components/modals/imagingSession/newImagingSession';
-import { save } from '@tauri-apps/plugin-dialog';
+import { open, save } from '@tauri-apps/plugin-dialog';
 import { toast } from '@/components/ui/use-toast';
 import { invoke } from '@tauri-apps/api/core';
 import {
@@ -18,9 +18,10 @@ import { useModal } from '@/context/modalProvider';
 import SelectImagingFrames from '@/components/modals/selectImagingFrames';
 import ImagePreview, { ImagePreviewUndefined } from '@/components/images/imagePreview';
 import HeaderCard from '@/components/headerCard';
-import { Download, Plus } from 'lucide-react';
+import { Download, Plus, Upload } from 'lucide-react';
 import { useAppState } from '@/context/stateProvider';
 import { newCalibrationFrameSession } from '@/components/modals/calibrationRowEditor';
+import ImportCsv from '@/components/modals/importCsv';
 
 export default function Log() {
   const { openModal } = useModal();
@@ -29,32 +30,61 @@ export default function Log() {
   const [images, setImages] = useState<string[] | undefined>(undefined);
 
   function exportCSV() {
-    toast({
-      variant: 'destructive',
-      title: 'Feature not implemented!',
-      description: 'This feature will be available soon.'
+    save({
+      defaultPath: 'astrolog.csv',
+      filters: [
+        {
+          name: '.csv',
+          extensions: ['csv'],
+        },
+      ],
     })
-    // save({
-    //   defaultPath: '', // TODO: define default path
-    //   filters: [
-    //     {
-    //       name: '.csv',
-    //       extensions: ['csv'],
-    //     },
-    //   ],
-    // })
-    //   .then((selectedPath) => {
-    //     if (selectedPath) {
-    //       console.log(selectedPath);
-    //       invoke('export_csv', { path: selectedPath });
-    //     }
-    //   })
-    //   .catch((err) =>
-    //     toast({
-    //       variant: 'destructive',
-    //       description: 'Failed to export CSV: ' + err,
-    //     }),
-    //   );
+      .then((selectedPath) => {
+        if (selectedPath) {
+          invoke('export_csv', { path: selectedPath })
+            .then(() =>
+              toast({
+                description: 'Exported imaging log to ' + selectedPath,
+              }),
+            )
+            .catch((err) =>
+              toast({
+                variant: 'destructive',
+                description: 'Failed to export CSV: ' + err,
+              }),
+            );
+        }
+      })
+      .catch((err) =>
+        toast({
+          variant: 'destructive',
+          description: 'Failed to export CSV: ' + err,
+        }),
+      );
+  }
+
+  function importCSV() {
+    open({
+      multiple: false,
+      directory: false,
+      filters: [
+        {
+          name: '.csv',
+          extensions: ['csv'],
+        },
+      ],
+    })
+      .then((selectedPath) => {
+        if (selectedPath) {
+          openModal(<ImportCsv path={selectedPath} />);
+        }
+      })
+      .catch((err) =>
+        toast({
+          variant: 'destructive',
+          description: 'Failed to import CSV: ' + err,
+        }),
+      );
   }
 
   return (
@@ -87,6 +117,10 @@ export default function Log() {
           <Plus className="h-4 w-4 mr-2" />
           Add Calibration
         </Button>
+        <Button variant="outline" size="sm" onClick={importCSV}>
+          <Upload className="h-4 w-4 mr-2" />
+          Import CSV
+        </Button>
         <Button variant="outline" size="sm" onClick={exportCSV}>
           <Download className="h-4 w-4 mr-2" />
           Export CSV
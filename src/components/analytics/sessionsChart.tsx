@@ -33,6 +33,7 @@ export default function SessionsChart({ className, data = [] }: SessionsChartPro
     const datesWithData = data.map((item) => ({
       date: item.date instanceof Date ? item.date : new Date(item.date),
       seconds: item.seconds,
+      dark_seconds: item.dark_seconds,
     }))
 
     // Find min and max dates
@@ -53,15 +54,18 @@ export default function SessionsChart({ className, data = [] }: SessionsChartPro
     }
 
     // Map data to the complete date range
-    const dataMap = new Map(datesWithData.map((item) => [item.date.toISOString().split("T")[0], item.seconds]))
+    const dataMap = new Map(datesWithData.map((item) => [item.date.toISOString().split("T")[0], item]))
 
     // Create final dataset with all dates
     return allDates.map((item) => {
       const dateKey = item.date.toISOString().split("T")[0]
+      const seconds = dataMap.get(dateKey)?.seconds || 0
       return {
         date: dateKey,
-        seconds: dataMap.get(dateKey) || 0,
-        minutes: Math.round((dataMap.get(dateKey) || 0) / 60),
+        seconds,
+        minutes: Math.round(seconds / 60),
+        // nights without a session have no location to compute the darkness for
+        dark_seconds: dataMap.get(dateKey)?.dark_seconds ?? 0,
       }
     })
   }
@@ -75,6 +79,10 @@ export default function SessionsChart({ className, data = [] }: SessionsChartPro
       label: "Session Duration",
       color: "hsl(var(--chart-1))",
     },
+    dark_seconds: {
+      label: "Astronomical Darkness",
+      color: "hsl(var(--chart-2))",
+    },
   }
 
   // Format seconds to a readable duration
@@ -92,7 +100,7 @@ export default function SessionsChart({ className, data = [] }: SessionsChartPro
     <Card className={cn("p-4", className)}>
       <div className="mb-4">
         <h3 className="text-lg font-medium">Session Duration</h3>
-        <p className="text-sm text-muted-foreground">Time spent in sessions by date</p>
+        <p className="text-sm text-muted-foreground">Time spent in sessions by night, next to the darkness the night had</p>
       </div>
 
       <ChartContainer config={chartConfig} className="aspect-auto h-[300px] w-full">
@@ -115,6 +123,7 @@ export default function SessionsChart({ className, data = [] }: SessionsChartPro
             tickFormatter={(value) => `${Math.round(value / 60)}m`}
           />
           <Bar dataKey="seconds" fill="var(--color-seconds)" radius={[4, 4, 0, 0]} name="Duration" />
+          <Bar dataKey="dark_seconds" fill="var(--color-dark_seconds)" radius={[4, 4, 0, 0]} name="Darkness" />
           <ChartTooltip
             content={
               <ChartTooltipContent
@@ -128,7 +137,7 @@ export default function SessionsChart({ className, data = [] }: SessionsChartPro
                   })
                 }}
                 formatter={(value, name) => {
-                  if (name === "seconds") {
+                  if (name === "seconds" || name === "dark_seconds") {
                     return formatDuration(Number(value))
                   }
                   return value
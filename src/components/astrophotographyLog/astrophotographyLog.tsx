@@ -220,7 +220,7 @@ export function AstrophotographyLog({ setImages }: SessionTableProps) {
                 <p className="flex items-center gap-2 text-foreground">
                   <Cloud className="h-4 w-4 text-muted-foreground" />
                   <span className="font-medium">Conditions:</span>
-                  {'Seeing: ' + session?.average_seeing + ', Clouds: ' + session?.average_cloud_cover + ', Moon: ' + session?.average_moon + '%'}
+                  {'Seeing: ' + session?.average_seeing + ', Clouds: ' + session?.average_cloud_cover + ', Moon: ' + session?.average_moon.toFixed(0) + '%'}
                 </p>
               </div>
               <div>
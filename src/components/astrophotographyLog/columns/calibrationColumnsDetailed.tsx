@@ -54,9 +54,25 @@ export const calibrationColumnsSimple: ColumnDef<CalibrationFrame>[] = [
     ),
   },
   {
-    accessorKey: 'created_at',
+    accessorKey: 'capture_date',
     header: ({ column }) => (
-      <SortableHeader column={column} title="Created" />
+      <SortableHeader column={column} title="Captured" />
     ),
+    cell: ({ row }) => {
+      const rawValue = row.original.capture_date;
+      if (!rawValue) {
+        return <div>N/A</div>;
+      }
+      const date = new Date(rawValue);
+      const day = String(date.getDate()).padStart(2, '0');
+      const month = String(date.getMonth() + 1).padStart(2, '0');
+      const year = date.getFullYear();
+      return (
+        <div className={row.original.expired ? 'text-destructive' : undefined}>
+          {`${day}.${month}.${year}`}
+          {row.original.expired && ' (expired)'}
+        </div>
+      );
+    },
   },
 ];
@@ -4,9 +4,14 @@ import { ColumnDef } from '@tanstack/react-table';
 import { ImagingSession } from '@/interfaces/state';
 import { TempCell } from '@/components/ui/custom/units';
 import { SortableHeader, UnitsHeader } from '@/components/ui/custom/tableHeader';
+import { CalibrationWarnings } from '@/components/astrophotographyLog/columns/calibrationWarnings';
 
 const TEMP_UNITS = { metric: "°C", imperial: "°F" } as const;
 
+function DegreesCell({ value }: { value: number | undefined }) {
+  return <div>{value == null ? 'N/A' : `${value.toFixed(1)}°`}</div>;
+}
+
 export const sessionsColumnsDetailed: ColumnDef<ImagingSession>[] = [
   {
     accessorKey: 'date',
@@ -28,6 +33,13 @@ export const sessionsColumnsDetailed: ColumnDef<ImagingSession>[] = [
       <SortableHeader column={column} title="Target" />
     ),
   },
+  {
+    accessorKey: 'catalog_id',
+    header: ({ column }) => (
+      <SortableHeader column={column} title="Catalog" />
+    ),
+    cell: ({ row }) => <div>{row.original.catalog_id ?? 'N/A'}</div>,
+  },
   {
     accessorKey: 'sub_length',
     header: ({ column }) => (
@@ -103,6 +115,21 @@ export const sessionsColumnsDetailed: ColumnDef<ImagingSession>[] = [
     header: ({ column }) => (
       <SortableHeader column={column} title="Average Moon" />
     ),
+    cell: ({ row }) => <div>{`${row.original.average_moon.toFixed(0)}%`}</div>,
+  },
+  {
+    accessorKey: 'moon_altitude',
+    header: ({ column }) => (
+      <SortableHeader column={column} title="Moon Altitude" />
+    ),
+    cell: ({ row }) => <DegreesCell value={row.original.moon_altitude} />,
+  },
+  {
+    accessorKey: 'moon_separation',
+    header: ({ column }) => (
+      <SortableHeader column={column} title="Moon Separation" />
+    ),
+    cell: ({ row }) => <DegreesCell value={row.original.moon_separation} />,
   },
   {
     accessorKey: 'telescope',
@@ -134,4 +161,11 @@ export const sessionsColumnsDetailed: ColumnDef<ImagingSession>[] = [
       <SortableHeader column={column} title="Notes" />
     ),
   },
+  {
+    accessorKey: 'calibration_warnings',
+    header: 'Calibration',
+    cell: ({ row }) => (
+      <CalibrationWarnings warnings={row.original.calibration_warnings} />
+    ),
+  },
 ];
@@ -3,13 +3,14 @@
 import { ColumnDef } from '@tanstack/react-table';
 import { Calendar, Camera, Clock, MapPin, Moon } from 'lucide-react';
 import { ImagingSession } from '@/interfaces/state';
+import { CalibrationWarnings } from '@/components/astrophotographyLog/columns/calibrationWarnings';
 
 export const sessionsColumnsSimple: ColumnDef<ImagingSession>[] = [
   {
     id: "date",
     accessorFn: (row) => new Date(row.date),
     header: "Date",
-    cell: ({ getValue }) => {
+    cell: ({ row, getValue }) => {
       const date = getValue() as Date
       const day = String(date.getDate()).padStart(2, "0")
       const month = String(date.getMonth() + 1).padStart(2, "0")
@@ -19,6 +20,7 @@ export const sessionsColumnsSimple: ColumnDef<ImagingSession>[] = [
         <div className="flex items-center gap-2">
           <Calendar className="h-4 w-4 text-muted-foreground" />
           {formatted}
+          <CalibrationWarnings warnings={row.original.calibration_warnings} />
         </div>
       )
     }
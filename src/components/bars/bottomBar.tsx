@@ -65,6 +65,9 @@ export default function BottomBar() {
       {processes.size != 0 && (
         <div className={styles.right}>
           <div>{currentProcess?.name}...</div>
+          {currentProcess?.transfer && (
+            <div>&nbsp;({currentProcess.transfer.mode.toLowerCase()})</div>
+          )}
           {processes.size > 1 && <div>&nbsp; (+{processes.size - 1} more)</div>}
           {progress != undefined && (
             <>
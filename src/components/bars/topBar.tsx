@@ -19,6 +19,8 @@ import { useModal } from '@/context/modalProvider';
 import NewImagingSession, { newImagingSession } from '@/components/modals/imagingSession/newImagingSession';
 import { newCalibrationFrameSession } from '@/components/modals/calibrationRowEditor';
 import SelectImagingFrames from '@/components/modals/selectImagingFrames';
+import DarkCoverage from '@/components/modals/darkCoverage';
+import VerifyLibrary from '@/components/modals/verifyLibrary';
 import { useAppState } from '@/context/stateProvider';
 import EquipmentModal from '@/components/modals/equipment/equipment';
 import { EquipmentType } from '@/enums/equipmentType';
@@ -87,6 +89,13 @@ export function TopBar() {
             >
               New Calibration Frame...
             </MenubarItem>
+            <MenubarSeparator />
+            <MenubarItem onClick={() => openModal(<DarkCoverage />)}>
+              Dark Library Coverage...
+            </MenubarItem>
+            <MenubarItem onClick={() => openModal(<VerifyLibrary />)}>
+              Verify Library...
+            </MenubarItem>
           </MenubarContent>
         </MenubarMenu>
         <MenubarMenu>
@@ -26,7 +26,7 @@ import { Label } from '@/components/ui/label';
 import { useModal } from '@/context/modalProvider';
 import { EquipmentComboBox } from '@/components/ui/comboBox';
 import { invoke } from '@tauri-apps/api/core';
-import { AnalyzedCalibrationFrames, BiasFrame, DarkFrame } from '@/interfaces/commands';
+import { AnalyzedCalibrationFrames, BiasFrame, DarkFlatFrame, DarkFrame, FlatFrame } from '@/interfaces/commands';
 import { CalibrationType } from '@/enums/calibrationType';
 import { toast } from '@/components/ui/use-toast';
 import { AppState, CalibrationFrame } from '@/interfaces/state';
@@ -61,9 +61,9 @@ export default function CalibrationRowEditor({
       camera: z.string().min(1, {
         message: 'You must at least select one calibration frame.'
       }),
-      calibrationType: z.enum([CalibrationType.DARK, CalibrationType.BIAS], {
+      calibrationType: z.enum([CalibrationType.DARK, CalibrationType.BIAS, CalibrationType.FLAT, CalibrationType.DARKFLAT], {
         errorMap: () => ({
-          message: 'You must select a valid calibration type (DARK or BIAS).'
+          message: 'You must select a valid calibration type (DARK, BIAS, FLAT or DARKFLAT).'
         })
       }),
       gain: z.coerce.number().min(1, {
@@ -90,6 +90,15 @@ export default function CalibrationRowEditor({
           });
         }
       }
+      if (data.calibrationType === CalibrationType.DARKFLAT) {
+        if (data.subLength === undefined || data.subLength <= 0) {
+          ctx.addIssue({
+            code: z.ZodIssueCode.custom,
+            message: 'Sub length must match the exposure of the flat frames',
+            path: ['subLength']
+          });
+        }
+      }
     });
 
   const form = useForm<z.infer<typeof formSchema>>({
@@ -124,6 +133,26 @@ export default function CalibrationRowEditor({
         frames_classified: []
       };
 
+      const flatFrame: FlatFrame = {
+        id: uuidv4() as UUID,
+        camera_id: form.getValues().camera as UUID,
+        gain: Number(form.getValues().gain),
+        frames_to_classify: paths ?? [],
+        frames_classified: [],
+        in_imaging_session: false
+      };
+
+      const darkFlatFrame: DarkFlatFrame = {
+        id: uuidv4() as UUID,
+        camera_id: form.getValues().camera as UUID,
+        gain: Number(form.getValues().gain),
+        frames_to_classify: paths ?? [],
+        frames_classified: [],
+        in_imaging_session: false,
+        camera_temp: Number(form.getValues().cameraTemp) ?? 0,
+        sub_length: Number(form.getValues().subLength) ?? 0
+      };
+
       if (calibrationType == CalibrationType.DARK) {
         invoke('classify_dark_frame', {
           darkFrame: darkFrame
@@ -136,6 +165,30 @@ export default function CalibrationRowEditor({
               description: 'Error: ' + error
             })
           );
+      } else if (calibrationType == CalibrationType.FLAT) {
+        invoke('classify_flat_frame', {
+          flatFrame: flatFrame
+        })
+          .then(() => closeModal())
+          .catch((error) =>
+            toast({
+              variant: 'destructive',
+              title: 'Uh oh! Something went wrong.',
+              description: 'Error: ' + error
+            })
+          );
+      } else if (calibrationType == CalibrationType.DARKFLAT) {
+        invoke('classify_dark_flat_frame', {
+          darkFlatFrame: darkFlatFrame
+        })
+          .then(() => closeModal())
+          .catch((error) =>
+            toast({
+              variant: 'destructive',
+              title: 'Uh oh! Something went wrong.',
+              description: 'Error: ' + error
+            })
+          );
       } else {
         invoke('classify_bias_frame', {
           biasFrame: biasFrame
@@ -200,6 +253,8 @@ export default function CalibrationRowEditor({
                       <SelectContent>
                         <SelectItem value="DARK">DARK</SelectItem>
                         <SelectItem value="BIAS">BIAS</SelectItem>
+                        <SelectItem value="FLAT">FLAT</SelectItem>
+                        <SelectItem value="DARKFLAT">DARKFLAT</SelectItem>
                       </SelectContent>
                     </Select>
                   </FormControl>
@@ -223,7 +278,7 @@ export default function CalibrationRowEditor({
               )}
             />
           </div>
-          {calibrationType === CalibrationType.DARK && (
+          {(calibrationType === CalibrationType.DARK || calibrationType === CalibrationType.DARKFLAT) && (
             <>
               <div className={styles.row}>
                 <Label className={styles.label}>Sub Length</Label>
@@ -0,0 +1,115 @@
+'use client';
+
+import { Modal } from '@/components/ui/custom/modal';
+import { TempCell } from '@/components/ui/custom/units';
+import {
+  Table,
+  TableBody,
+  TableCell,
+  TableHead,
+  TableHeader,
+  TableRow,
+} from '@/components/ui/table';
+import { toast } from '@/components/ui/use-toast';
+import { DarkCoverageReport } from '@/interfaces/commands';
+import { invoke } from '@tauri-apps/api/core';
+import { useEffect, useState } from 'react';
+
+export default function DarkCoverage() {
+  const [report, setReport] = useState<DarkCoverageReport | null>(null);
+
+  useEffect(() => {
+    invoke<DarkCoverageReport>('get_dark_coverage')
+      .then((report) => setReport(report))
+      .catch((error) =>
+        toast({
+          variant: 'destructive',
+          title: 'Uh oh! Something went wrong.',
+          description: 'Error: ' + error,
+        })
+      );
+  }, []);
+
+  return (
+    <Modal
+      title="Dark Library Coverage"
+      subtitle="Dark frames your imaging sessions still need and library dark frames nothing uses."
+      separator={true}
+    >
+      {report && (
+        <div className="space-y-8">
+          <div>
+            <h2 className="text-xl font-semibold mb-4">Missing Dark Frames</h2>
+            {report.cameras.length === 0 ? (
+              <p>Every imaging session has matching dark frames.</p>
+            ) : (
+              <Table>
+                <TableHeader>
+                  <TableRow>
+                    <TableHead>Camera</TableHead>
+                    <TableHead>Gain</TableHead>
+                    <TableHead>Sub Length</TableHead>
+                    <TableHead>Camera Temp.</TableHead>
+                    <TableHead>Sessions</TableHead>
+                  </TableRow>
+                </TableHeader>
+                <TableBody>
+                  {report.cameras.flatMap((camera) =>
+                    camera.missing.map((missing) => (
+                      <TableRow key={`${camera.camera_id}-${missing.gain}-${missing.sub_length}-${missing.camera_temp}`}>
+                        <TableCell>{camera.camera}</TableCell>
+                        <TableCell>{missing.gain}</TableCell>
+                        <TableCell>{missing.sub_length}s</TableCell>
+                        <TableCell>
+                          <TempCell
+                            celsius={missing.camera_temp ?? undefined}
+                            columnUnits={{ metric: '°C', imperial: '°F' }}
+                          />
+                        </TableCell>
+                        <TableCell>{missing.sessions.length}</TableCell>
+                      </TableRow>
+                    ))
+                  )}
+                </TableBody>
+              </Table>
+            )}
+          </div>
+          <div>
+            <h2 className="text-xl font-semibold mb-4">Unused Dark Frames</h2>
+            {report.unused.length === 0 ? (
+              <p>Every library dark frame is used.</p>
+            ) : (
+              <Table>
+                <TableHeader>
+                  <TableRow>
+                    <TableHead>Camera</TableHead>
+                    <TableHead>Gain</TableHead>
+                    <TableHead>Sub Length</TableHead>
+                    <TableHead>Camera Temp.</TableHead>
+                    <TableHead>Total Subs</TableHead>
+                  </TableRow>
+                </TableHeader>
+                <TableBody>
+                  {report.unused.map((dark) => (
+                    <TableRow key={dark.id}>
+                      <TableCell>{dark.camera}</TableCell>
+                      <TableCell>{dark.gain}</TableCell>
+                      <TableCell>{dark.sub_length}s</TableCell>
+                      <TableCell>
+                        <TempCell
+                          celsius={dark.camera_temp}
+                          columnUnits={{ metric: '°C', imperial: '°F' }}
+                        />
+                      </TableCell>
+                      <TableCell>{dark.total_subs}</TableCell>
+                    </TableRow>
+                  ))}
+                </TableBody>
+              </Table>
+            )}
+          </div>
+        </div>
+      )}
+    </Modal>
+  );
+}
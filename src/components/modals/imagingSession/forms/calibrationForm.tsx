@@ -12,9 +12,12 @@ import { ButtonBar } from '@/components/ui/custom/modal';
 import { ImagingSessionCalibration } from '@/interfaces/imagingSessionEdit';
 import { ImagingSessionCalibrationSchema } from '@/schemas/imagingSessionSchema';
 import { Button } from '@/components/ui/button';
-import { BiasFrameComboBox, DarkFrameComboBox } from '@/components/ui/comboBox';
+import { BiasFrameComboBox, DarkFlatFrameComboBox, DarkFrameComboBox, FlatFrameComboBox } from '@/components/ui/comboBox';
 import { UUID } from 'crypto';
 import FileListSelector from '@/components/fileSelectors/fileListSelector';
+import { invoke } from '@tauri-apps/api/core';
+import { toast } from '@/components/ui/use-toast';
+import { AnalyzedCalibrationFrames } from '@/interfaces/commands';
 
 interface CalibrationFormProps {
   prevTab: () => void,
@@ -36,9 +39,31 @@ export default function CalibrationForm({ prevTab, isEdit, isDslr, calibration,
       flat_frames_to_classify: calibration?.flat_frames_to_classify ?? [],
       dark_frame_list_id: calibration?.dark_frame_list_id,
       bias_frame_list_id: calibration?.bias_frame_list_id,
+      flat_frame_list_id: calibration?.flat_frame_list_id,
+      dark_flat_frames_to_classify: calibration?.dark_flat_frames_to_classify ?? [],
+      dark_flat_frame_list_id: calibration?.dark_flat_frame_list_id,
     }
   });
 
+  // the dark flat values are read from their headers, values the headers lack stay empty
+  function warnAboutDarkFlatFrames(frames: string[]) {
+    if (frames.length === 0) {
+      return;
+    }
+
+    invoke<AnalyzedCalibrationFrames>('analyze_calibration_frames', { frames: frames })
+      .then((result) => {
+        if (result.message !== null) {
+          toast({
+            variant: 'destructive',
+            title: 'Warning',
+            description: 'Dark flat frames: ' + result.message,
+          });
+        }
+      })
+      .catch(() => {});
+  }
+
   function onSubmit() {
     if (isEdit) {
       // TODO
@@ -50,12 +75,16 @@ export default function CalibrationForm({ prevTab, isEdit, isDslr, calibration,
     const newCalibration: ImagingSessionCalibration = {
       dark_frame_list_id: values.dark_frame_list_id as UUID | undefined,
       bias_frame_list_id: values.bias_frame_list_id as UUID | undefined,
+      flat_frame_list_id: values.flat_frame_list_id as UUID | undefined,
       flat_frames_to_classify: values.flat_frames_to_classify,
-      dark_frames_to_classify: values.dark_frames_to_classify
+      dark_frames_to_classify: values.dark_frames_to_classify,
+      dark_flat_frame_list_id: values.dark_flat_frame_list_id as UUID | undefined,
+      dark_flat_frames_to_classify: values.dark_flat_frames_to_classify
     }
 
     // special since its last
     if (submitAction.current === "next") {
+      warnAboutDarkFlatFrames(newCalibration.dark_flat_frames_to_classify);
       classifySession(newCalibration);
     } else if (submitAction.current === "prev") {
       setCalibration(newCalibration);
@@ -81,6 +110,48 @@ export default function CalibrationForm({ prevTab, isEdit, isDslr, calibration,
           )}
         />
 
+        <FormField
+          control={form.control}
+          name="flat_frame_list_id"
+          render={({ field }) => (
+            <FormItem className="flex flex-col">
+              <FormLabel>Flat Frames from Library</FormLabel>
+              <FormControl>
+                <FlatFrameComboBox value={field.value as UUID | undefined} onChange={field.onChange} />
+              </FormControl>
+              <FormMessage />
+            </FormItem>
+          )}
+        />
+
+        <FormField
+          control={form.control}
+          name="dark_flat_frames_to_classify"
+          render={({ field }) => (
+            <FormItem className="flex flex-col">
+              <FormLabel>Dark Flat Frames</FormLabel>
+              <FormControl>
+                <FileListSelector {...field} />
+              </FormControl>
+              <FormMessage />
+            </FormItem>
+          )}
+        />
+
+        <FormField
+          control={form.control}
+          name="dark_flat_frame_list_id"
+          render={({ field }) => (
+            <FormItem className="flex flex-col">
+              <FormLabel>Dark Flat Frames from Library</FormLabel>
+              <FormControl>
+                <DarkFlatFrameComboBox value={field.value as UUID | undefined} onChange={field.onChange} />
+              </FormControl>
+              <FormMessage />
+            </FormItem>
+          )}
+        />
+
         {isDslr ? (
           <FormField
             control={form.control}
@@ -10,7 +10,7 @@ import {
   FormLabel,
   FormMessage
 } from '@/components/ui/form';
-import React, { Dispatch, SetStateAction, useEffect } from 'react';
+import React, { Dispatch, SetStateAction, useEffect, useState } from 'react';
 import { Input } from '@/components/ui/input';
 import { z } from 'zod';
 import { useForm } from 'react-hook-form';
@@ -31,6 +31,7 @@ import { ImagingSessionGeneral } from '@/interfaces/imagingSessionEdit';
 import { ButtonBar } from '@/components/ui/custom/modal';
 import { LocationComboBox } from '@/components/ui/comboBox';
 import { UUID } from 'crypto';
+import { CatalogEntry } from '@/interfaces/commands';
 
 interface GeneralFormFormProps {
   nextTab: () => void,
@@ -49,6 +50,14 @@ export default function GeneralForm({ nextTab, isEdit, general, setGeneral, edit
       location: general?.location_id
     }
   });
+  const [catalogEntry, setCatalogEntry] = useState<CatalogEntry | null>(null);
+  const target = form.watch('target');
+
+  useEffect(() => {
+    invoke<CatalogEntry | null>('resolve_target', { name: target })
+      .then(setCatalogEntry)
+      .catch(() => setCatalogEntry(null));
+  }, [target]);
 
   function onSubmit() {
     if (isEdit) {
@@ -124,7 +133,15 @@ export default function GeneralForm({ nextTab, isEdit, general, setGeneral, edit
                 <FormControl>
                   <Input {...field} placeholder="NGC 9999" />
                 </FormControl>
-                <FormDescription>The name of the target (e.g. NGC 7000).</FormDescription>
+                <FormDescription>
+                  {catalogEntry
+                    ? `${catalogEntry.names.join(', ')} (${catalogEntry.object_type})`
+                    : target.trim() === ''
+                      ? 'The name of the target (e.g. NGC 7000).'
+                      : 'Not in the catalog. AstroLog only ships all Messier objects and a selection of popular ' +
+                        'NGC, IC, Caldwell and Sharpless targets. The full NGC and IC catalogs are read from the ' +
+                        'OpenNGC NGC.csv in .astrolog, further targets can be added in .astrolog/catalog.csv.'}
+                </FormDescription>
                 <FormMessage />
               </FormItem>
             )}
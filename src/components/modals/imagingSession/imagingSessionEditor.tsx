@@ -36,8 +36,11 @@ const defaultWeather: ImagingSessionWeather = {
 const defaultCalibration: ImagingSessionCalibration = {
   dark_frame_list_id: undefined,
   bias_frame_list_id: undefined,
+  flat_frame_list_id: undefined,
   flat_frames_to_classify: [],
-  dark_frames_to_classify: []
+  dark_frames_to_classify: [],
+  dark_flat_frame_list_id: undefined,
+  dark_flat_frames_to_classify: []
 };
 
 interface ImagingSessionEditorProps {
@@ -138,6 +141,12 @@ export default function ImagingSessionEditor({ session, base }: ImagingSessionEd
         }));
         fetchAppState(setAppState);
         closeModal();
+        if (data.calibration_warnings.length > 0) {
+          toast({
+            title: "The calibration frames don't match the session.",
+            description: data.calibration_warnings.join('\n'),
+          });
+        }
       })
       .catch((error) => {
         toast({
@@ -2,7 +2,7 @@
 
 import styles from './newImagingSession.module.scss';
 import { Modal } from '@/components/ui/custom/modal';
-import React from 'react';
+import React, { useEffect, useState } from 'react';
 import { z } from 'zod';
 import { useForm } from 'react-hook-form';
 import { zodResolver } from '@hookform/resolvers/zod';
@@ -21,6 +21,8 @@ import { ToastAction } from '@/components/ui/toast';
 import { Preferences } from '@/components/modals/preferences/preferences';
 import EquipmentModal from '@/components/modals/equipment/equipment';
 import { EquipmentType } from '@/enums/equipmentType';
+import { invoke } from '@tauri-apps/api/core';
+import { AnalyzedLightFrames, DuplicateFrame } from '@/interfaces/commands';
 
 export default function NewImagingSession() {
   const { openModal } = useModal();
@@ -32,15 +34,46 @@ export default function NewImagingSession() {
     }
   });
 
-  function onSubmit() {
+  const [duplicates, setDuplicates] = useState<DuplicateFrame[]>([]);
+  const selectedFrames = form.watch('frames');
+
+  useEffect(() => {
+    setDuplicates([]);
+  }, [selectedFrames]);
+
+  function openEditor(frames: string[]) {
     const base: ImagingSessionBase = {
       id: uuidv4() as UUID,
-      frames: form.getValues().frames
+      frames: frames
     };
 
     openModal(<ImagingSessionEditor base={base} />);
   }
 
+  function onSubmit() {
+    const frames = form.getValues().frames;
+    if (duplicates.length > 0) {
+      openEditor(frames);
+      return;
+    }
+
+    // frames that are already in the library are listed before anything is classified
+    invoke<AnalyzedLightFrames>('analyze_light_frames', { frames: frames })
+      .then((result) => {
+        if (result.duplicates.length === 0) {
+          openEditor(frames);
+          return;
+        }
+        setDuplicates(result.duplicates);
+      })
+      .catch(() => openEditor(frames));
+  }
+
+  function skipDuplicates() {
+    const paths = new Set(duplicates.map((duplicate) => duplicate.path));
+    openEditor(form.getValues().frames.filter((frame) => !paths.has(frame)));
+  }
+
   return (
     <Modal
       title="Add Imaging Session"
@@ -65,6 +98,22 @@ export default function NewImagingSession() {
               </FormItem>
             )}
           />
+          {duplicates.length > 0 && (
+            <div className={styles.duplicates}>
+              <p>{duplicates.length} of the selected frames are already in the library:</p>
+              <ul>
+                {duplicates.map((duplicate) => (
+                  <li key={duplicate.path}>
+                    {duplicate.path} ({duplicate.owner}
+                    {duplicate.matched_by === 'NAMEANDDATE' && ', same name and capture time'})
+                  </li>
+                ))}
+              </ul>
+              <Button type="button" variant="secondary" onClick={skipDuplicates}>
+                Skip Duplicates
+              </Button>
+            </div>
+          )}
           <Button className={styles.nextButton} type="submit">
             Next
           </Button>
@@ -86,7 +135,7 @@ export function newImagingSession({ open, appState, openModal }: { open: () => v
     return;
   }
 
-  if (appState.config.locations.size === 0) {
+  if (appState.locations.size === 0) {
     toast({
       variant: 'destructive',
       title: 'Uh oh! Something went wrong.',
@@ -0,0 +1,170 @@
+'use client';
+
+import { Modal } from '@/components/ui/custom/modal';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import { Switch } from '@/components/ui/switch';
+import { toast } from '@/components/ui/use-toast';
+import { useModal } from '@/context/modalProvider';
+import { fetchAppState, useAppState } from '@/context/stateProvider';
+import { CsvImportPreview } from '@/interfaces/commands';
+import { invoke } from '@tauri-apps/api/core';
+import { useEffect, useState } from 'react';
+
+interface ImportCsvProps {
+  path: string;
+}
+
+export default function ImportCsv({ path }: ImportCsvProps) {
+  const { setAppState } = useAppState();
+  const { closeModal } = useModal();
+
+  const [delimiter, setDelimiter] = useState<string>(',');
+  const [dateFormat, setDateFormat] = useState<string>('%Y-%m-%d');
+  const [createMissing, setCreateMissing] = useState<boolean>(false);
+  const [preview, setPreview] = useState<CsvImportPreview | null>(null);
+  const [importing, setImporting] = useState<boolean>(false);
+
+  const options = () => ({
+    delimiter: delimiter,
+    date_format: dateFormat,
+    create_missing: createMissing,
+  });
+
+  // every change of the options runs a new dry run, nothing is written before the import
+  useEffect(() => {
+    if (delimiter.length !== 1 || dateFormat.trim() === '') {
+      return;
+    }
+
+    invoke<CsvImportPreview>('import_csv', { path: path, options: options(), dryRun: true })
+      .then((preview) => setPreview(preview))
+      .catch((error) => {
+        setPreview(null);
+        toast({
+          variant: 'destructive',
+          title: 'Uh oh! Something went wrong.',
+          description: 'Error: ' + error,
+        });
+      });
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [path, delimiter, dateFormat, createMissing]);
+
+  function onImport() {
+    setImporting(true);
+
+    invoke<CsvImportPreview>('import_csv', { path: path, options: options(), dryRun: false })
+      .then((result) => {
+        fetchAppState(setAppState);
+        toast({
+          title: 'Success',
+          description: `Imported ${result.imported} imaging session(s).`,
+        });
+        closeModal();
+      })
+      .catch((error) => {
+        setImporting(false);
+        toast({
+          variant: 'destructive',
+          title: 'Uh oh! Something went wrong.',
+          description: 'Nothing was imported. Error: ' + error,
+        });
+      });
+  }
+
+  return (
+    <Modal
+      title="Import CSV"
+      subtitle="Imports an observing log as imaging sessions without frames. Review the preview before importing."
+      separator={true}
+    >
+      <div className="space-y-8">
+        <div className="grid grid-cols-2 gap-4">
+          <div className="space-y-2">
+            <Label htmlFor="delimiter">Delimiter</Label>
+            <Input
+              id="delimiter"
+              maxLength={1}
+              value={delimiter}
+              onChange={(e) => setDelimiter(e.target.value)}
+            />
+          </div>
+          <div className="space-y-2">
+            <Label htmlFor="dateFormat">Date Format</Label>
+            <Input
+              id="dateFormat"
+              value={dateFormat}
+              onChange={(e) => setDateFormat(e.target.value)}
+            />
+          </div>
+        </div>
+        <div className="flex flex-row items-center justify-between rounded-lg border p-4">
+          <div className="space-y-0.5">
+            <Label className="text-base">Create Missing Entries</Label>
+            <p className="text-sm text-muted-foreground">
+              Equipment and locations the log names but AstroLog doesn&apos;t know are created.
+              Otherwise their rows are skipped.
+            </p>
+          </div>
+          <Switch checked={createMissing} onCheckedChange={setCreateMissing} />
+        </div>
+        {preview === null ? (
+          <p>Reading the log...</p>
+        ) : (
+          <>
+            <p>
+              {preview.sessions.length} of {preview.total_rows} rows can be imported.
+            </p>
+            {preview.missing.length > 0 && (
+              <div>
+                <h2 className="text-xl font-semibold mb-4">
+                  {createMissing ? 'Entries to Create' : 'Unknown Entries'}
+                </h2>
+                <ul className="list-disc pl-6">
+                  {preview.missing.map((entry) => (
+                    <li key={entry.id}>{entry.column.toLowerCase()}: {entry.name}</li>
+                  ))}
+                </ul>
+                {createMissing && preview.missing.some((entry) => entry.column === 'LOCATION') && (
+                  <p className="mt-2">
+                    New locations have no coordinates or timezone yet, their night and moon altitude
+                    are computed once they are reviewed in the preferences.
+                  </p>
+                )}
+              </div>
+            )}
+            {preview.errors.length > 0 && (
+              <div>
+                <h2 className="text-xl font-semibold mb-4">Skipped Rows</h2>
+                <ul className="list-disc pl-6">
+                  {preview.errors.map((error) => (
+                    <li key={error.line}>Line {error.line}: {error.reason}</li>
+                  ))}
+                </ul>
+              </div>
+            )}
+            {preview.sessions.length > 0 && (
+              <div>
+                <h2 className="text-xl font-semibold mb-4">Imaging Sessions</h2>
+                <ul className="list-disc pl-6">
+                  {preview.sessions.map((session) => (
+                    <li key={session.line}>
+                      {new Date(session.date).toLocaleDateString()} {session.target}, {session.total_subs} subs
+                    </li>
+                  ))}
+                </ul>
+              </div>
+            )}
+            <Button
+              onClick={onImport}
+              disabled={importing || preview.sessions.length === 0}
+            >
+              Import {preview.sessions.length} Session(s)
+            </Button>
+          </>
+        )}
+      </div>
+    </Modal>
+  );
+}
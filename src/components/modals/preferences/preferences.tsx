@@ -5,6 +5,8 @@ import styles from './preferences.module.scss';
 import AppearanceForm from './tabs/appearanceForm';
 import SourceForm from './tabs/sourceForm';
 import LocationsForm from './tabs/locationsForm';
+import CalibrationMatchingForm from './tabs/calibrationMatchingForm';
+import FileNamesForm from './tabs/fileNamesForm';
 import BackupForm from '@/components/modals/preferences/tabs/backupForm';
 import { FolderPathBuilder, FolderPathBuilderType } from '@/components/modals/preferences/tabs/folderPathBuilder';
 import { ReactNode } from 'react';
@@ -20,6 +22,8 @@ export function Preferences({ defaultValue = "appearance" }: { defaultValue?: st
           {/*<TabsTrigger value="backup">Backup</TabsTrigger>*/}
           <TabsTrigger value="imaging_sessions">Imaging Sessions</TabsTrigger>
           <TabsTrigger value="calibration_frames">Calibration Frames</TabsTrigger>
+          <TabsTrigger value="file_names">File Names</TabsTrigger>
+          <TabsTrigger value="calibration_matching">Calibration Matching</TabsTrigger>
           <TabsTrigger value="location">Locations</TabsTrigger>
         </TabsList>
         <TabsContent value="appearance" className={styles.tabsContent}>
@@ -66,6 +70,22 @@ export function Preferences({ defaultValue = "appearance" }: { defaultValue?: st
             />
           </Content>
         </TabsContent>
+        <TabsContent value="file_names" className={styles.tabsContent}>
+          <Content
+            title="File Names"
+            subtitle="Define how classified frames are named. Leave a pattern empty to keep the original file names."
+          >
+            <FileNamesForm />
+          </Content>
+        </TabsContent>
+        <TabsContent value="calibration_matching" className={styles.tabsContent}>
+          <Content
+            title="Calibration Matching"
+            subtitle="Define how far library calibration frames may differ from an imaging session and still be suggested for it."
+          >
+            <CalibrationMatchingForm />
+          </Content>
+        </TabsContent>
         <TabsContent value="location" className={styles.tabsContent}>
           <Content
             title="Location"
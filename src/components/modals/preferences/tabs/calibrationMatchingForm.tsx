@@ -0,0 +1,152 @@
+'use client';
+
+import styles from '../preferences.module.scss';
+import {
+  Form,
+  FormControl,
+  FormDescription,
+  FormField,
+  FormItem,
+  FormLabel,
+  FormMessage,
+} from '@/components/ui/form';
+import { Input } from '@/components/ui/input';
+import { Switch } from '@/components/ui/switch';
+import { Button } from '@/components/ui/button';
+import { toast } from '@/components/ui/use-toast';
+import { useAppState } from '@/context/stateProvider';
+import { zodResolver } from '@hookform/resolvers/zod';
+import { useForm } from 'react-hook-form';
+import { z } from 'zod';
+import { invoke } from '@tauri-apps/api/core';
+import { CalibrationTolerances, CalibrationValidity } from '@/interfaces/state';
+
+// an empty validity field means calibration sets never expire
+const validDays = z.preprocess(
+  (value) => (value === '' || value === null || value === undefined ? null : Number(value)),
+  z.number().int().min(0).nullable()
+);
+
+const formSchema = z.object({
+  gain: z.coerce.number().int().min(0),
+  offset: z.coerce.number().int().min(0),
+  sub_length_percent: z.coerce.number().min(0),
+  camera_temp: z.coerce.number().min(0),
+  dark_days: validDays,
+  bias_days: validDays,
+  flat_days: validDays,
+  dark_flat_days: validDays,
+  flat_same_train: z.boolean(),
+});
+
+type FormValues = z.infer<typeof formSchema>;
+
+type NumberField = Exclude<keyof FormValues, 'flat_same_train'>;
+
+const FIELDS: { name: NumberField, label: string, description: string }[] = [
+  { name: 'gain', label: 'Gain', description: 'Allowed difference of the gain.' },
+  { name: 'offset', label: 'Offset', description: 'Allowed difference of the offset.' },
+  { name: 'sub_length_percent', label: 'Sub Length (%)', description: 'Allowed difference of the dark frame sub length in percent.' },
+  { name: 'camera_temp', label: 'Camera Temp. (°C)', description: 'Allowed difference of the dark frame camera temperature.' },
+  { name: 'dark_days', label: 'Dark Frames Valid (days)', description: 'Days dark frames stay valid after they were taken.' },
+  { name: 'bias_days', label: 'Bias Frames Valid (days)', description: 'Days bias frames stay valid after they were taken.' },
+  { name: 'flat_days', label: 'Flat Frames Valid (days)', description: 'Days flat frames stay valid after they were taken, 0 for the same night.' },
+  { name: 'dark_flat_days', label: 'Dark Flat Frames Valid (days)', description: 'Days dark flat frames stay valid after they were taken.' },
+];
+
+export default function CalibrationMatchingForm() {
+  const { appState, setAppState } = useAppState();
+
+  const form = useForm<FormValues>({
+    resolver: zodResolver(formSchema),
+    defaultValues: {
+      ...appState.config.calibration_tolerances,
+      ...appState.config.calibration_validity,
+    },
+  });
+
+  function onSubmit(values: FormValues) {
+    const tolerances: CalibrationTolerances = {
+      gain: values.gain,
+      offset: values.offset,
+      sub_length_percent: values.sub_length_percent,
+      camera_temp: values.camera_temp,
+    };
+    const validity: CalibrationValidity = {
+      dark_days: values.dark_days,
+      bias_days: values.bias_days,
+      flat_days: values.flat_days,
+      dark_flat_days: values.dark_flat_days,
+      flat_same_train: values.flat_same_train,
+    };
+
+    invoke('save_calibration_tolerances', { tolerances: tolerances })
+      .then(() => invoke('save_calibration_validity', { validity: validity }))
+      .then(() => {
+        setAppState((prevState) => ({
+          ...prevState,
+          config: {
+            ...prevState.config,
+            calibration_tolerances: tolerances,
+            calibration_validity: validity,
+          },
+        }));
+        toast({
+          title: 'Success',
+          description: 'Your calibration settings have been saved.',
+        });
+      })
+      .catch((error) => {
+        toast({
+          variant: 'destructive',
+          title: 'Uh oh! Something went wrong.',
+          description: 'Error: ' + error,
+        });
+      });
+  }
+
+  return (
+    <Form {...form}>
+      <form onSubmit={form.handleSubmit(onSubmit)} className={styles.form}>
+        {FIELDS.map(({ name, label, description }) => (
+          <FormField
+            key={name}
+            control={form.control}
+            name={name}
+            render={({ field }) => (
+              <FormItem>
+                <FormLabel>{label}</FormLabel>
+                <FormControl>
+                  <Input type="number" step="any" {...field} value={field.value ?? ''} />
+                </FormControl>
+                <FormDescription>{description}</FormDescription>
+                <FormMessage />
+              </FormItem>
+            )}
+          />
+        ))}
+        <FormField
+          control={form.control}
+          name="flat_same_train"
+          render={({ field }) => (
+            <FormItem className="flex flex-row items-center justify-between rounded-lg border p-4">
+              <div className="space-y-0.5">
+                <FormLabel className="text-base">Keep Flats per Train</FormLabel>
+                <FormDescription>
+                  Flat frames taken with the same telescope and filter don&apos;t expire.
+                </FormDescription>
+              </div>
+              <FormControl>
+                <Switch
+                  checked={field.value}
+                  onCheckedChange={field.onChange}
+                />
+              </FormControl>
+            </FormItem>
+          )}
+        />
+        <Button type="submit">Save</Button>
+      </form>
+    </Form>
+  );
+}
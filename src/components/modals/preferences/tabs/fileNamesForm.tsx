@@ -0,0 +1,114 @@
+'use client';
+
+import styles from '../preferences.module.scss';
+import {
+  Form,
+  FormControl,
+  FormField,
+  FormItem,
+  FormLabel,
+  FormMessage,
+} from '@/components/ui/form';
+import { Input } from '@/components/ui/input';
+import { Button } from '@/components/ui/button';
+import { toast } from '@/components/ui/use-toast';
+import { useAppState } from '@/context/stateProvider';
+import { zodResolver } from '@hookform/resolvers/zod';
+import { useForm } from 'react-hook-form';
+import { z } from 'zod';
+import { invoke } from '@tauri-apps/api/core';
+import { FilePatterns } from '@/interfaces/state';
+
+const filePattern = z
+  .string()
+  .trim()
+  .refine((value) => !/[\\/]/.test(value), "File names can't contain folders.")
+  .refine((value) => !/[<>:"|?*]/.test(value), 'File names can\'t contain < > : " | ? *')
+  .refine(
+    (value) => value === '' || value.includes('$$SEQUENCE$$') || value.includes('$$TIMESTAMP$$'),
+    'File names need $$SEQUENCE$$ or $$TIMESTAMP$$ to tell the frames apart.',
+  );
+
+const formSchema = z.object({
+  light: filePattern,
+  dark: filePattern,
+  bias: filePattern,
+  flat: filePattern,
+  dark_flat: filePattern,
+});
+
+const FIELDS: { name: keyof FilePatterns, label: string }[] = [
+  { name: 'light', label: 'Light Frames' },
+  { name: 'dark', label: 'Dark Frames' },
+  { name: 'bias', label: 'Bias Frames' },
+  { name: 'flat', label: 'Flat Frames' },
+  { name: 'dark_flat', label: 'Dark Flat Frames' },
+];
+
+const TOKENS = ['$$NAME$$', '$$SEQUENCE$$', '$$TIMESTAMP$$', '$$EXPOSURE$$', '$$FILTER$$', '$$TEMP$$', '$$GAIN$$', '$$TARGET$$', '$$DATE$$', '$$CAMERA$$', '$$TELESCOPE$$'];
+
+export default function FileNamesForm() {
+  const { appState, setAppState } = useAppState();
+
+  const form = useForm<FilePatterns>({
+    resolver: zodResolver(formSchema),
+    defaultValues: appState.config.folder_paths.file_patterns,
+  });
+
+  function onSubmit(values: FilePatterns) {
+    invoke('save_file_patterns', { filePatterns: values })
+      .then(() => {
+        setAppState((prevState) => ({
+          ...prevState,
+          config: {
+            ...prevState.config,
+            folder_paths: {
+              ...prevState.config.folder_paths,
+              file_patterns: values,
+            },
+          },
+        }));
+        toast({
+          title: 'Success',
+          description: 'Frames classified from now on are named after these patterns.',
+        });
+      })
+      .catch((error) => {
+        toast({
+          variant: 'destructive',
+          title: 'Uh oh! Something went wrong.',
+          description: 'Error: ' + error,
+        });
+      });
+  }
+
+  return (
+    <Form {...form}>
+      <form onSubmit={form.handleSubmit(onSubmit)} className={styles.form}>
+        {FIELDS.map(({ name, label }) => (
+          <FormField
+            key={name}
+            control={form.control}
+            name={name}
+            render={({ field }) => (
+              <FormItem>
+                <FormLabel>{label}</FormLabel>
+                <FormControl>
+                  <Input placeholder="$$TARGET$$_$$FILTER$$_$$EXPOSURE$$_$$SEQUENCE$$" {...field} />
+                </FormControl>
+                <FormMessage />
+              </FormItem>
+            )}
+          />
+        ))}
+        <p className="text-sm text-muted-foreground">
+          Available tokens: {TOKENS.join(', ')}. The file extension is kept, the sequence follows
+          the order of the selected frames and the values of the other tokens come from the
+          header of each frame, or from the session if the header has none. A pattern needs
+          $$SEQUENCE$$ or $$TIMESTAMP$$, leave it empty to keep the original file names.
+        </p>
+        <Button type="submit">Save</Button>
+      </form>
+    </Form>
+  );
+}
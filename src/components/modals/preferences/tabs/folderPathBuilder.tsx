@@ -8,7 +8,7 @@ import { Button } from '@/components/ui/button';
 import { Input } from '@/components/ui/input';
 import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from '@/components/ui/table';
 import { Alert, AlertDescription, AlertTitle } from '@/components/ui/alert';
-import { AlertCircle, CheckCircle2, ChevronRight } from 'lucide-react';
+import { AlertCircle, AlertTriangle, ArrowRight, CheckCircle2, ChevronRight } from 'lucide-react';
 import { Form, FormControl, FormDescription, FormField, FormItem, FormLabel, FormMessage } from '@/components/ui/form';
 import {
   Breadcrumb, BreadcrumbEllipsis,
@@ -18,11 +18,13 @@ import {
   BreadcrumbPage,
   BreadcrumbSeparator
 } from '@/components/ui/breadcrumb';
-import { useAppState } from '@/context/stateProvider';
+import { fetchAppState, useAppState } from '@/context/stateProvider';
 import { invoke } from '@tauri-apps/api/core';
 import { toast } from '@/components/ui/use-toast';
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@/components/ui/select';
 import { Label } from '@/components/ui/label';
+import { FolderPaths } from '@/interfaces/state';
+import { RelayoutPlan } from '@/interfaces/commands';
 
 const PREDEFINED_TOKENS_IS: Token[] = [
   { value: "$$DATE$$", description: "The Date of the Imaging Session" },
@@ -48,11 +50,31 @@ const DEFAULT_TOKEN_VALUES = {
   $$SUBLENGTH$$: "300",
   $$TOTALSUBS$$: "34",
   $$GAIN$$: "100",
+  $$CAMERATEMP$$: "-10",
 }
 
 const PREDEFINED_TOKENS_CAL: Token[] = [
-  { value: "$$SUBLENGTH$$", description: "The Exposure Time" },
   { value: "$$CAMERA$$", description: "The Camera Used" },
+  { value: "$$GAIN$$", description: "The Gain" },
+  { value: "$$TOTALSUBS$$", description: "The Number of Subs" },
+]
+
+const PREDEFINED_TOKENS_DARK: Token[] = [
+  ...PREDEFINED_TOKENS_CAL,
+  { value: "$$SUBLENGTH$$", description: "The Exposure Time" },
+  { value: "$$CAMERATEMP$$", description: "The Camera Temperature" },
+]
+
+const PREDEFINED_TOKENS_FLAT: Token[] = [
+  ...PREDEFINED_TOKENS_CAL,
+  { value: "$$TELESCOPE$$", description: "The Telescope Used" },
+  { value: "$$FILTER$$", description: "The Filter Used" },
+  { value: "$$DATE$$", description: "The Date the Flats were Taken" },
+]
+
+const PREDEFINED_TOKENS_DARK_FLAT: Token[] = [
+  ...PREDEFINED_TOKENS_DARK,
+  { value: "$$DATE$$", description: "The Date the Dark Flats were Taken" },
 ]
 
 export enum FolderPathBuilderType {
@@ -63,6 +85,15 @@ export enum FolderPathBuilderType {
 export enum CalibrationFrameType {
   DARK_FRAME = "Dark Frame",
   BIAS_FRAME = "Bias Frame",
+  FLAT_FRAME = "Flat Frame",
+  DARK_FLAT_FRAME = "Dark Flat Frame",
+}
+
+const CALIBRATION_FRAME_SETTINGS: Record<CalibrationFrameType, { pattern: Exclude<keyof FolderPaths, 'file_patterns'>, folder: string, command: string, tokens: Token[] }> = {
+  [CalibrationFrameType.DARK_FRAME]: { pattern: "dark_frame_pattern", folder: "Dark", command: "change_dark_frames_folder_path", tokens: PREDEFINED_TOKENS_DARK },
+  [CalibrationFrameType.BIAS_FRAME]: { pattern: "bias_frame_pattern", folder: "Bias", command: "change_bias_frames_folder_path", tokens: PREDEFINED_TOKENS_CAL },
+  [CalibrationFrameType.FLAT_FRAME]: { pattern: "flat_frame_pattern", folder: "Flat", command: "change_flat_frames_folder_path", tokens: PREDEFINED_TOKENS_FLAT },
+  [CalibrationFrameType.DARK_FLAT_FRAME]: { pattern: "dark_flat_frame_pattern", folder: "DarkFlat", command: "change_dark_flat_frames_folder_path", tokens: PREDEFINED_TOKENS_DARK_FLAT },
 }
 
 interface Token {
@@ -79,6 +110,23 @@ export function FolderPathBuilder({ type }: { type: FolderPathBuilderType }) {
     CalibrationFrameType.DARK_FRAME,
   )
   const folderPathInputRef = useRef<HTMLInputElement>(null)
+  // the moves of the new structure, shown before anything is moved
+  const [plan, setPlan] = useState<RelayoutPlan | null>(null)
+  // a re-layout that was interrupted, e.g. by a crash
+  const [pendingPlan, setPendingPlan] = useState<RelayoutPlan | null>(null)
+  const [moving, setMoving] = useState<boolean>(false)
+
+  useEffect(() => {
+    invoke<RelayoutPlan | null>("pending_relayout")
+      .then((pending) => setPendingPlan(pending))
+      .catch((error) => {
+        toast({
+          variant: "destructive",
+          title: "Uh oh! Something went wrong.",
+          description: "Error: " + error,
+        })
+      })
+  }, [])
 
   const rootDirectory = appState.local_config.root_directory.replace(/\\/g, "/").replace(/\/+$/, "")
   const parts = rootDirectory.split("/").filter(Boolean)
@@ -108,14 +156,12 @@ export function FolderPathBuilder({ type }: { type: FolderPathBuilderType }) {
     [FolderPathBuilderType.CALIBRATION]: {
       defaultBaseFolder: appState.config.folder_paths.calibration_base_folder,
       defaultFolderPath:
-        calibrationFrameType === CalibrationFrameType.DARK_FRAME
-          ? appState.config.folder_paths.dark_frame_pattern
-          : appState.config.folder_paths.bias_frame_pattern,
+        appState.config.folder_paths[CALIBRATION_FRAME_SETTINGS[calibrationFrameType].pattern],
       requiredTokens:
         calibrationFrameType === CalibrationFrameType.DARK_FRAME
           ? ["$$CAMERA$$"]
           : ["$$CAMERA$$"],
-      tokens: PREDEFINED_TOKENS_CAL,
+      tokens: CALIBRATION_FRAME_SETTINGS[calibrationFrameType].tokens,
       defaultTokenValues: DEFAULT_TOKEN_VALUES,
       baseFolderPlaceholder: "Calibration",
       folderPathPlaceholder: "$$CAMERA$$/$$EXPOSURE$$",
@@ -179,6 +225,7 @@ export function FolderPathBuilder({ type }: { type: FolderPathBuilderType }) {
       if (name === "folderPath" || name === "baseFolder") {
         validatePath(value.baseFolder || "", value.folderPath || "")
         updateBreadcrumbs(value.baseFolder || "", value.folderPath || "")
+        setPlan(null)
       }
     })
     return () => subscription.unsubscribe()
@@ -193,11 +240,10 @@ export function FolderPathBuilder({ type }: { type: FolderPathBuilderType }) {
   useEffect(() => {
     if (type === FolderPathBuilderType.CALIBRATION) {
       const newDefaultFolderPath =
-        calibrationFrameType === CalibrationFrameType.DARK_FRAME
-          ? appState.config.folder_paths.dark_frame_pattern
-          : appState.config.folder_paths.bias_frame_pattern
+        appState.config.folder_paths[CALIBRATION_FRAME_SETTINGS[calibrationFrameType].pattern]
       form.setValue("folderPath", newDefaultFolderPath)
       updateBreadcrumbs(form.getValues().baseFolder, newDefaultFolderPath)
+      setPlan(null)
     }
   }, [calibrationFrameType, type])
 
@@ -252,7 +298,7 @@ export function FolderPathBuilder({ type }: { type: FolderPathBuilderType }) {
     const segments = [
       lastFolder,
       baseFolder,
-      ...(type === FolderPathBuilderType.CALIBRATION ? calibrationFrameType === CalibrationFrameType.DARK_FRAME ? ["Dark"] : ["Bias"] : []),
+      ...(type === FolderPathBuilderType.CALIBRATION ? [CALIBRATION_FRAME_SETTINGS[calibrationFrameType].folder] : []),
       ...folderPath.split(/[/\\]/).filter(Boolean),
       ...(type === FolderPathBuilderType.IMAGING_SESSION ? ["Light"] : []),
       "image.fits"
@@ -285,64 +331,89 @@ export function FolderPathBuilder({ type }: { type: FolderPathBuilderType }) {
     }
   }
 
+  function newFolderPaths(base_folder: string, pattern: string): FolderPaths {
+    if (type === FolderPathBuilderType.IMAGING_SESSION) {
+      return {
+        ...appState.config.folder_paths,
+        imaging_session_base_folder: base_folder,
+        imaging_session_pattern: pattern,
+      }
+    }
+    return {
+      ...appState.config.folder_paths,
+      calibration_base_folder: base_folder,
+      [CALIBRATION_FRAME_SETTINGS[calibrationFrameType].pattern]: pattern,
+    }
+  }
+
   function handleSubmit() {
     form.setValue("folderPath", form.getValues().folderPath.trim().replace(/\//g, "\\"))
 
     const base_folder = form.getValues().baseFolder.trim()
     const pattern = form.getValues().folderPath.trim()
 
-    if (type === FolderPathBuilderType.IMAGING_SESSION) {
-      invoke("change_imaging_session_folder_path", { baseFolder: base_folder, pattern: pattern })
-        .then(() => {
-          setAppState((prevState) => ({
-            ...prevState,
-            config: {
-              ...prevState.config,
-              folder_paths: {
-                ...prevState.config.folder_paths,
-                imaging_session_base_folder: base_folder,
-                imaging_session_pattern: pattern,
-              },
-            },
-          }))
+    invoke<RelayoutPlan>("preview_folder_paths", { folderPaths: newFolderPaths(base_folder, pattern) })
+      .then((plan) => setPlan(plan))
+      .catch((error) => {
+        toast({
+          variant: "destructive",
+          title: "Uh oh! Something went wrong.",
+          description: "Error: " + error,
         })
-        .catch((error) => {
-          toast({
-            variant: "destructive",
-            title: "Uh oh! Something went wrong.",
-            description: "Error: " + error,
-          })
+      })
+  }
+
+  function applyChange() {
+    const base_folder = form.getValues().baseFolder.trim()
+    const pattern = form.getValues().folderPath.trim()
+    const functionName =
+      type === FolderPathBuilderType.IMAGING_SESSION
+        ? "change_imaging_session_folder_path"
+        : CALIBRATION_FRAME_SETTINGS[calibrationFrameType].command
+
+    setMoving(true)
+    invoke(functionName, { baseFolder: base_folder, pattern: pattern })
+      .then(() => {
+        setAppState((prevState) => ({
+          ...prevState,
+          config: {
+            ...prevState.config,
+            folder_paths: newFolderPaths(base_folder, pattern),
+          },
+        }))
+        setPlan(null)
+      })
+      .catch((error) => {
+        toast({
+          variant: "destructive",
+          title: "Uh oh! Something went wrong.",
+          description: "Error: " + error,
         })
-    } else if (type === FolderPathBuilderType.CALIBRATION) {
-      const functionName =
-        calibrationFrameType === CalibrationFrameType.DARK_FRAME
-          ? "change_dark_frames_folder_path"
-          : "change_bias_frames_folder_path"
-
-      invoke(functionName, { baseFolder: base_folder, pattern: pattern })
-        .then(() => {
-          setAppState((prevState) => ({
-            ...prevState,
-            config: {
-              ...prevState.config,
-              folder_paths: {
-                ...prevState.config.folder_paths,
-                calibration_base_folder: base_folder,
-                [calibrationFrameType === CalibrationFrameType.DARK_FRAME
-                  ? "dark_frame_pattern"
-                  : "bias_frame_pattern"]: pattern,
-              },
-            },
-          }))
+        // an interrupted move leaves a plan behind that has to be resumed
+        invoke<RelayoutPlan | null>("pending_relayout").then((pending) => setPendingPlan(pending))
+      })
+      .finally(() => setMoving(false))
+  }
+
+  function resumeRelayout() {
+    setMoving(true)
+    invoke("resume_relayout")
+      .then(() => {
+        setPendingPlan(null)
+        fetchAppState(setAppState)
+        toast({
+          title: "Success",
+          description: "The folder structure was changed.",
         })
-        .catch((error) => {
-          toast({
-            variant: "destructive",
-            title: "Uh oh! Something went wrong.",
-            description: "Error: " + error,
-          })
+      })
+      .catch((error) => {
+        toast({
+          variant: "destructive",
+          title: "Uh oh! Something went wrong.",
+          description: "Error: " + error,
         })
-    }
+      })
+      .finally(() => setMoving(false))
   }
 
   const isDefaultValue =
@@ -351,6 +422,22 @@ export function FolderPathBuilder({ type }: { type: FolderPathBuilderType }) {
   return (
     <Form {...form}>
       <form onSubmit={form.handleSubmit(handleSubmit)} className="space-y-6">
+        {pendingPlan !== null && (
+          <Alert variant="destructive">
+            <AlertTriangle className="h-4 w-4" />
+            <AlertTitle>Interrupted Folder Change</AlertTitle>
+            <AlertDescription>
+              <p>
+                A change of the folder structure was interrupted,{" "}
+                {pendingPlan.moves.filter((m) => m.state !== "MOVED").length} of {pendingPlan.moves.length} folder(s)
+                still have to be moved. Resume it before changing the folder structure again.
+              </p>
+              <Button type="button" className="mt-2" disabled={moving} onClick={resumeRelayout}>
+                Resume
+              </Button>
+            </AlertDescription>
+          </Alert>
+        )}
         {type === FolderPathBuilderType.CALIBRATION && (
           <div className="mb-4">
             <Label htmlFor="calibration-type">Calibration Type</Label>
@@ -364,6 +451,8 @@ export function FolderPathBuilder({ type }: { type: FolderPathBuilderType }) {
               <SelectContent>
                 <SelectItem value={CalibrationFrameType.DARK_FRAME}>Dark Frames</SelectItem>
                 <SelectItem value={CalibrationFrameType.BIAS_FRAME}>Bias Frames</SelectItem>
+                <SelectItem value={CalibrationFrameType.FLAT_FRAME}>Flat Frames</SelectItem>
+                <SelectItem value={CalibrationFrameType.DARK_FLAT_FRAME}>Dark Flat Frames</SelectItem>
               </SelectContent>
             </Select>
           </div>
@@ -489,13 +578,89 @@ export function FolderPathBuilder({ type }: { type: FolderPathBuilderType }) {
           </div>
         </div>
 
-        <Button
-          type="submit"
-          className="ml-auto"
-          disabled={!form.formState.isValid || errors.length > 0 || isDefaultValue}
-        >
-          Save Path Configuration
-        </Button>
+        {plan !== null && (
+          <div className="space-y-4">
+            <h3 className="text-lg font-medium">Folders to Move</h3>
+            {plan.collisions.length > 0 && (
+              <Alert variant="destructive">
+                <AlertCircle className="h-4 w-4" />
+                <AlertTitle>Collisions</AlertTitle>
+                <AlertDescription>
+                  <ul className="list-disc pl-5 mt-2">
+                    {plan.collisions.map((collision) => (
+                      <li key={collision.new_dir}>
+                        {collision.new_dir}
+                        {collision.exists ? " already contains files" : ""}
+                        {collision.old_dirs.length > 1 ? " is the target of " + collision.old_dirs.join(", ") : ""}
+                      </li>
+                    ))}
+                  </ul>
+                </AlertDescription>
+              </Alert>
+            )}
+            {plan.missing.length > 0 && (
+              <Alert variant="destructive">
+                <AlertCircle className="h-4 w-4" />
+                <AlertTitle>Missing Folders</AlertTitle>
+                <AlertDescription>
+                  <ul className="list-disc pl-5 mt-2">
+                    {plan.missing.map((folder) => (
+                      <li key={folder}>{folder}</li>
+                    ))}
+                  </ul>
+                </AlertDescription>
+              </Alert>
+            )}
+            {plan.moves.length === 0 ? (
+              <p className="text-sm text-muted-foreground">No folder has to be moved.</p>
+            ) : (
+              <div className="border rounded-md">
+                <Table>
+                  <TableHeader>
+                    <TableRow>
+                      <TableHead>Old Folder</TableHead>
+                      <TableHead />
+                      <TableHead>New Folder</TableHead>
+                    </TableRow>
+                  </TableHeader>
+                  <TableBody>
+                    {plan.moves.map((move) => (
+                      <TableRow key={move.id}>
+                        <TableCell className="font-mono">{move.old_dir}</TableCell>
+                        <TableCell>
+                          <ArrowRight className="h-4 w-4" />
+                        </TableCell>
+                        <TableCell className="font-mono">{move.new_dir}</TableCell>
+                      </TableRow>
+                    ))}
+                  </TableBody>
+                </Table>
+              </div>
+            )}
+            <div className="flex gap-2">
+              <Button type="button" variant="outline" disabled={moving} onClick={() => setPlan(null)}>
+                Cancel
+              </Button>
+              <Button
+                type="button"
+                disabled={moving || plan.collisions.length > 0 || plan.missing.length > 0}
+                onClick={applyChange}
+              >
+                Move {plan.moves.length} Folder(s)
+              </Button>
+            </div>
+          </div>
+        )}
+
+        {plan === null && (
+          <Button
+            type="submit"
+            className="ml-auto"
+            disabled={!form.formState.isValid || errors.length > 0 || isDefaultValue || pendingPlan !== null}
+          >
+            Save Path Configuration
+          </Button>
+        )}
       </form>
     </Form>
   )
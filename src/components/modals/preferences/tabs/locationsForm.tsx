@@ -8,6 +8,7 @@ import {
   FormMessage
 } from '@/components/ui/form';
 import { Input } from '@/components/ui/input';
+import { Textarea } from '@/components/ui/textarea';
 import { zodResolver } from '@hookform/resolvers/zod';
 import { useEffect, useState } from 'react';
 import { useForm } from 'react-hook-form';
@@ -31,7 +32,7 @@ import {
   SelectTrigger,
   SelectValue,
 } from '@/components/ui/select';
-import { Location } from '@/interfaces/state';
+import { HorizonPoint, Location } from '@/interfaces/state';
 import { v4 as uuidv4 } from 'uuid';
 import { UUID } from 'crypto';
 import { invoke } from '@tauri-apps/api/core';
@@ -83,16 +84,60 @@ export default function LocationsForm() {
   );
 }
 
+function isTimezone(timezone: string): boolean {
+  try {
+    new Intl.DateTimeFormat('en', { timeZone: timezone });
+    return true;
+  } catch {
+    return false;
+  }
+}
+
+// one "azimuth, altitude" pair per line
+function parseHorizon(text: string): HorizonPoint[] | null {
+  const points: HorizonPoint[] = [];
+  for (const line of text.split('\n')) {
+    if (line.trim() === '') continue;
+    const [azimuth, altitude] = line.split(/[,;\s]+/).map(Number.parseFloat);
+    if (
+      Number.isNaN(azimuth) || Number.isNaN(altitude) ||
+      azimuth < 0 || azimuth > 360 || altitude < -90 || altitude > 90
+    ) {
+      return null;
+    }
+    points.push({ azimuth, altitude });
+  }
+  return points;
+}
+
+function horizonText(horizon: HorizonPoint[]): string {
+  return horizon.map((point) => `${point.azimuth}, ${point.altitude}`).join('\n');
+}
+
 const formSchema = z.object({
   name: z.string().min(1, 'Name is required'),
-  x: z.number().min(-180).max(180),
-  y: z.number().min(-90).max(90),
-  height: z.number().positive('Height must be positive'),
+  latitude: z.number().min(-90).max(90),
+  longitude: z.number().min(-180).max(180),
+  elevation: z.number().min(-500).max(9000),
+  timezone: z.string().refine(isTimezone, 'Unknown timezone'),
   bortle: z.number().min(1).max(9),
+  sqm: z.number().min(16).max(23).optional(),
+  horizon: z.string().refine((text) => parseHorizon(text) !== null, 'Expected one "azimuth, altitude" pair per line'),
 });
 
 type FormValues = z.infer<typeof formSchema>;
 
+const emptyValues: FormValues = {
+  name: '',
+  latitude: 0,
+  longitude: 0,
+  elevation: 0,
+  timezone: Intl.DateTimeFormat().resolvedOptions().timeZone,
+  bortle: 1,
+  sqm: undefined,
+  horizon: '',
+};
+
 interface LocationFormProps {
   editingLocation: Location | null;
   onCancel: () => void;
@@ -103,26 +148,21 @@ export function LocationForm({ editingLocation, onCancel }: LocationFormProps) {
 
   const form = useForm<FormValues>({
     resolver: zodResolver(formSchema),
-    defaultValues: {
-      name: '',
-      x: 0,
-      y: 0,
-      height: 0,
-      bortle: 1,
-    },
+    defaultValues: emptyValues,
   });
 
   useEffect(() => {
     if (editingLocation) {
-      form.reset(editingLocation);
-    } else {
       form.reset({
-        name: '',
-        x: 0,
-        y: 0,
-        height: 0,
-        bortle: 1,
+        ...editingLocation,
+        // locations moved from the old config have no bortle class or timezone yet
+        bortle: editingLocation.bortle || 1,
+        timezone: editingLocation.needs_review ? emptyValues.timezone : editingLocation.timezone,
+        sqm: editingLocation.sqm ?? undefined,
+        horizon: horizonText(editingLocation.horizon),
       });
+    } else {
+      form.reset(emptyValues);
     }
   }, [editingLocation, form]);
 
@@ -130,20 +170,21 @@ export function LocationForm({ editingLocation, onCancel }: LocationFormProps) {
     const loc: Location = {
       id: (editingLocation?.id as UUID) ?? uuidv4(),
       name: values.name,
-      x: values.x,
-      y: values.y,
-      height: values.height,
+      latitude: values.latitude,
+      longitude: values.longitude,
+      elevation: values.elevation,
+      timezone: values.timezone,
       bortle: values.bortle,
+      sqm: values.sqm ?? null,
+      horizon: parseHorizon(values.horizon) ?? [],
+      needs_review: false,
     };
 
     invoke('save_location', { location: loc })
       .then(() => {
         setAppState((prevState) => ({
           ...prevState,
-          config: {
-            ...prevState.config,
-            locations: new Map(prevState.config.locations).set(loc.id, loc),
-          },
+          locations: new Map(prevState.locations).set(loc.id, loc),
         }));
         if (editingLocation) {
           fetchAppState(setAppState);
@@ -173,6 +214,16 @@ export function LocationForm({ editingLocation, onCancel }: LocationFormProps) {
             onSubmit={form.handleSubmit(handleSubmit)}
             className="space-y-4"
           >
+            {editingLocation?.needs_review && (
+              <Alert>
+                <AlertTriangle className="h-4 w-4" />
+                <AlertTitle>Please review this location</AlertTitle>
+                <AlertDescription>
+                  It was moved from an older version of AstroLog, which didn&apos;t store a timezone. Coordinates
+                  outside the valid range were reset to 0. Check the values and save the location.
+                </AlertDescription>
+              </Alert>
+            )}
             <FormField
               control={form.control}
               name="name"
@@ -187,107 +238,163 @@ export function LocationForm({ editingLocation, onCancel }: LocationFormProps) {
               )}
             />
 
-            {!editingLocation && (
-              <>
-                <div className="grid grid-cols-3 gap-4">
-                  <FormField
-                    control={form.control}
-                    name="x"
-                    render={({ field }) => (
-                      <FormItem>
-                        <FormLabel>X Coordinate</FormLabel>
-                        <FormControl>
-                          <Input
-                            type="number"
-                            step="any"
-                            placeholder="X"
-                            {...field}
-                            onChange={(e) =>
-                              field.onChange(Number.parseFloat(e.target.value))
-                            }
-                          />
-                        </FormControl>
-                        <FormDescription>E.g. -23.638</FormDescription>
-                        <FormMessage />
-                      </FormItem>
-                    )}
-                  />
-
-                  <FormField
-                    control={form.control}
-                    name="y"
-                    render={({ field }) => (
-                      <FormItem>
-                        <FormLabel>Y Coordinate</FormLabel>
-                        <FormControl>
-                          <Input
-                            type="number"
-                            step="any"
-                            placeholder="Y"
-                            {...field}
-                            onChange={(e) =>
-                              field.onChange(Number.parseFloat(e.target.value))
-                            }
-                          />
-                        </FormControl>
-                        <FormDescription>E.g. 32.3324</FormDescription>
-                        <FormMessage />
-                      </FormItem>
-                    )}
-                  />
-
-                  <FormField
-                    control={form.control}
-                    name="height"
-                    render={({ field }) => (
-                      <FormItem>
-                        <FormLabel>Height</FormLabel>
-                        <FormControl>
-                          <UnitsInput
-                            kind="length"
-                            columnUnits={{ metric: "m", imperial: "ft" }}
-                            metricValue={field.value} // <-- always meters in state
-                            onMetricChange={(val) => field.onChange(val)}
-                          />
-                        </FormControl>
-                        <FormDescription>E.g. 426</FormDescription>
-                        <FormMessage />
-                      </FormItem>
-                    )}
-                  />
-                </div>
-
-                <FormField
-                  control={form.control}
-                  name="bortle"
-                  render={({ field }) => (
-                    <FormItem>
-                      <FormLabel>Bortle Scale</FormLabel>
-                      <Select
-                        onValueChange={(value) =>
-                          field.onChange(Number.parseInt(value))
+            <div className="grid grid-cols-3 gap-4">
+              <FormField
+                control={form.control}
+                name="latitude"
+                render={({ field }) => (
+                  <FormItem>
+                    <FormLabel>Latitude</FormLabel>
+                    <FormControl>
+                      <Input
+                        type="number"
+                        step="any"
+                        placeholder="Latitude"
+                        {...field}
+                        onChange={(e) =>
+                          field.onChange(Number.parseFloat(e.target.value))
                         }
-                        defaultValue={field.value.toString()}
-                      >
-                        <FormControl>
-                          <SelectTrigger>
-                            <SelectValue placeholder="Select Bortle scale" />
-                          </SelectTrigger>
-                        </FormControl>
-                        <SelectContent>
-                          {[1, 2, 3, 4, 5, 6, 7, 8, 9].map((value) => (
-                            <SelectItem key={value} value={value.toString()}>
-                              {value}
-                            </SelectItem>
-                          ))}
-                        </SelectContent>
-                      </Select>
-                      <FormMessage />
-                    </FormItem>
-                  )}
-                />
-              </>
-            )}
+                      />
+                    </FormControl>
+                    <FormDescription>Decimal degrees, north is positive. E.g. 32.3324</FormDescription>
+                    <FormMessage />
+                  </FormItem>
+                )}
+              />
+
+              <FormField
+                control={form.control}
+                name="longitude"
+                render={({ field }) => (
+                  <FormItem>
+                    <FormLabel>Longitude</FormLabel>
+                    <FormControl>
+                      <Input
+                        type="number"
+                        step="any"
+                        placeholder="Longitude"
+                        {...field}
+                        onChange={(e) =>
+                          field.onChange(Number.parseFloat(e.target.value))
+                        }
+                      />
+                    </FormControl>
+                    <FormDescription>Decimal degrees, east is positive. E.g. -23.638</FormDescription>
+                    <FormMessage />
+                  </FormItem>
+                )}
+              />
+
+              <FormField
+                control={form.control}
+                name="elevation"
+                render={({ field }) => (
+                  <FormItem>
+                    <FormLabel>Elevation</FormLabel>
+                    <FormControl>
+                      <UnitsInput
+                        kind="length"
+                        columnUnits={{ metric: "m", imperial: "ft" }}
+                        metricValue={field.value} // <-- always meters in state
+                        onMetricChange={(val) => field.onChange(val ?? 0)}
+                      />
+                    </FormControl>
+                    <FormDescription>Above sea level. E.g. 426</FormDescription>
+                    <FormMessage />
+                  </FormItem>
+                )}
+              />
+            </div>
+
+            <div className="grid grid-cols-3 gap-4">
+              <FormField
+                control={form.control}
+                name="timezone"
+                render={({ field }) => (
+                  <FormItem>
+                    <FormLabel>Timezone</FormLabel>
+                    <FormControl>
+                      <Input placeholder="Europe/Berlin" {...field} />
+                    </FormControl>
+                    <FormDescription>IANA name, e.g. America/New_York</FormDescription>
+                    <FormMessage />
+                  </FormItem>
+                )}
+              />
+
+              <FormField
+                control={form.control}
+                name="bortle"
+                render={({ field }) => (
+                  <FormItem>
+                    <FormLabel>Bortle Scale</FormLabel>
+                    <Select
+                      onValueChange={(value) =>
+                        field.onChange(Number.parseInt(value))
+                      }
+                      value={field.value.toString()}
+                    >
+                      <FormControl>
+                        <SelectTrigger>
+                          <SelectValue placeholder="Select Bortle scale" />
+                        </SelectTrigger>
+                      </FormControl>
+                      <SelectContent>
+                        {[1, 2, 3, 4, 5, 6, 7, 8, 9].map((value) => (
+                          <SelectItem key={value} value={value.toString()}>
+                            {value}
+                          </SelectItem>
+                        ))}
+                      </SelectContent>
+                    </Select>
+                    <FormMessage />
+                  </FormItem>
+                )}
+              />
+
+              <FormField
+                control={form.control}
+                name="sqm"
+                render={({ field }) => (
+                  <FormItem>
+                    <FormLabel>SQM</FormLabel>
+                    <FormControl>
+                      <Input
+                        type="number"
+                        step="any"
+                        placeholder="Optional"
+                        {...field}
+                        value={field.value ?? ''}
+                        onChange={(e) =>
+                          field.onChange(
+                            e.target.value === '' ? undefined : Number.parseFloat(e.target.value)
+                          )
+                        }
+                      />
+                    </FormControl>
+                    <FormDescription>mag/arcsec², e.g. 21.3</FormDescription>
+                    <FormMessage />
+                  </FormItem>
+                )}
+              />
+            </div>
+
+            <FormField
+              control={form.control}
+              name="horizon"
+              render={({ field }) => (
+                <FormItem>
+                  <FormLabel>Horizon Profile</FormLabel>
+                  <FormControl>
+                    <Textarea placeholder={'0, 15\n90, 20\n180, 10\n270, 25'} {...field} />
+                  </FormControl>
+                  <FormDescription>
+                    Optional. One &quot;azimuth, altitude&quot; pair in degrees per line.
+                  </FormDescription>
+                  <FormMessage />
+                </FormItem>
+              )}
+            />
 
             <div className="flex gap-2">
               <Button type="submit" className="flex-1">
@@ -314,7 +421,7 @@ interface LocationListProps {
 export function LocationList({ onEditLocation }: LocationListProps) {
   const { appState, setAppState } = useAppState();
 
-  const locations = Array.from(appState.config.locations.values());
+  const locations = Array.from(appState.locations.values());
 
   const columnUnits = { metric: "m", imperial: "ft" } as const;
   const unit = useUnit(columnUnits);
@@ -331,15 +438,12 @@ export function LocationList({ onEditLocation }: LocationListProps) {
     invoke('delete_location', { location: loc })
       .then(() =>
         setAppState((prevState) => {
-          const newLocations = new Map(prevState.config.locations);
+          const newLocations = new Map(prevState.locations);
           newLocations.delete(loc.id);
 
           return {
             ...prevState,
-            config: {
-              ...prevState.config,
-              locations: newLocations,
-            },
+            locations: newLocations,
           };
         }),
       )
@@ -359,23 +463,36 @@ export function LocationList({ onEditLocation }: LocationListProps) {
           <TableHeader>
             <TableRow>
               <TableHead>Name</TableHead>
-              <TableHead>X</TableHead>
-              <TableHead>Y</TableHead>
-              <TableHead>Height [{unit}]</TableHead>
+              <TableHead>Latitude</TableHead>
+              <TableHead>Longitude</TableHead>
+              <TableHead>Elevation [{unit}]</TableHead>
+              <TableHead>Timezone</TableHead>
               <TableHead>Bortle</TableHead>
+              <TableHead>SQM</TableHead>
               <TableHead className="w-[100px]">Actions</TableHead>
             </TableRow>
           </TableHeader>
           <TableBody>
             {locations.map((location) => (
               <TableRow key={location.id}>
-                <TableCell className="font-medium">{location.name}</TableCell>
-                <TableCell>{location.x}</TableCell>
-                <TableCell>{location.y}</TableCell>
+                <TableCell className="font-medium">
+                  <div className="flex items-center gap-1">
+                    {location.name}
+                    {location.needs_review && (
+                      <span title="Moved from an older version, please review the timezone and coordinates">
+                        <AlertTriangle className="h-4 w-4 text-yellow-500" />
+                      </span>
+                    )}
+                  </div>
+                </TableCell>
+                <TableCell>{location.latitude}°</TableCell>
+                <TableCell>{location.longitude}°</TableCell>
                 <TableCell>
-                  <LengthCell meters={location.height} columnUnits={columnUnits} />
+                  <LengthCell meters={location.elevation} columnUnits={columnUnits} />
                 </TableCell>
-                <TableCell>{location.bortle}</TableCell>
+                <TableCell>{location.needs_review ? 'Unknown' : location.timezone}</TableCell>
+                <TableCell>{location.bortle || 'N/A'}</TableCell>
+                <TableCell>{location.sqm ?? 'N/A'}</TableCell>
                 <TableCell>
                   <div className="flex space-x-1">
                     <Button
@@ -21,8 +21,32 @@ import FileSelector, {
 import { z } from 'zod';
 import { invoke } from '@tauri-apps/api/core';
 import React from 'react';
-import { CopyButton, DeleteButton } from '@/components/ui/button';
-import { AppState } from '@/interfaces/state';
+import { Button, CopyButton, DeleteButton } from '@/components/ui/button';
+import { AppState, CollisionHandling } from '@/interfaces/state';
+import { Input } from '@/components/ui/input';
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from '@/components/ui/select';
+import { ImportMode } from '@/enums/importMode';
+import { CollisionStrategy } from '@/enums/collisionStrategy';
+
+const IMPORT_MODES: { value: ImportMode, label: string }[] = [
+  { value: ImportMode.COPY, label: 'Copy' },
+  { value: ImportMode.MOVE, label: 'Move (verify, then delete the source)' },
+  { value: ImportMode.HARDLINK, label: 'Hardlink' },
+  { value: ImportMode.REFLINK, label: 'Reflink' },
+];
+
+const COLLISION_STRATEGIES: { value: CollisionStrategy, label: string }[] = [
+  { value: CollisionStrategy.SKIP, label: 'Skip the file' },
+  { value: CollisionStrategy.SUFFIX, label: 'Add a suffix (Light_001_1.fits)' },
+  { value: CollisionStrategy.PATTERN, label: 'Rename with a pattern' },
+  { value: CollisionStrategy.ABORT, label: 'Abort the classification' },
+];
 
 const formSchema = z.object({
   rootDirectory: z.string().min(2, {
@@ -34,11 +58,14 @@ const formSchema = z.object({
   sourceDirectory: z.string().min(2, {
     message: 'Username must be at least 2 characters.', // change
   }),
+  importMode: z.nativeEnum(ImportMode),
+  collisionStrategy: z.nativeEnum(CollisionStrategy),
+  collisionPattern: z.string(),
 });
 
 export default function SourceForm() {
   const { toast } = useToast();
-  const { appState } = useAppState();
+  const { appState, setAppState } = useAppState();
 
   const form = useForm<z.infer<typeof formSchema>>({
     resolver: zodResolver(formSchema),
@@ -46,6 +73,9 @@ export default function SourceForm() {
       rootDirectory: appState.local_config.root_directory,
       backupDirectory: '',
       sourceDirectory: appState.local_config.source_directory,
+      importMode: appState.config.import_mode,
+      collisionStrategy: appState.config.collision_handling.strategy,
+      collisionPattern: appState.config.collision_handling.pattern,
     },
   });
 
@@ -61,6 +91,40 @@ export default function SourceForm() {
     });
   }
 
+  function importModeChange(value: ImportMode) {
+    invoke('save_import_mode', { importMode: value })
+      .then(() => {
+        setAppState((prev) => ({
+          ...prev,
+          config: { ...prev.config, import_mode: value },
+        }));
+      })
+      .catch((error) => {
+        toast({
+          variant: 'destructive',
+          title: 'Uh oh! Something went wrong.',
+          description: 'Error: ' + error,
+        });
+      });
+  }
+
+  function saveCollisionHandling(collisionHandling: CollisionHandling) {
+    invoke('save_collision_handling', { collisionHandling: collisionHandling })
+      .then(() => {
+        setAppState((prev) => ({
+          ...prev,
+          config: { ...prev.config, collision_handling: collisionHandling },
+        }));
+      })
+      .catch((error) => {
+        toast({
+          variant: 'destructive',
+          title: 'Uh oh! Something went wrong.',
+          description: 'Error: ' + error,
+        });
+      });
+  }
+
   return (
     <Form {...form}>
       <form onSubmit={form.handleSubmit(onSubmit)} className={styles.form}>
@@ -168,6 +232,98 @@ export default function SourceForm() {
             </FormItem>
           )}
         />
+        <FormField
+          control={form.control}
+          name="importMode"
+          render={() => (
+            <FormItem>
+              <FormLabel>Import Mode</FormLabel>
+              <Select value={appState.config.import_mode} onValueChange={importModeChange}>
+                <FormControl>
+                  <SelectTrigger>
+                    <SelectValue />
+                  </SelectTrigger>
+                </FormControl>
+                <SelectContent>
+                  {IMPORT_MODES.map(({ value, label }) => (
+                    <SelectItem key={value} value={value}>{label}</SelectItem>
+                  ))}
+                </SelectContent>
+              </Select>
+              <FormDescription>
+                How classified frames get into the root directory. Hardlinks and
+                reflinks fall back to a copy where the filesystem doesn&apos;t
+                support them.
+              </FormDescription>
+              <FormMessage />
+            </FormItem>
+          )}
+        />
+        <FormField
+          control={form.control}
+          name="collisionStrategy"
+          render={({ field }) => (
+            <FormItem>
+              <FormLabel>File Name Collisions</FormLabel>
+              <Select
+                value={field.value}
+                onValueChange={(value: CollisionStrategy) => {
+                  field.onChange(value);
+                  saveCollisionHandling({
+                    strategy: value,
+                    pattern: form.getValues().collisionPattern,
+                  });
+                }}
+              >
+                <FormControl>
+                  <SelectTrigger>
+                    <SelectValue />
+                  </SelectTrigger>
+                </FormControl>
+                <SelectContent>
+                  {COLLISION_STRATEGIES.map(({ value, label }) => (
+                    <SelectItem key={value} value={value}>{label}</SelectItem>
+                  ))}
+                </SelectContent>
+              </Select>
+              <FormDescription>
+                What happens when a classified frame has the name of a file that
+                is already in its folder.
+              </FormDescription>
+              <FormMessage />
+            </FormItem>
+          )}
+        />
+        {form.watch('collisionStrategy') === CollisionStrategy.PATTERN && (
+          <FormField
+            control={form.control}
+            name="collisionPattern"
+            render={({ field }) => (
+              <FormItem>
+                <FormLabel>Collision File Name</FormLabel>
+                <div className="flex gap-2">
+                  <FormControl>
+                    <Input {...field} placeholder="$$NAME$$_$$TIMESTAMP$$" />
+                  </FormControl>
+                  <Button
+                    type="button"
+                    onClick={() => saveCollisionHandling({
+                      strategy: CollisionStrategy.PATTERN,
+                      pattern: field.value,
+                    })}
+                  >
+                    Save
+                  </Button>
+                </div>
+                <FormDescription>
+                  The new name without its extension. $$NAME$$ is the original
+                  name, $$TIMESTAMP$$ the capture time of the frame.
+                </FormDescription>
+                <FormMessage />
+              </FormItem>
+            )}
+          />
+        )}
         <div></div>
       </form>
     </Form>
@@ -44,11 +44,15 @@ export default function SelectImagingFrames() {
       frames: form.getValues().frames,
     })
       .then((result) => {
+        // files whose header couldn't be read aren't classified
+        const paths = form.getValues().frames.filter(
+          (path) => !result.unreadable.some((error) => error.path === path),
+        );
         openModal(
           <CalibrationRowEditor
             analyzedFrames={result}
             edit={false}
-            paths={form.getValues().frames}
+            paths={paths}
           />,
         );
         if (result.message !== null) {
@@ -0,0 +1,62 @@
+'use client';
+
+import { Modal } from '@/components/ui/custom/modal';
+import { toast } from '@/components/ui/use-toast';
+import { IntegrityReport } from '@/interfaces/commands';
+import { invoke } from '@tauri-apps/api/core';
+import { useEffect, useState } from 'react';
+
+function FileList({ title, files, empty }: { title: string, files: string[], empty: string }) {
+  return (
+    <div>
+      <h2 className="text-xl font-semibold mb-4">{title}</h2>
+      {files.length === 0 ? (
+        <p>{empty}</p>
+      ) : (
+        <ul className="list-disc pl-6">
+          {files.map((file) => (
+            <li key={file}>{file}</li>
+          ))}
+        </ul>
+      )}
+    </div>
+  );
+}
+
+export default function VerifyLibrary() {
+  const [report, setReport] = useState<IntegrityReport | null>(null);
+
+  useEffect(() => {
+    invoke<IntegrityReport>('verify_library')
+      .then((report) => setReport(report))
+      .catch((error) =>
+        toast({
+          variant: 'destructive',
+          title: 'Uh oh! Something went wrong.',
+          description: 'Error: ' + error,
+        })
+      );
+  }, []);
+
+  return (
+    <Modal
+      title="Verify Library"
+      subtitle="Compares every classified frame against the checksum it had when it entered the library."
+      separator={true}
+    >
+      {report ? (
+        <div className="space-y-8">
+          <p>
+            {report.verified} frames are intact.
+            {report.recorded > 0 && ` ${report.recorded} frames had no checksum yet, their current one was recorded.`}
+          </p>
+          <FileList title="Corrupted Frames" files={report.corrupted} empty="No frame changed since it was classified." />
+          <FileList title="Missing Frames" files={report.missing} empty="Every frame is still in the library." />
+          <FileList title="Unreadable Frames" files={report.unreadable} empty="Every frame could be read." />
+        </div>
+      ) : (
+        <p>Verifying your library, this can take a while...</p>
+      )}
+    </Modal>
+  );
+}
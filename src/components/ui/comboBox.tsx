@@ -169,7 +169,7 @@ export function LocationComboBox({ value, onChange }: ComboBoxPros) {
   const [values, setValues] = useState<ComboBoxValue[]>([]);
 
   useEffect(() => {
-    const locations = Array.from(appState.config.locations.values());
+    const locations = Array.from(appState.locations.values());
 
     const newValues: ComboBoxValue[] = locations.map(loc => ({
       id: loc.id,
@@ -177,7 +177,7 @@ export function LocationComboBox({ value, onChange }: ComboBoxPros) {
     }));
 
     setValues(newValues)
-  }, [appState.config.locations]);
+  }, [appState.locations]);
 
   return (
     <ComboBox
@@ -213,6 +213,50 @@ export function BiasFrameComboBox({ value, onChange }: ComboBoxPros) {
   );
 }
 
+export function FlatFrameComboBox({ value, onChange }: ComboBoxPros) {
+  const { appState } = useAppState();
+
+  const flatFrames = Array.from(appState.table_data.calibration).filter(frame => frame.calibration_type === CalibrationType.FLAT);
+
+  const values: ComboBoxValue[] = flatFrames.map(frame => ({
+    id: frame.id,
+    name: frame.camera + " " + frame.gain,
+  }));
+
+  return (
+    <ComboBox
+      value={value}
+      onChange={onChange}
+      values={values}
+      placeholder="Flat Frames"
+      nullItem={true}
+      placeFirst={false}
+    />
+  );
+}
+
+export function DarkFlatFrameComboBox({ value, onChange }: ComboBoxPros) {
+  const { appState } = useAppState();
+
+  const darkFlatFrames = Array.from(appState.table_data.calibration).filter(frame => frame.calibration_type === CalibrationType.DARKFLAT);
+
+  const values: ComboBoxValue[] = darkFlatFrames.map(frame => ({
+    id: frame.id,
+    name: frame.camera + " " + frame.gain + " " + frame.sub_length + "s",
+  }));
+
+  return (
+    <ComboBox
+      value={value}
+      onChange={onChange}
+      values={values}
+      placeholder="Dark Flat Frames"
+      nullItem={true}
+      placeFirst={false}
+    />
+  );
+}
+
 export function DarkFrameComboBox({ value, onChange }: ComboBoxPros) {
   const { appState } = useAppState();
 
@@ -39,6 +39,14 @@ export const ProcessProvider: React.FC<{ children: React.ReactNode }> = ({
               description: 'Finished Process: ' + event.payload.name,
             });
           }
+
+          if (event.payload.collisions.length > 0) {
+            const skipped = event.payload.collisions.filter((collision) => collision.destination === null).length;
+            toast({
+              title: 'File name collisions',
+              description: `${event.payload.collisions.length} file(s) of ${event.payload.name} had a name that was already taken, ${skipped} of them were skipped.`,
+            });
+          }
         } else {
           updatedProcesses.set(event.payload.id, event.payload);
         }
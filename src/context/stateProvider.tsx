@@ -6,6 +6,9 @@ import { toast } from '@/components/ui/use-toast';
 import {
   AppState,
   CalibrationFrame,
+  CalibrationTolerances,
+  CalibrationValidity,
+  CollisionHandling,
   Config,
   FolderPaths,
   GalleryImage,
@@ -18,6 +21,8 @@ import { Camera, EquipmentItem, EquipmentNote, Filter, Flattener, Mount, Telesco
 import { UUID } from 'crypto';
 import { Analytics } from '@/interfaces/analytics';
 import { UnitSystem } from '@/enums/unitSystem';
+import { ImportMode } from '@/enums/importMode';
+import { CollisionStrategy } from '@/enums/collisionStrategy';
 
 const defaultAppState: AppState = {
   initialised: false,
@@ -33,8 +38,34 @@ const defaultAppState: AppState = {
       calibration_base_folder: "",
       dark_frame_pattern: "",
       bias_frame_pattern: "",
+      flat_frame_pattern: "",
+      dark_flat_frame_pattern: "",
+      file_patterns: {
+        light: "",
+        dark: "",
+        bias: "",
+        flat: "",
+        dark_flat: "",
+      },
+    },
+    calibration_tolerances: {
+      gain: 0,
+      offset: 0,
+      sub_length_percent: 1,
+      camera_temp: 2,
+    },
+    calibration_validity: {
+      dark_days: 180,
+      bias_days: 365,
+      flat_days: 0,
+      dark_flat_days: 180,
+      flat_same_train: true,
+    },
+    import_mode: ImportMode.COPY,
+    collision_handling: {
+      strategy: CollisionStrategy.SUFFIX,
+      pattern: "$$NAME$$_$$TIMESTAMP$$",
     },
-    locations: new Map<UUID, Location>(),
   },
   table_data: {
     sessions: [],
@@ -47,6 +78,7 @@ const defaultAppState: AppState = {
     flatteners: new Map<UUID, Flattener>(),
     filters: new Map<UUID, Filter>(),
   },
+  locations: new Map<UUID, Location>(),
   image_list: [],
   analytics: null,
 };
@@ -71,7 +103,10 @@ export function fetchAppState(setAppState: Dispatch<SetStateAction<AppState>>):
         local_config: LocalConfig;
         config: {
           folder_paths: FolderPaths;
-          locations: Record<UUID, Location>;
+          calibration_tolerances: CalibrationTolerances;
+          calibration_validity: CalibrationValidity;
+          import_mode: ImportMode;
+          collision_handling: CollisionHandling;
         };
         table_data: {
           sessions: Array<Omit<ImagingSession, 'date'> & { date: string }>;
@@ -84,6 +119,7 @@ export function fetchAppState(setAppState: Dispatch<SetStateAction<AppState>>):
           filters?: Record<UUID, RawEquipmentItem<Filter>>;
           flatteners?: Record<UUID, RawEquipmentItem<Flattener>>;
         };
+        locations: Record<UUID, Location>;
         image_list: GalleryImage[];
         analytics: Analytics | null;
       };
@@ -121,15 +157,18 @@ export function fetchAppState(setAppState: Dispatch<SetStateAction<AppState>>):
         date: new Date(session.date),
       }));
 
-      // Convert config.locations from a Record to a Map.
-      const rawLocations = responseData.config.locations;
+      // Convert locations from a Record to a Map.
+      const rawLocations = responseData.locations;
       const locationsMap = new Map<UUID, Location>(
         Object.entries(rawLocations).map(([id, location]) => [id as UUID, location as Location])
       );
 
       const fixedConfig: Config = {
         folder_paths: responseData.config.folder_paths,
-        locations: locationsMap,
+        calibration_tolerances: responseData.config.calibration_tolerances,
+        calibration_validity: responseData.config.calibration_validity,
+        import_mode: responseData.config.import_mode,
+        collision_handling: responseData.config.collision_handling,
       };
 
       let fixedAnalytics: Analytics | null = null;
@@ -154,7 +193,7 @@ export function fetchAppState(setAppState: Dispatch<SetStateAction<AppState>>):
         unit: toEnumUnit(responseData.local_config.unit),
       };
 
-      // Construct the final AppState with parsed dates, equipment Maps, and config.locations as a Map.
+      // Construct the final AppState with parsed dates, equipment Maps, and locations as a Map.
       const fixedAppState: AppState = {
         initialised: true,
         local_config: fixedLocalConfig,
@@ -163,6 +202,7 @@ export function fetchAppState(setAppState: Dispatch<SetStateAction<AppState>>):
           sessions,
           calibration: responseData.table_data.calibration,
         },
+        locations: locationsMap,
         image_list: responseData.image_list,
         analytics: fixedAnalytics,
         equipment_list: {
@@ -191,6 +231,19 @@ export default function StateProvider({ children }: { children: ReactNode }) {
   useEffect(() => {
     fetchAppState(setAppState);
     removeContextMenu();
+
+    // updates of older libraries run at startup, a failed one is tried again at the next start
+    invoke<string[]>('take_backfill_errors')
+      .then((errors) => {
+        errors.forEach((error) => {
+          toast({
+            variant: 'destructive',
+            title: 'Uh oh! Something went wrong.',
+            description: error,
+          });
+        });
+      })
+      .catch(() => {});
   }, []);
 
   useEffect(() => {
@@ -1,4 +1,6 @@
 export enum CalibrationType {
   DARK = 'DARK',
   BIAS = 'BIAS',
+  FLAT = 'FLAT',
+  DARKFLAT = 'DARKFLAT',
 }
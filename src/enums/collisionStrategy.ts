@@ -0,0 +1,6 @@
+export enum CollisionStrategy {
+  SKIP = "SKIP",
+  SUFFIX = "SUFFIX",
+  PATTERN = "PATTERN",
+  ABORT = "ABORT"
+}
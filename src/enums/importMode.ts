@@ -0,0 +1,6 @@
+export enum ImportMode {
+  COPY = "COPY",
+  MOVE = "MOVE",
+  HARDLINK = "HARDLINK",
+  REFLINK = "REFLINK"
+}
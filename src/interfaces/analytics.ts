@@ -6,6 +6,7 @@ export interface Analytics {
 export interface SessionsChartData {
   date: Date;
   seconds: number;
+  dark_seconds: number | null;
 }
 
 interface InfoCards {
@@ -1,11 +1,47 @@
 import { CalibrationType } from '@/enums/calibrationType';
 import { UUID } from 'crypto';
+import { FolderPaths } from '@/interfaces/state';
+
+export interface FileError {
+  path: string;
+  reason: string;
+}
+
+export interface DuplicateFrame {
+  path: string;
+  existing: string;
+  matched_by: 'HASH' | 'NAMEANDDATE';
+  session_id: UUID | null;
+  owner: string;
+}
+
+export interface AnalyzedLightFrames {
+  total_subs: number;
+  missing: string[];
+  errors: FileError[];
+  duplicates: DuplicateFrame[];
+}
+
+export interface CalibrationFrameGroup {
+  calibration_type: CalibrationType;
+  gain?: number;
+  sub_length?: number;
+  camera_temp?: number;
+  binning?: number;
+  frames: string[];
+}
 
 export interface AnalyzedCalibrationFrames {
   calibration_type: CalibrationType;
   gain: number;
   sub_length: number;
+  camera_temp?: number;
   total_subs: number;
+  groups: CalibrationFrameGroup[];
+  split: boolean;
+  unreadable: FileError[];
+  errors: FileError[];
+  duplicates: DuplicateFrame[];
   message: string;
 }
 
@@ -25,3 +61,161 @@ export interface DarkFrame extends CalibrationFrame {
 }
 
 export interface BiasFrame extends CalibrationFrame {}
+
+export interface FlatFrame extends CalibrationFrame {
+  in_imaging_session: boolean,
+}
+
+export interface DarkFlatFrame extends CalibrationFrame {
+  camera_temp: number | null,
+  sub_length: number | null,
+  in_imaging_session: boolean,
+}
+
+export interface MatchCheck {
+  criterion: string,
+  passed: boolean,
+  reason: string,
+}
+
+export interface CalibrationMatch {
+  id: UUID,
+  calibration_type: CalibrationType,
+  camera: string,
+  gain: number,
+  sub_length: number | null,
+  camera_temp: number | null,
+  total_subs: number,
+  date: string | null,
+  matches: boolean,
+  score: number,
+  checks: MatchCheck[],
+}
+
+export interface CalibrationMatches {
+  dark_frames: CalibrationMatch[],
+  bias_frames: CalibrationMatch[],
+  flat_frames: CalibrationMatch[],
+  dark_flat_frames: CalibrationMatch[],
+}
+
+export interface MissingDarkFrame {
+  gain: number,
+  sub_length: number,
+  camera_temp: number | null,
+  sessions: UUID[],
+}
+
+export interface CameraDarkCoverage {
+  camera_id: UUID,
+  camera: string,
+  missing: MissingDarkFrame[],
+}
+
+export interface UnusedDarkFrame {
+  id: UUID,
+  camera: string,
+  gain: number,
+  sub_length: number,
+  camera_temp: number,
+  total_subs: number,
+}
+
+export interface DarkCoverageReport {
+  cameras: CameraDarkCoverage[],
+  unused: UnusedDarkFrame[],
+}
+
+export interface Twilight {
+  dusk: string | null,
+  dawn: string | null,
+}
+
+export interface NightTimes {
+  night_of: string,
+  sun: Twilight,
+  civil: Twilight,
+  nautical: Twilight,
+  astronomical: Twilight,
+  dark_hours: number,
+}
+
+export interface AltitudePoint {
+  date: string,
+  altitude: number,
+  azimuth: number,
+}
+
+export interface TargetVisibility {
+  rise: string | null,
+  transit: string,
+  set: string | null,
+  max_altitude: number,
+  observable_hours: number,
+  altitude_curve: AltitudePoint[],
+}
+
+export interface CatalogEntry {
+  id: string,
+  names: string[],
+  object_type: string,
+  ra: number,
+  dec: number,
+  size: [number, number] | null,
+  magnitude: number | null,
+}
+
+export interface IntegrityReport {
+  verified: number,
+  recorded: number,
+  missing: string[],
+  corrupted: string[],
+  unreadable: string[],
+}
+
+export interface CsvImportRow {
+  line: number,
+  date: string,
+  target: string,
+  total_subs: number,
+}
+
+export interface CsvRowError {
+  line: number,
+  reason: string,
+}
+
+export interface MissingEntry {
+  column: string,
+  name: string,
+  id: UUID,
+}
+
+export interface CsvImportPreview {
+  total_rows: number,
+  sessions: CsvImportRow[],
+  errors: CsvRowError[],
+  missing: MissingEntry[],
+  imported: number,
+}
+
+export interface FolderMove {
+  id: UUID,
+  kind: 'IMAGINGSESSION' | 'DARK' | 'BIAS' | 'FLAT' | 'DARKFLAT',
+  old_dir: string,
+  new_dir: string,
+  state: 'PENDING' | 'STAGED' | 'MOVED',
+}
+
+export interface FolderCollision {
+  new_dir: string,
+  old_dirs: string[],
+  exists: boolean,
+}
+
+export interface RelayoutPlan {
+  folder_paths: FolderPaths,
+  moves: FolderMove[],
+  collisions: FolderCollision[],
+  missing: string[],
+}
@@ -36,8 +36,11 @@ export interface ImagingSessionWeather {
 export interface ImagingSessionCalibration {
   dark_frame_list_id: UUID | undefined,
   bias_frame_list_id: UUID | undefined,
+  flat_frame_list_id: UUID | undefined,
   flat_frames_to_classify: string[],
   dark_frames_to_classify: string[],
+  dark_flat_frame_list_id: UUID | undefined,
+  dark_flat_frames_to_classify: string[],
 }
 
 export interface ImagingSessionEdit {
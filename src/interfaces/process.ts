@@ -1,4 +1,17 @@
 import { UUID } from 'crypto';
+import { ImportMode } from '@/enums/importMode';
+import { CollisionStrategy } from '@/enums/collisionStrategy';
+
+export interface FileTransfer {
+  file: string;
+  mode: ImportMode;
+}
+
+export interface FileCollision {
+  file: string;
+  destination: string | null;
+  strategy: CollisionStrategy;
+}
 
 export interface Process {
   id: UUID;
@@ -8,4 +21,6 @@ export interface Process {
   step: number | undefined;
   max: number | undefined;
   error: string | null;
+  transfer: FileTransfer | null;
+  collisions: FileCollision[];
 }
@@ -9,6 +9,8 @@ import {
 } from '@/interfaces/equipment';
 import { Analytics } from '@/interfaces/analytics';
 import { UnitSystem } from '@/enums/unitSystem';
+import { ImportMode } from '@/enums/importMode';
+import { CollisionStrategy } from '@/enums/collisionStrategy';
 
 export interface AppState {
   initialised: boolean;
@@ -16,6 +18,7 @@ export interface AppState {
   config: Config;
   table_data: TableData;
   equipment_list: EquipmentList;
+  locations: Map<UUID, Location>;
   image_list: GalleryImage[];
   analytics: Analytics | null;
 }
@@ -28,7 +31,15 @@ export interface LocalConfig {
 
 export interface Config {
   folder_paths: FolderPaths,
-  locations: Map<UUID, Location>,
+  calibration_tolerances: CalibrationTolerances,
+  calibration_validity: CalibrationValidity,
+  import_mode: ImportMode,
+  collision_handling: CollisionHandling,
+}
+
+export interface CollisionHandling {
+  strategy: CollisionStrategy,
+  pattern: string,
 }
 
 export interface FolderPaths {
@@ -38,15 +49,52 @@ export interface FolderPaths {
   calibration_base_folder: string;
   dark_frame_pattern: string;
   bias_frame_pattern: string;
+  flat_frame_pattern: string;
+  dark_flat_frame_pattern: string;
+
+  file_patterns: FilePatterns;
+}
+
+// empty patterns keep the original file names
+export interface FilePatterns {
+  light: string;
+  dark: string;
+  bias: string;
+  flat: string;
+  dark_flat: string;
+}
+
+export interface CalibrationTolerances {
+  gain: number;
+  offset: number;
+  sub_length_percent: number;
+  camera_temp: number;
+}
+
+export interface CalibrationValidity {
+  dark_days: number | null;
+  bias_days: number | null;
+  flat_days: number | null;
+  dark_flat_days: number | null;
+  flat_same_train: boolean;
 }
 
 export interface Location {
   id: UUID,
   name: string,
-  x: number,
-  y: number,
-  height: number,
+  latitude: number,
+  longitude: number,
+  elevation: number,
+  timezone: string,
   bortle: number,
+  sqm: number | null,
+  horizon: HorizonPoint[],
+  needs_review: boolean,
+}
+
+export interface HorizonPoint {
+  azimuth: number,
+  altitude: number,
 }
 
 export interface TableData {
@@ -58,6 +106,7 @@ export interface ImagingSession {
   id: UUID;
   date: Date;
   target: string;
+  catalog_id: string | undefined;
   location_name: string | undefined;
   location_bortle: number | undefined;
   sub_length: number;
@@ -70,11 +119,14 @@ export interface ImagingSession {
   average_seeing: number | undefined;
   average_cloud_cover: number | undefined;
   average_moon: number;
+  moon_altitude: number | undefined;
+  moon_separation: number | undefined;
   telescope: string;
   flattener: string;
   mount: string;
   camera: string;
   notes: string | undefined;
+  calibration_warnings: string[];
 }
 
 export interface CalibrationFrame {
@@ -85,6 +137,8 @@ export interface CalibrationFrame {
   sub_length: number | undefined;
   camera_temp: number | undefined;
   total_subs: number;
+  capture_date: string | null;
+  expired: boolean;
 }
 
 export interface EquipmentList {